@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+
+use iced::widget::markdown;
+
+use crate::{folding, front_matter};
+
+#[derive(Debug, Clone)]
+pub struct Block {
+  pub lines: Range<usize>,
+  pub items: Vec<markdown::Item>,
+  pub images: Vec<String>,
+  pub tasks: Vec<usize>,
+  hash: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Contents {
+  items: Vec<markdown::Item>,
+  images: Vec<String>,
+  tasks: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Code {
+  pub lines: Range<usize>,
+  pub is_nested: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+  pub revision: u64,
+  text: String,
+  previous: Vec<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parsed {
+  pub revision: u64,
+  layout: Vec<(Range<usize>, u64)>,
+  items: Vec<(usize, Contents)>,
+}
+
+type Layout = (Vec<(Range<usize>, u64)>, Vec<(usize, String)>);
+
+pub fn request(revision: u64, text: String, previous: &[Block]) -> Request {
+  Request {
+    revision,
+    text,
+    previous: previous.iter().map(|block| block.hash).collect(),
+  }
+}
+
+pub fn parse(request: Request) -> Parsed {
+  let (layout, sources) = layout(&request.text, &request.previous);
+  let items = sources
+    .into_iter()
+    .map(|(index, source)| (index, contents(&source)))
+    .collect();
+
+  Parsed {
+    revision: request.revision,
+    layout,
+    items,
+  }
+}
+
+fn layout(text: &str, previous: &[u64]) -> Layout {
+  let lines: Vec<&str> = text.lines().collect();
+
+  let definitions: String = lines
+    .iter()
+    .filter(|line| is_definition(line))
+    .map(|line| format!("{line}\n"))
+    .collect();
+
+  let mut reusable: HashMap<u64, usize> = HashMap::new();
+
+  for hash in previous {
+    *reusable.entry(*hash).or_default() += 1;
+  }
+
+  let mut layout = Vec::new();
+  let mut sources = Vec::new();
+
+  let start = front_matter::end(&lines).unwrap_or(0);
+
+  for range in split(&lines[start..]) {
+    let range = range.start + start..range.end + start;
+    let block = &lines[range.clone()];
+    let references = (!definitions.is_empty() && block.iter().any(|line| line.contains('[')))
+      .then_some(definitions.as_str());
+
+    let mut hasher = DefaultHasher::new();
+    block.hash(&mut hasher);
+    references.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    match reusable.get_mut(&hash) {
+      Some(count) if *count > 0 => *count -= 1,
+      _ => {
+        let mut source = block.join("\n");
+
+        if let Some(references) = references {
+          source.push_str("\n\n");
+          source.push_str(references);
+        }
+
+        sources.push((layout.len(), source));
+      }
+    }
+
+    layout.push((range, hash));
+  }
+
+  (layout, sources)
+}
+
+pub async fn parse_in_background(request: Request) -> Result<Parsed, String> {
+  tokio::task::spawn_blocking(move || parse(request))
+    .await
+    .map_err(|error| error.to_string())
+}
+
+pub fn assemble(previous: &mut Vec<Block>, parsed: Parsed) -> Vec<Block> {
+  let mut reusable: HashMap<u64, Vec<Contents>> = HashMap::new();
+
+  for block in previous.drain(..).rev() {
+    reusable.entry(block.hash).or_default().push(Contents {
+      items: block.items,
+      images: block.images,
+      tasks: block.tasks,
+    });
+  }
+
+  let mut contents: Vec<Option<Contents>> = vec![None; parsed.layout.len()];
+
+  for (index, parsed) in parsed.items {
+    contents[index] = Some(parsed);
+  }
+
+  parsed
+    .layout
+    .into_iter()
+    .zip(contents)
+    .filter_map(|((lines, hash), contents)| {
+      let contents = contents
+        .or_else(|| reusable.get_mut(&hash).and_then(Vec::pop))
+        .unwrap_or_default();
+
+      (!contents.items.is_empty()).then_some(Block {
+        lines,
+        items: contents.items,
+        images: contents.images,
+        tasks: contents.tasks,
+        hash,
+      })
+    })
+    .collect()
+}
+
+fn contents(source: &str) -> Contents {
+  let mut contents = Contents {
+    items: markdown::parse(source).collect(),
+    ..Contents::default()
+  };
+
+  if !source.contains('[') {
+    return contents;
+  }
+
+  let parser = pulldown_cmark::Parser::new_ext(source, pulldown_cmark::Options::ENABLE_TASKLISTS);
+
+  for (event, range) in parser.into_offset_iter() {
+    match event {
+      pulldown_cmark::Event::Start(pulldown_cmark::Tag::Image { dest_url, .. }) => {
+        contents.images.push(dest_url.into_string());
+      }
+      pulldown_cmark::Event::TaskListMarker(_) => {
+        contents
+          .tasks
+          .push(source[..range.start].matches('\n').count());
+      }
+      _ => {}
+    }
+  }
+
+  contents
+}
+
+pub fn code_blocks<S: AsRef<str>>(lines: &[S]) -> Vec<Code> {
+  let mut starts = Vec::with_capacity(lines.len());
+  let mut text = String::new();
+
+  for line in lines {
+    starts.push(text.len());
+    text.push_str(line.as_ref());
+    text.push('\n');
+  }
+
+  let line_at = |offset: usize| starts.partition_point(|start| *start <= offset) - 1;
+  let options = pulldown_cmark::Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+    | pulldown_cmark::Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+
+  let mut blocks = Vec::new();
+  let mut depth = 0;
+
+  for (event, range) in pulldown_cmark::Parser::new_ext(&text, options).into_offset_iter() {
+    match event {
+      pulldown_cmark::Event::Start(pulldown_cmark::Tag::CodeBlock(_)) if !range.is_empty() => {
+        blocks.push(Code {
+          lines: line_at(range.start)..line_at(range.end - 1) + 1,
+          is_nested: depth > 0,
+        });
+      }
+      pulldown_cmark::Event::Start(
+        pulldown_cmark::Tag::BlockQuote(_)
+        | pulldown_cmark::Tag::List(_)
+        | pulldown_cmark::Tag::FootnoteDefinition(_),
+      ) => depth += 1,
+      pulldown_cmark::Event::End(
+        pulldown_cmark::TagEnd::BlockQuote(_)
+        | pulldown_cmark::TagEnd::List(_)
+        | pulldown_cmark::TagEnd::FootnoteDefinition,
+      ) => depth -= 1,
+      _ => {}
+    }
+  }
+
+  blocks
+}
+
+pub fn code_lines<S: AsRef<str>>(lines: &[S]) -> Vec<bool> {
+  let mut is_code = vec![false; lines.len()];
+
+  for block in code_blocks(lines) {
+    is_code[block.lines].fill(true);
+  }
+
+  is_code
+}
+
+pub fn code_shape(line: &str) -> (usize, Option<char>, bool) {
+  let content = line.trim_start();
+
+  (
+    line.len() - content.len(),
+    content.chars().next(),
+    line.contains(['`', '~']),
+  )
+}
+
+pub fn split(lines: &[&str]) -> Vec<Range<usize>> {
+  let mut blocks = Vec::new();
+  let mut start: Option<usize> = None;
+  let mut code = code_blocks(lines).into_iter().peekable();
+  let mut index = 0;
+
+  while index < lines.len() {
+    let line = lines[index];
+
+    if let Some(block) = code.next_if(|block| block.lines.start == index) {
+      while code
+        .next_if(|nested| nested.lines.end <= block.lines.end)
+        .is_some()
+      {}
+
+      if !block.is_nested || start.is_none() {
+        blocks.extend(start.take().map(|first| first..index));
+        start = Some(index);
+      }
+
+      index = block.lines.end;
+
+      if !block.is_nested {
+        blocks.extend(start.take().map(|first| first..index));
+      }
+
+      continue;
+    }
+
+    if line.trim().is_empty() {
+      if let Some(first) = start
+        && !continues(lines, first, index)
+      {
+        blocks.push(first..index);
+        start = None;
+      }
+
+      index += 1;
+      continue;
+    }
+
+    if start.is_none_or(|_| !is_indented(line))
+      && folding::heading_level(line.trim_start()).is_some()
+    {
+      blocks.extend(start.take().map(|first| first..index));
+      blocks.push(index..index + 1);
+      index += 1;
+      continue;
+    }
+
+    start.get_or_insert(index);
+    index += 1;
+  }
+
+  blocks.extend(start.map(|first| first..lines.len()));
+  blocks
+}
+
+fn continues(lines: &[&str], first: usize, blank: usize) -> bool {
+  let Some(next) = lines[blank..].iter().find(|line| !line.trim().is_empty()) else {
+    return false;
+  };
+
+  if is_list_item(lines[first]) {
+    is_indented(next) || is_list_item(next)
+  } else if is_code_indented(lines[first]) {
+    is_code_indented(next)
+  } else {
+    false
+  }
+}
+
+fn is_list_item(line: &str) -> bool {
+  let trimmed = line.trim_start();
+
+  if trimmed.starts_with(['-', '*', '+']) {
+    return trimmed[1..].is_empty() || trimmed[1..].starts_with([' ', '\t']);
+  }
+
+  let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+  let rest = &trimmed[digits..];
+
+  (1..=9).contains(&digits)
+    && rest.starts_with(['.', ')'])
+    && (rest[1..].is_empty() || rest[1..].starts_with([' ', '\t']))
+}
+
+fn is_indented(line: &str) -> bool {
+  line.starts_with([' ', '\t'])
+}
+
+fn is_code_indented(line: &str) -> bool {
+  line.starts_with("    ") || line.starts_with('\t')
+}
+
+fn is_definition(line: &str) -> bool {
+  let trimmed = line.trim_start();
+
+  trimmed.starts_with('[')
+    && !trimmed.starts_with("[^")
+    && trimmed
+      .find("]:")
+      .is_some_and(|end| end > 1 && !trimmed[1..end].contains(']'))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+  }
+
+  fn blocks(text: &str) -> Vec<Block> {
+    assemble(&mut Vec::new(), parse(request(1, text.to_owned(), &[])))
+  }
+
+  fn sources(text: &str, previous: &[Block]) -> Vec<String> {
+    let hashes: Vec<u64> = previous.iter().map(|block| block.hash).collect();
+
+    layout(text, &hashes)
+      .1
+      .into_iter()
+      .map(|(_, source)| source)
+      .collect()
+  }
+
+  #[test]
+  fn splits_headings_paragraphs_and_fences() {
+    let text = "# Title\nfirst\nparagraph\n\n```\n# not a heading\n\n```\n- item\n\n  ```\n  nested\n  ```\n";
+    let lines = lines(text);
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+
+    assert_eq!(split(&lines), [0..1, 1..3, 4..8, 8..13]);
+  }
+
+  #[test]
+  fn marks_code_blocks_inside_containers_as_nested() {
+    let lines = lines("    indented\n\n> ```\n> quoted\n> ```\n");
+
+    assert_eq!(
+      code_blocks(&lines),
+      [
+        Code {
+          lines: 0..1,
+          is_nested: false,
+        },
+        Code {
+          lines: 2..5,
+          is_nested: true,
+        },
+      ],
+    );
+  }
+
+  #[test]
+  fn skips_front_matter() {
+    let blocks = blocks("---\ntitle: x\n---\n# Heading\n");
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].lines, 3..4);
+  }
+
+  #[test]
+  fn reparses_only_changed_blocks() {
+    let mut previous = blocks("# One\n\nfirst\n\nsecond\n");
+    let edited = "# One\n\nnew\n\nfirst\n\nsecond!\n";
+
+    assert_eq!(sources(edited, &previous), ["new", "second!"]);
+
+    let parsed = parse(request(2, edited.to_owned(), &previous));
+    let blocks = assemble(&mut previous, parsed);
+    let ranges: Vec<Range<usize>> = blocks.iter().map(|block| block.lines.clone()).collect();
+
+    assert_eq!(ranges, [0..1, 2..3, 4..5, 6..7]);
+  }
+
+  #[test]
+  fn reparses_references_when_definitions_change() {
+    let previous = blocks("see [link]\n\n[link]: https://a.example\n");
+    let edited = "see [link]\n\n[link]: https://b.example\n";
+
+    assert_eq!(sources(edited, &previous).len(), 2);
+  }
+}
@@ -0,0 +1,170 @@
+use iced::widget::text_editor::{Content, Position};
+
+const PAIRS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: &[char] = &['"', '`'];
+const MAX_SCAN_LINES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closer {
+  pub line: usize,
+  pub after: usize,
+  pub close: char,
+}
+
+impl Closer {
+  pub fn column(&self, text: &str) -> Option<usize> {
+    let column = text.len().checked_sub(self.after + self.close.len_utf8())?;
+
+    (text.is_char_boundary(column) && text[column..].starts_with(self.close)).then_some(column)
+  }
+}
+
+pub fn closing(c: char) -> Option<char> {
+  PAIRS
+    .iter()
+    .find(|(open, _)| *open == c)
+    .map(|(_, close)| *close)
+    .or_else(|| QUOTES.contains(&c).then_some(c))
+}
+
+pub fn is_closing(c: char) -> bool {
+  PAIRS.iter().any(|(_, close)| *close == c) || QUOTES.contains(&c)
+}
+
+pub fn should_close(c: char, previous: Option<char>, next: Option<char>) -> bool {
+  let is_next_free = next.is_none_or(|n| n.is_whitespace() || is_closing(n));
+
+  if QUOTES.contains(&c) {
+    is_next_free && previous.is_none_or(|p| !p.is_alphanumeric())
+  } else {
+    is_next_free
+  }
+}
+
+pub fn matching(content: &Content, position: Position) -> Option<(Position, Position)> {
+  let line = content.line(position.line)?;
+
+  let after = line.text[position.column..].chars().next();
+  let before = line.text[..position.column].chars().next_back();
+
+  let (bracket, column) = match (after, before) {
+    (Some(c), _) if is_bracket(c) => (c, position.column),
+    (_, Some(c)) if is_bracket(c) => (c, position.column - c.len_utf8()),
+    _ => return None,
+  };
+
+  let at = Position {
+    line: position.line,
+    column,
+  };
+
+  let other = match PAIRS.iter().find(|(open, _)| *open == bracket) {
+    Some(&(open, close)) => scan_forward(content, at, open, close),
+    None => {
+      let &(open, close) = PAIRS.iter().find(|(_, close)| *close == bracket)?;
+      scan_backward(content, at, open, close)
+    }
+  }?;
+
+  Some((at, other))
+}
+
+fn is_bracket(c: char) -> bool {
+  PAIRS.iter().any(|(open, close)| *open == c || *close == c)
+}
+
+fn scan_forward(content: &Content, from: Position, open: char, close: char) -> Option<Position> {
+  let mut depth = 0usize;
+  let last = (from.line + MAX_SCAN_LINES).min(content.line_count());
+
+  for line in from.line..last {
+    let text = content.line(line)?.text;
+    let start = if line == from.line { from.column } else { 0 };
+
+    for (index, c) in text[start..].char_indices() {
+      if c == open {
+        depth += 1;
+      } else if c == close {
+        depth -= 1;
+
+        if depth == 0 {
+          return Some(Position {
+            line,
+            column: start + index,
+          });
+        }
+      }
+    }
+  }
+
+  None
+}
+
+fn scan_backward(content: &Content, from: Position, open: char, close: char) -> Option<Position> {
+  let mut depth = 0usize;
+  let first = from.line.saturating_sub(MAX_SCAN_LINES);
+
+  for line in (first..=from.line).rev() {
+    let text = content.line(line)?.text;
+    let end = if line == from.line {
+      from.column + close.len_utf8()
+    } else {
+      text.len()
+    };
+
+    for (index, c) in text[..end].char_indices().rev() {
+      if c == close {
+        depth += 1;
+      } else if c == open {
+        depth -= 1;
+
+        if depth == 0 {
+          return Some(Position {
+            line,
+            column: index,
+          });
+        }
+      }
+    }
+  }
+
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn closes_brackets_only_before_free_space() {
+    assert!(should_close('(', None, None));
+    assert!(should_close('(', Some('a'), Some(')')));
+    assert!(!should_close('(', None, Some('a')));
+    assert!(should_close('"', Some(' '), None));
+    assert!(!should_close('"', Some('a'), None));
+  }
+
+  #[test]
+  fn finds_closers_by_their_distance_from_the_line_end() {
+    let closer = Closer {
+      line: 0,
+      after: 1,
+      close: ')',
+    };
+
+    assert_eq!(closer.column("f(abc);"), Some(5));
+    assert_eq!(closer.column("f(abc]"), None);
+    assert_eq!(closer.column(""), None);
+  }
+
+  #[test]
+  fn matches_nested_brackets_in_both_directions() {
+    let content = Content::with_text("a(b[c]\n(d))");
+    let at = |line, column| Position { line, column };
+
+    assert_eq!(matching(&content, at(0, 1)), Some((at(0, 1), at(1, 3))));
+    assert_eq!(matching(&content, at(1, 4)), Some((at(1, 3), at(0, 1))));
+    assert_eq!(matching(&content, at(0, 6)), Some((at(0, 5), at(0, 3))));
+    assert_eq!(matching(&content, at(0, 0)), None);
+  }
+}
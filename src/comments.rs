@@ -0,0 +1,254 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+use crate::indent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syntax {
+  pub line: Option<&'static str>,
+  pub block: Option<(&'static str, &'static str)>,
+}
+
+const C_LIKE: Syntax = Syntax {
+  line: Some("//"),
+  block: Some(("/*", "*/")),
+};
+
+const HASH: Syntax = Syntax {
+  line: Some("#"),
+  block: None,
+};
+
+const MARKUP: Syntax = Syntax {
+  line: None,
+  block: Some(("<!--", "-->")),
+};
+
+pub fn for_path(path: Option<&Path>) -> Option<Syntax> {
+  let path = path?;
+  let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+
+  match name.as_str() {
+    ".gitignore" | ".gitattributes" | ".dockerignore" | ".env" | "dockerfile" | "containerfile"
+    | "makefile" => Some(HASH),
+    _ => for_extension(path.extension().and_then(OsStr::to_str)?),
+  }
+}
+
+fn for_extension(extension: &str) -> Option<Syntax> {
+  let extension = extension.to_ascii_lowercase();
+
+  let syntax = match extension.as_str() {
+    "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "cs" | "java" | "js" | "jsx" | "mjs" | "ts"
+    | "tsx" | "go" | "kt" | "swift" | "scala" | "dart" | "zig" | "jsonc" => C_LIKE,
+    "toml" | "yaml" | "yml" | "sh" | "bash" | "zsh" | "fish" | "py" | "rb" | "pl" | "r"
+    | "conf" | "cfg" | "env" | "dockerfile" | "mk" | "nix" => HASH,
+    "ps1" | "psm1" => Syntax {
+      line: Some("#"),
+      block: Some(("<#", "#>")),
+    },
+    "html" | "htm" | "xml" | "svg" | "xaml" | "csproj" | "vue" | "md" | "markdown" => MARKUP,
+    "css" => Syntax {
+      line: None,
+      block: Some(("/*", "*/")),
+    },
+    "scss" | "less" => C_LIKE,
+    "sql" => Syntax {
+      line: Some("--"),
+      block: Some(("/*", "*/")),
+    },
+    "lua" => Syntax {
+      line: Some("--"),
+      block: Some(("--[[", "]]")),
+    },
+    "hs" => Syntax {
+      line: Some("--"),
+      block: Some(("{-", "-}")),
+    },
+    "ini" => Syntax {
+      line: Some(";"),
+      block: None,
+    },
+    "bat" | "cmd" => Syntax {
+      line: Some("REM"),
+      block: None,
+    },
+    _ => return None,
+  };
+
+  Some(syntax)
+}
+
+pub fn toggle_lines(syntax: Syntax, lines: &[String]) -> Vec<String> {
+  match (syntax.line, syntax.block) {
+    (Some(token), _) => toggle_line_comments(token, lines),
+    (None, Some((open, close))) => toggle_wrapped_lines(open, close, lines),
+    (None, None) => lines.to_vec(),
+  }
+}
+
+fn toggle_line_comments(token: &str, lines: &[String]) -> Vec<String> {
+  let code: Vec<&String> = lines.iter().filter(|l| !l.trim().is_empty()).collect();
+
+  if code.is_empty() {
+    return lines.to_vec();
+  }
+
+  let is_commented = code.iter().all(|line| line.trim_start().starts_with(token));
+
+  if is_commented {
+    return lines
+      .iter()
+      .map(|line| {
+        let whitespace = indent::leading_whitespace(line);
+        let rest = &line[whitespace.len()..];
+
+        match rest.strip_prefix(token) {
+          Some(uncommented) => {
+            whitespace.to_owned() + uncommented.strip_prefix(' ').unwrap_or(uncommented)
+          }
+          None => line.clone(),
+        }
+      })
+      .collect();
+  }
+
+  let column = code
+    .iter()
+    .map(|line| indent::leading_whitespace(line).len())
+    .min()
+    .unwrap_or(0);
+
+  lines
+    .iter()
+    .map(|line| {
+      if line.trim().is_empty() {
+        line.clone()
+      } else {
+        format!("{}{token} {}", &line[..column], &line[column..])
+      }
+    })
+    .collect()
+}
+
+fn toggle_wrapped_lines(open: &str, close: &str, lines: &[String]) -> Vec<String> {
+  let Some(first) = lines.iter().position(|l| !l.trim().is_empty()) else {
+    return lines.to_vec();
+  };
+
+  let last = lines
+    .iter()
+    .rposition(|l| !l.trim().is_empty())
+    .unwrap_or(first);
+
+  let mut lines = lines.to_vec();
+
+  let whitespace = indent::leading_whitespace(&lines[first]).to_owned();
+  let is_commented = if first == last {
+    let trimmed = lines[first].trim();
+    trimmed.len() >= open.len() + close.len()
+      && trimmed.starts_with(open)
+      && trimmed.ends_with(close)
+  } else {
+    lines[first].trim_start().starts_with(open) && lines[last].trim_end().ends_with(close)
+  };
+
+  if is_commented && first == last {
+    let trimmed = lines[first].trim();
+    let inner = &trimmed[open.len()..trimmed.len() - close.len()];
+    let inner = inner.strip_prefix(' ').unwrap_or(inner);
+    lines[first] = whitespace + inner.strip_suffix(' ').unwrap_or(inner);
+  } else if is_commented {
+    let start = &lines[first][whitespace.len() + open.len()..];
+    lines[first] = whitespace + start.strip_prefix(' ').unwrap_or(start);
+
+    let trimmed = lines[last].trim_end();
+    let end = &trimmed[..trimmed.len() - close.len()];
+    lines[last] = end.strip_suffix(' ').unwrap_or(end).to_owned();
+  } else {
+    lines[first] = format!("{whitespace}{open} {}", &lines[first][whitespace.len()..]);
+    lines[last] = format!("{} {close}", lines[last].trim_end());
+  }
+
+  lines
+}
+
+pub fn toggle_block(open: &str, close: &str, text: &str) -> String {
+  let trimmed = text.trim();
+
+  if let Some(inner) = trimmed
+    .strip_prefix(open)
+    .and_then(|rest| rest.strip_suffix(close))
+  {
+    let inner = inner.strip_prefix(' ').unwrap_or(inner);
+    let inner = inner.strip_suffix(' ').unwrap_or(inner);
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len();
+
+    return format!("{}{inner}{}", &text[..start], &text[end..]);
+  }
+
+  format!("{open} {text} {close}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+  }
+
+  #[test]
+  fn comments_lines_at_the_shallowest_indent() {
+    assert_eq!(
+      toggle_lines(C_LIKE, &lines(&["  a", "", "    b"])),
+      ["  // a", "", "  //   b"],
+    );
+  }
+
+  #[test]
+  fn uncomments_lines_when_all_are_commented() {
+    assert_eq!(
+      toggle_lines(HASH, &lines(&["  # a", "", "#b"])),
+      ["  a", "", "b"],
+    );
+  }
+
+  #[test]
+  fn wraps_and_unwraps_lines_without_a_line_token() {
+    let wrapped = toggle_lines(MARKUP, &lines(&["  <p>", "  </p>"]));
+
+    assert_eq!(wrapped, ["  <!-- <p>", "  </p> -->"]);
+    assert_eq!(toggle_lines(MARKUP, &wrapped), ["  <p>", "  </p>"]);
+  }
+
+  #[test]
+  fn unwraps_a_single_line() {
+    assert_eq!(toggle_lines(MARKUP, &lines(&["  <!-- a -->"])), ["  a"]);
+  }
+
+  #[test]
+  fn wraps_a_line_where_the_delimiters_overlap() {
+    let css = for_extension("css").unwrap();
+
+    assert_eq!(toggle_lines(MARKUP, &lines(&["<!-->"])), ["<!-- <!--> -->"]);
+    assert_eq!(toggle_lines(css, &lines(&["/*/"])), ["/* /*/ */"]);
+  }
+
+  #[test]
+  fn toggles_block_comments_around_a_selection() {
+    assert_eq!(toggle_block("/*", "*/", "a + b"), "/* a + b */");
+    assert_eq!(toggle_block("/*", "*/", " /* a + b */ "), " a + b ");
+    assert_eq!(toggle_block("/*", "*/", "/*/"), "/* /*/ */");
+  }
+
+  #[test]
+  fn picks_syntax_by_file_name_or_extension() {
+    assert_eq!(for_path(Some(Path::new("/repo/.gitignore"))), Some(HASH));
+    assert_eq!(for_path(Some(Path::new("Dockerfile"))), Some(HASH));
+    assert_eq!(for_path(Some(Path::new("main.RS"))), Some(C_LIKE));
+    assert_eq!(for_path(Some(Path::new("notes"))), None);
+    assert_eq!(for_path(None), None);
+  }
+}
@@ -0,0 +1,180 @@
+use iced::widget::text_editor::Position;
+
+use crate::cursors;
+use crate::file::File;
+
+const MIN_PREFIX_LENGTH: usize = 2;
+const MIN_WORD_LENGTH: usize = 3;
+const MAX_ITEMS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+  pub start: Position,
+  pub items: Vec<String>,
+  pub selected: usize,
+}
+
+impl Completion {
+  pub fn select_next(&mut self) {
+    self.selected = (self.selected + 1) % self.items.len();
+  }
+
+  pub fn select_previous(&mut self) {
+    self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+  }
+
+  pub fn selected_item(&self) -> &str {
+    &self.items[self.selected]
+  }
+}
+
+pub fn complete(file: &File, files: &[File], is_forced: bool) -> Option<Completion> {
+  let (start, prefix) = file.word_before_cursor()?;
+  let min_length = if is_forced { 1 } else { MIN_PREFIX_LENGTH };
+
+  if prefix.chars().count() < min_length {
+    return None;
+  }
+
+  if file.is_markdown() && is_in_code(file, file.content().cursor().position) {
+    return None;
+  }
+
+  let mut ranked: Vec<(i64, &str)> = files
+    .iter()
+    .flat_map(File::words)
+    .filter(|word| *word != prefix)
+    .filter_map(|word| Some((score(word, &prefix)?, word)))
+    .collect();
+
+  ranked.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| x.cmp(y)));
+  ranked.dedup();
+
+  let items: Vec<String> = ranked
+    .into_iter()
+    .take(MAX_ITEMS)
+    .map(|(_, word)| word.to_owned())
+    .collect();
+
+  (!items.is_empty()).then_some(Completion {
+    start,
+    items,
+    selected: 0,
+  })
+}
+
+pub fn words(text: &str) -> impl Iterator<Item = &str> {
+  text
+    .split(|c: char| !cursors::is_word_char(c))
+    .filter(|word| word.chars().count() >= MIN_WORD_LENGTH)
+    .filter(|word| !word.chars().all(|c| c.is_numeric()))
+}
+
+fn score(candidate: &str, query: &str) -> Option<i64> {
+  let candidate_chars: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+  let query_chars: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+
+  if candidate_chars.first() != query_chars.first() {
+    return None;
+  }
+
+  let mut score = 0i64;
+  let mut from = 0;
+  let mut previous: Option<usize> = None;
+
+  for c in &query_chars {
+    let index = from
+      + candidate_chars[from..]
+        .iter()
+        .position(|other| other == c)?;
+
+    score += match previous {
+      Some(previous) if index == previous + 1 => 5,
+      Some(previous) => -((index - previous) as i64),
+      None => 10,
+    };
+
+    previous = Some(index);
+    from = index + 1;
+  }
+
+  if candidate_chars.starts_with(&query_chars) {
+    score += 100;
+  }
+
+  if candidate.starts_with(query) {
+    score += 10;
+  }
+
+  Some(score - candidate_chars.len() as i64)
+}
+
+fn is_in_code(file: &File, position: Position) -> bool {
+  if file.is_code_line(position.line) {
+    return true;
+  }
+
+  file
+    .content()
+    .line(position.line)
+    .is_some_and(|line| line.text[..position.column].matches('`').count() % 2 == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use iced::widget::text_editor::{Action, Motion};
+
+  use super::*;
+
+  fn typed(text: &str, path: &str) -> File {
+    let mut file = File::from(text, Some(PathBuf::from(path)));
+    file.perform(Action::Move(Motion::DocumentEnd));
+    file.update_words();
+    file.update_code_lines();
+    file
+  }
+
+  #[test]
+  fn skips_short_and_numeric_words() {
+    let words: Vec<&str> = words("a an ant 1234 x_y_z café").collect();
+
+    assert_eq!(words, ["ant", "x_y_z", "café"]);
+  }
+
+  #[test]
+  fn ranks_prefix_matches_first() {
+    let file = typed("completion compute competent\nco", "notes.txt");
+    let completion = complete(&file, std::slice::from_ref(&file), false).unwrap();
+
+    assert_eq!(completion.start, Position { line: 1, column: 0 });
+    assert_eq!(completion.items, ["compute", "competent", "completion"]);
+  }
+
+  #[test]
+  fn needs_a_longer_prefix_unless_forced() {
+    let file = typed("compute\nc", "notes.txt");
+
+    assert!(complete(&file, std::slice::from_ref(&file), false).is_none());
+    assert!(complete(&file, std::slice::from_ref(&file), true).is_some());
+  }
+
+  #[test]
+  fn stays_quiet_in_markdown_code() {
+    let fenced = typed("compute\n```\nco", "notes.md");
+    let inline = typed("compute `co", "notes.md");
+    let prose = typed("compute co", "notes.md");
+
+    assert!(complete(&fenced, std::slice::from_ref(&fenced), false).is_none());
+    assert!(complete(&inline, std::slice::from_ref(&inline), false).is_none());
+    assert!(complete(&prose, std::slice::from_ref(&prose), false).is_some());
+  }
+
+  #[test]
+  fn matches_fuzzily() {
+    assert!(score("compute", "cmp").is_some());
+    assert!(score("compute", "xmp").is_none());
+    assert!(score("compute", "co") > score("recompute", "co"));
+  }
+}
@@ -3,11 +3,19 @@ use iced::{
   widget::{container, pick_list, row},
 };
 
-use crate::message::{FileAction, Message, ViewAction};
+use crate::message::{
+  EditAction, FileAction, LineAction, MacroItem, Message, TableAction, TransformAction, ViewAction,
+};
+use crate::snippets::Snippet;
 
 pub fn view(
   selected_file_action: Option<FileAction>,
+  selected_edit_action: Option<EditAction>,
+  selected_line_action: Option<LineAction>,
+  selected_transform_action: Option<TransformAction>,
   selected_view_action: Option<ViewAction>,
+  snippets: Vec<Snippet>,
+  macro_items: Vec<MacroItem>,
 ) -> Element<'static, Message> {
   let pick_list_padding = Padding {
     left: 4.0,
@@ -25,6 +33,42 @@ pub fn view(
   .style(pick_list_style)
   .placeholder("File");
 
+  let edit_menu = pick_list(
+    EditAction::ALL,
+    selected_edit_action,
+    Message::EditActionSelected,
+  )
+  .padding(pick_list_padding)
+  .style(pick_list_style)
+  .placeholder("Edit");
+
+  let line_menu = pick_list(
+    LineAction::ALL,
+    selected_line_action,
+    Message::LineActionSelected,
+  )
+  .padding(pick_list_padding)
+  .style(pick_list_style)
+  .placeholder("Line");
+
+  let transform_menu = pick_list(
+    TransformAction::ALL,
+    selected_transform_action,
+    Message::TransformActionSelected,
+  )
+  .padding(pick_list_padding)
+  .style(pick_list_style)
+  .placeholder("Transform");
+
+  let table_menu = pick_list(
+    TableAction::ALL,
+    None::<TableAction>,
+    Message::TableActionSelected,
+  )
+  .padding(pick_list_padding)
+  .style(pick_list_style)
+  .placeholder("Table");
+
   let view_menu = pick_list(
     ViewAction::ALL,
     selected_view_action,
@@ -34,18 +78,42 @@ pub fn view(
   .style(pick_list_style)
   .placeholder("View");
 
-  container(row![file_menu, view_menu].spacing(10))
-    .style(|theme: &Theme| {
-      let base = container::Style::default();
-
-      container::Style {
-        background: container::primary(theme).background,
-        ..base
-      }
-    })
-    .padding(5)
-    .width(Length::Fill)
-    .into()
+  let snippet_menu = pick_list(snippets, None::<Snippet>, Message::SnippetSelected)
+    .padding(pick_list_padding)
+    .style(pick_list_style)
+    .placeholder("Snippet");
+
+  let macro_menu = pick_list(macro_items, None::<MacroItem>, |item| {
+    Message::MacroActionSelected(item.action)
+  })
+  .padding(pick_list_padding)
+  .style(pick_list_style)
+  .placeholder("Macro");
+
+  container(
+    row![
+      file_menu,
+      edit_menu,
+      line_menu,
+      transform_menu,
+      table_menu,
+      view_menu,
+      snippet_menu,
+      macro_menu
+    ]
+    .spacing(10),
+  )
+  .style(|theme: &Theme| {
+    let base = container::Style::default();
+
+    container::Style {
+      background: container::primary(theme).background,
+      ..base
+    }
+  })
+  .padding(5)
+  .width(Length::Fill)
+  .into()
 }
 
 fn pick_list_style(theme: &Theme, status: pick_list::Status) -> pick_list::Style {
@@ -0,0 +1,43 @@
+use iced::widget::{container, text};
+use iced::{Element, Theme};
+
+use crate::file::File;
+use crate::message::Message;
+use crate::{constants, wrapping};
+
+const CARET: char = '▏';
+
+pub fn view(file: &File, font_size: u32, wrap_columns: Option<usize>) -> Element<'_, Message> {
+  let columns = wrap_columns.unwrap_or(usize::MAX);
+  let mut rows: Vec<String> = Vec::new();
+
+  for position in file.trailing_carets() {
+    let (row, column) = wrapping::locate(file.content(), position, columns, file.tab_width());
+
+    if rows.len() <= row {
+      rows.resize(row + 1, String::new());
+    }
+
+    let line = &mut rows[row];
+    let width = line.chars().count();
+
+    if width <= column {
+      line.push_str(&" ".repeat(column - width));
+      line.push(CARET);
+    }
+  }
+
+  container(
+    text(rows.join("\n"))
+      .size(font_size)
+      .line_height(constants::LINE_HEIGHT)
+      .shaping(text::Shaping::Advanced)
+      .wrapping(text::Wrapping::None)
+      .style(|theme: &Theme| text::Style {
+        color: Some(theme.palette().danger),
+      }),
+  )
+  .padding(constants::EDITOR_PADDING)
+  .clip(true)
+  .into()
+}
@@ -0,0 +1,29 @@
+use iced::Element;
+
+use crate::completion::Completion;
+use crate::components::popup;
+use crate::file::File;
+use crate::message::{CompletionAction, Message};
+
+pub fn view<'a>(
+  file: &'a File,
+  completion: &'a Completion,
+  font_size: u32,
+  wrap_columns: Option<usize>,
+) -> Element<'a, Message> {
+  let items = completion
+    .items
+    .iter()
+    .enumerate()
+    .map(|(index, item)| {
+      popup::item(
+        item.as_str(),
+        font_size,
+        index == completion.selected,
+        Some(Message::Completion(CompletionAction::Pick(index))),
+      )
+    })
+    .collect();
+
+  popup::view(file, completion.start, font_size, wrap_columns, items)
+}
@@ -1,105 +1,232 @@
+use std::sync::Arc;
+
 use iced::{
   Background, Element, Length, Theme,
   border::{self},
-  highlighter,
-  widget::{container, markdown, responsive, row, scrollable, text::Wrapping, text_editor},
+  mouse,
+  widget::{
+    Stack, container, mouse_area, responsive, row, rule, scrollable, space, text::Wrapping,
+    text_editor,
+  },
+};
+
+use crate::components::{carets, completion, gutter, invisibles, preview, spelling};
+use crate::{
+  completion::Completion,
+  constants,
+  file::File,
+  highlighter, images, key_bindings,
+  message::{Message, Pane, Pointer, SpellingAction},
+  spelling::Menu,
+  state::Mode,
+  wrapping,
 };
 
-use crate::{constants, file::File, message::Message, state::Mode};
+pub const ID: &str = "editor";
+
+#[derive(Debug, Clone, Copy)]
+pub enum Popup<'a> {
+  Completion(&'a Completion),
+  Spelling(&'a Menu),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+  pub font_size: u32,
+  pub is_word_wrap_on: bool,
+  pub numbering: gutter::Numbering,
+  pub is_whitespace_visible: bool,
+  pub viewport: Option<gutter::Viewport>,
+}
 
 pub fn view<'a>(
   file: &'a File,
   mode: Mode,
-  font_size: u32,
-  is_word_wrap_on: bool,
+  settings: Settings,
+  popup: Option<Popup<'a>>,
+  images: &'a images::Cache,
 ) -> Element<'a, Message> {
+  let font_size = settings.font_size;
+  let create_editor = move || editor(file, settings, popup);
+
   match mode {
-    Mode::Edit => {
-      let wrapping = if is_word_wrap_on {
-        Wrapping::WordOrGlyph
-      } else {
-        Wrapping::None
-      };
-
-      let create_editor = move || {
-        text_editor(file.content())
-          .wrapping(wrapping)
-          .highlight(
-            file.extension().unwrap_or("txt"),
-            highlighter::Theme::Base16Ocean,
-          )
-          .padding(10)
-          .size(font_size)
-          .style(|theme: &Theme, status: text_editor::Status| {
-            let base = text_editor::default(theme, status);
-
-            text_editor::Style {
-              border: border::Border {
-                width: 0.0,
-                ..Default::default()
-              },
-              ..base
-            }
-          })
-          .on_action(Message::Edit)
-      };
-
-      let create_scrollable = |content: Element<'a, Message>| {
-        scrollable(content)
-          .auto_scroll(true)
-          .direction(scrollable::Direction::Both {
-            vertical: scrollable::Scrollbar::default(),
-            horizontal: scrollable::Scrollbar::default(),
-          })
-          .style(|theme: &Theme, status: scrollable::Status| {
-            let mut style = scrollable::default(theme, status);
-            style.horizontal_rail.background = Some(Background::Color(theme.palette().background));
-            style.horizontal_rail.scroller.background = Background::Color(theme.palette().primary);
-            style.vertical_rail.background = Some(Background::Color(theme.palette().background));
-            style.vertical_rail.scroller.background = Background::Color(theme.palette().primary);
-            style
-          })
-          .anchor_bottom()
-          .height(Length::Fill)
-      };
-
-      if is_word_wrap_on {
-        responsive(move |size| {
-          let editor = create_editor();
-          create_scrollable(container(editor).width(size.width).into()).into()
-        })
-        .into()
-      } else {
-        let editor = create_editor();
-        create_scrollable(container(editor).height(Length::Fill).into())
-          .width(Length::Fill)
-          .into()
+    Mode::Edit => create_editor(),
+    Mode::Preview => preview::view(file, font_size, images),
+    Mode::Split(ratio) => responsive(move |size| {
+      let divider = mouse_area(rule::vertical(constants::DIVIDER_WIDTH))
+        .on_press(Message::SplitDividerPressed)
+        .interaction(mouse::Interaction::ResizingHorizontally);
+
+      let editor = mouse_area(create_editor()).on_enter(Message::PaneHovered(Pane::Editor));
+      let preview = mouse_area(preview::view(file, font_size, images))
+        .on_enter(Message::PaneHovered(Pane::Preview));
+
+      mouse_area(row![
+        container(editor).width((size.width - constants::DIVIDER_WIDTH) * ratio),
+        divider,
+        preview
+      ])
+      .on_move(move |point| Message::SplitDividerMoved(point.x / size.width))
+      .on_release(Message::SplitDividerReleased)
+      .into()
+    })
+    .into(),
+  }
+}
+
+fn editor<'a>(
+  file: &'a File,
+  settings: Settings,
+  popup: Option<Popup<'a>>,
+) -> Element<'a, Message> {
+  let Settings {
+    font_size,
+    is_word_wrap_on,
+    numbering,
+    is_whitespace_visible,
+    viewport,
+  } = settings;
+
+  let wrapping = if is_word_wrap_on {
+    Wrapping::WordOrGlyph
+  } else {
+    Wrapping::None
+  };
+
+  let highlighter_settings = highlighter::Settings {
+    syntax: iced::highlighter::Settings {
+      theme: iced::highlighter::Theme::Base16Ocean,
+      token: file.extension().unwrap_or("txt").to_owned(),
+    },
+    decorations: Arc::new(file.decorations()),
+  };
+
+  let create_editor = move || {
+    text_editor(file.content())
+      .wrapping(wrapping)
+      .highlight_with::<highlighter::Highlighter>(
+        highlighter_settings.clone(),
+        highlighter::Highlight::to_format,
+      )
+      .key_binding(move |key_press| {
+        key_bindings::editor_binding(
+          key_press,
+          file.cursor_count() > 1,
+          matches!(popup, Some(Popup::Completion(_))),
+          matches!(popup, Some(Popup::Spelling(_))),
+        )
+      })
+      .padding(constants::EDITOR_PADDING)
+      .size(font_size)
+      .line_height(constants::LINE_HEIGHT)
+      .style(|theme: &Theme, status: text_editor::Status| {
+        let base = text_editor::default(theme, status);
+
+        text_editor::Style {
+          border: border::Border {
+            width: 0.0,
+            ..Default::default()
+          },
+          ..base
+        }
+      })
+      .on_action(Message::Edit)
+  };
+
+  let create_text = move |wrap_columns: Option<usize>| -> Element<'a, Message> {
+    let mut layers: Vec<Element<'a, Message>> = vec![create_editor().into()];
+
+    if let Some(length) = file.editorconfig().max_line_length {
+      let offset =
+        constants::EDITOR_PADDING + length as f32 * font_size as f32 * constants::CHARACTER_WIDTH;
+
+      layers.push(row![space().width(offset), rule::vertical(1).style(rule::weak)].into());
+    }
+
+    if is_whitespace_visible {
+      layers.push(invisibles::view(file, font_size, wrap_columns));
+    }
+
+    if !file.misspellings().is_empty() {
+      layers.push(spelling::underlines(file, font_size, wrap_columns));
+    }
+
+    if file.cursor_count() > 1 {
+      layers.push(carets::view(file, font_size, wrap_columns));
+    }
+
+    match popup {
+      Some(Popup::Completion(suggestions)) => {
+        layers.push(completion::view(file, suggestions, font_size, wrap_columns));
+      }
+      Some(Popup::Spelling(menu)) => {
+        layers.push(spelling::menu(file, menu, font_size, wrap_columns));
       }
+      None => {}
     }
-    Mode::Preview => {
-      let mut style: markdown::Style = Theme::Dark.into();
-      style.font = constants::CUSTOM_FONT;
-
-      let settings = markdown::Settings::with_text_size(font_size, style);
-      let markdown_preview = markdown::view(file.markdown(), settings).map(Message::LinkClicked);
-
-      scrollable(row![markdown_preview].padding(10))
-        .auto_scroll(true)
-        .direction(scrollable::Direction::Both {
-          vertical: scrollable::Scrollbar::new().spacing(1),
-          horizontal: scrollable::Scrollbar::new().spacing(1),
-        })
-        .style(|theme: &Theme, status: scrollable::Status| {
-          let mut style = scrollable::default(theme, status);
-          style.horizontal_rail.background = Some(Background::Color(theme.palette().background));
-          style.horizontal_rail.scroller.background = Background::Color(theme.palette().primary);
-          style.vertical_rail.background = Some(Background::Color(theme.palette().background));
-          style.vertical_rail.scroller.background = Background::Color(theme.palette().primary);
-          style
+
+    mouse_area(Stack::with_children(layers))
+      .on_move(move |point| {
+        let font_size = font_size as f32;
+        let row = (point.y - constants::EDITOR_PADDING) / (font_size * constants::LINE_HEIGHT);
+        let column =
+          (point.x - constants::EDITOR_PADDING) / (font_size * constants::CHARACTER_WIDTH);
+
+        Message::EditorPointerMoved(Pointer {
+          row: row.max(0.0) as usize,
+          column: column.max(0.0).round() as usize,
+          columns: wrap_columns.unwrap_or(usize::MAX),
         })
-        .height(Length::Fill)
-        .width(Length::Fill)
-        .into()
-    }
+      })
+      .on_right_press(Message::Spelling(SpellingAction::OpenAtPointer))
+      .into()
+  };
+
+  let create_scrollable = |content: Element<'a, Message>| {
+    scrollable(content)
+      .id(ID)
+      .on_scroll(Message::EditorScrolled)
+      .auto_scroll(true)
+      .direction(scrollable::Direction::Both {
+        vertical: scrollable::Scrollbar::default(),
+        horizontal: scrollable::Scrollbar::default(),
+      })
+      .style(|theme: &Theme, status: scrollable::Status| {
+        let mut style = scrollable::default(theme, status);
+        style.horizontal_rail.background = Some(Background::Color(theme.palette().background));
+        style.horizontal_rail.scroller.background = Background::Color(theme.palette().primary);
+        style.vertical_rail.background = Some(Background::Color(theme.palette().background));
+        style.vertical_rail.scroller.background = Background::Color(theme.palette().primary);
+        style
+      })
+      .anchor_bottom()
+      .height(Length::Fill)
+  };
+
+  if is_word_wrap_on {
+    responsive(move |size| {
+      let width = size.width - gutter::width(file, font_size, numbering);
+      let columns = wrapping::columns(width, font_size);
+
+      create_scrollable(
+        row![
+          gutter::view(file, font_size, numbering, Some(columns), viewport),
+          container(create_text(Some(columns))).width(width)
+        ]
+        .into(),
+      )
+      .into()
+    })
+    .into()
+  } else {
+    create_scrollable(
+      row![
+        gutter::view(file, font_size, numbering, None, viewport),
+        container(create_text(None)).height(Length::Fill)
+      ]
+      .into(),
+    )
+    .width(Length::Fill)
+    .into()
   }
 }
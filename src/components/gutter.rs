@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+use iced::widget::{column, container, mouse_area, space, text};
+use iced::{Element, Padding, Point, mouse};
+
+use crate::file::File;
+use crate::folding::Marker;
+use crate::message::{GutterTarget, Message};
+use crate::{constants, wrapping};
+
+const MARGIN: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Numbering {
+  Hidden,
+  Absolute,
+  Relative,
+}
+
+fn digits(file: &File, numbering: Numbering) -> usize {
+  match numbering {
+    Numbering::Hidden => 0,
+    _ => file.line_count().to_string().len(),
+  }
+}
+
+fn character_width(font_size: u32) -> f32 {
+  font_size as f32 * constants::CHARACTER_WIDTH
+}
+
+pub fn width(file: &File, font_size: u32, numbering: Numbering) -> f32 {
+  let digits = digits(file, numbering);
+  let separator = usize::from(digits > 0);
+
+  (digits + separator + 1) as f32 * character_width(font_size) + MARGIN * 2.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+  pub top: f32,
+  pub height: f32,
+}
+
+struct Rows {
+  first: usize,
+  // Only complete when the window starts past the last row.
+  total: usize,
+  labels: Vec<String>,
+  lines: Vec<usize>,
+  current: Range<usize>,
+}
+
+fn rows(
+  file: &File,
+  numbering: Numbering,
+  wrap_columns: Option<usize>,
+  window: Range<usize>,
+) -> Rows {
+  let digits = digits(file, numbering);
+  let current = file.content().cursor().position.line;
+  let numbers = file.line_numbers();
+
+  let mut rows = Rows {
+    first: 0,
+    total: 0,
+    labels: Vec::new(),
+    lines: Vec::new(),
+    current: 0..0,
+  };
+
+  for (line, marker) in file.fold_markers().into_iter().enumerate() {
+    let count = match wrap_columns {
+      Some(columns) => file.content().line(line).map_or(1, |text| {
+        wrapping::rows(&text.text, columns, file.tab_width()).len()
+      }),
+      None => 1,
+    };
+
+    let row = rows.total;
+    rows.total += count;
+
+    if row >= window.end {
+      break;
+    }
+
+    if rows.total <= window.start {
+      continue;
+    }
+
+    if rows.labels.is_empty() {
+      rows.first = row;
+    }
+
+    let marker = match marker {
+      Some(Marker::Foldable) => "▾",
+      Some(Marker::Folded) => "▸",
+      None => " ",
+    };
+
+    let number = match numbering {
+      Numbering::Hidden => String::new(),
+      Numbering::Relative if line != current => line.abs_diff(current).to_string(),
+      _ => (numbers[line] + 1).to_string(),
+    };
+
+    if line == current {
+      rows.current = rows.labels.len()..rows.labels.len() + count;
+    }
+
+    rows.labels.push(match numbering {
+      Numbering::Hidden => marker.to_owned(),
+      _ => format!("{number:>digits$} {marker}"),
+    });
+    rows
+      .labels
+      .extend(std::iter::repeat_n(String::new(), count - 1));
+    rows.lines.extend(std::iter::repeat_n(line, count));
+  }
+
+  rows
+}
+
+pub fn view(
+  file: &File,
+  font_size: u32,
+  numbering: Numbering,
+  wrap_columns: Option<usize>,
+  viewport: Option<Viewport>,
+) -> Element<'_, Message> {
+  let digits = digits(file, numbering);
+  let line_height = font_size as f32 * constants::LINE_HEIGHT;
+
+  let window = viewport.map_or(0..usize::MAX, |viewport| {
+    let top = (viewport.top - viewport.height - constants::EDITOR_PADDING) / line_height;
+    let bottom = (viewport.top + viewport.height * 2.0) / line_height;
+
+    top.max(0.0) as usize..bottom.max(0.0).ceil() as usize + 1
+  });
+
+  let mut rows = rows(file, numbering, wrap_columns, window.clone());
+
+  if rows.labels.is_empty() && window.start > 0 {
+    let start = rows.total.saturating_sub(window.len());
+    rows = self::rows(file, numbering, wrap_columns, start..rows.total);
+  }
+
+  let Rows {
+    first,
+    labels,
+    lines,
+    current,
+    ..
+  } = rows;
+
+  let block = |range: Range<usize>, style: fn(&iced::Theme) -> text::Style| {
+    (!range.is_empty()).then(|| {
+      text(labels[range].join("\n"))
+        .size(font_size)
+        .line_height(constants::LINE_HEIGHT)
+        .shaping(text::Shaping::Advanced)
+        .style(style)
+    })
+  };
+
+  let gutter = column![
+    space().height(first as f32 * line_height),
+    block(0..current.start, text::secondary),
+    block(current.clone(), text::base),
+    block(current.end..labels.len(), text::secondary),
+  ];
+
+  let numbers_width = MARGIN + (digits as f32 + 0.5) * character_width(font_size);
+
+  let target = move |point: Point| {
+    let row = (point.y - constants::EDITOR_PADDING) / line_height - first as f32;
+    let line = *lines.get(row.max(0.0) as usize).filter(|_| row >= 0.0)?;
+
+    if point.x < numbers_width {
+      Some(GutterTarget::LineNumber(line))
+    } else {
+      Some(GutterTarget::FoldMarker(line))
+    }
+  };
+
+  mouse_area(
+    container(gutter)
+      .width(width(file, font_size, numbering))
+      .padding(Padding {
+        top: constants::EDITOR_PADDING,
+        bottom: constants::EDITOR_PADDING,
+        left: MARGIN,
+        right: MARGIN,
+      }),
+  )
+  .on_move(move |point| Message::GutterMoved(target(point)))
+  .on_press(Message::GutterPressed)
+  .interaction(mouse::Interaction::Pointer)
+  .into()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn renders_only_the_window() {
+    let text: String = (1..=100).map(|line| format!("line {line}\n")).collect();
+    let file = File::from(&text, None);
+    let rows = rows(&file, Numbering::Absolute, None, 40..45);
+
+    assert_eq!(rows.first, 40);
+    assert_eq!(rows.lines, [40, 41, 42, 43, 44]);
+    assert_eq!(rows.labels[0].trim(), "41");
+    assert!(rows.current.is_empty());
+  }
+
+  #[test]
+  fn counts_wrapped_rows_before_the_window() {
+    let file = File::from("aaaa bbbb\ncc\ndd\n", None);
+    let rows = rows(&file, Numbering::Hidden, Some(5), 2..3);
+
+    assert_eq!(rows.first, 2);
+    assert_eq!(rows.lines, [1]);
+  }
+}
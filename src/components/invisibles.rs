@@ -0,0 +1,60 @@
+use iced::widget::{container, stack, text};
+use iced::{Element, Theme};
+
+use crate::file::File;
+use crate::message::Message;
+use crate::{constants, whitespace, wrapping};
+
+const LINE_ENDING: &str = "¬";
+
+fn layer<'a>(
+  rows: &[String],
+  font_size: u32,
+  style: fn(&Theme) -> text::Style,
+) -> Element<'a, Message> {
+  container(
+    text(rows.join("\n"))
+      .size(font_size)
+      .line_height(constants::LINE_HEIGHT)
+      .shaping(text::Shaping::Advanced)
+      .wrapping(text::Wrapping::None)
+      .style(style),
+  )
+  .padding(constants::EDITOR_PADDING)
+  .clip(true)
+  .into()
+}
+
+pub fn view(file: &File, font_size: u32, wrap_columns: Option<usize>) -> Element<'_, Message> {
+  let is_markdown = file.is_markdown();
+  let count = file.content().line_count();
+
+  let mut visible = Vec::with_capacity(count);
+  let mut highlighted = Vec::with_capacity(count);
+
+  for (index, line) in file.content().lines().enumerate() {
+    let text = &line.text;
+    let trailing = whitespace::trailing_start(text, is_markdown);
+
+    let rows = wrapping::rows(text, wrap_columns.unwrap_or(usize::MAX), file.tab_width());
+
+    let last = rows.len() - 1;
+
+    for (rank, range) in rows.into_iter().enumerate() {
+      let (mut glyphs, trailing) = whitespace::glyphs(text, range, trailing, file.tab_width());
+
+      if rank == last && index + 1 < count {
+        glyphs.push_str(LINE_ENDING);
+      }
+
+      visible.push(glyphs);
+      highlighted.push(trailing);
+    }
+  }
+
+  stack![
+    layer(&visible, font_size, text::secondary),
+    layer(&highlighted, font_size, text::danger),
+  ]
+  .into()
+}
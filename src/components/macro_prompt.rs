@@ -0,0 +1,47 @@
+use iced::widget::{button, container, row, text, text_input};
+use iced::{Alignment, Element, Length, Padding, Theme};
+
+use crate::macros::{Prompt, PromptKind};
+use crate::message::Message;
+
+pub const ID: &str = "macro-prompt";
+
+pub fn view(prompt: &Prompt) -> Element<'_, Message> {
+  let label = match prompt.kind {
+    PromptKind::Repeat => "Play macro how many times?",
+    PromptKind::Name => "Save macro as",
+  };
+
+  let input = text_input("", &prompt.value)
+    .id(ID)
+    .on_input(Message::MacroPromptChanged)
+    .on_submit(Message::MacroPromptSubmitted)
+    .size(12)
+    .width(200);
+
+  let cancel = button(text("Cancel").size(12))
+    .on_press(Message::MacroPromptCancelled)
+    .style(button::text);
+
+  container(
+    row![text(label).size(12), input, cancel]
+      .spacing(10)
+      .align_y(Alignment::Center),
+  )
+  .style(|theme: &Theme| {
+    let base = container::Style::default();
+
+    container::Style {
+      background: container::primary(theme).background,
+      ..base
+    }
+  })
+  .width(Length::Fill)
+  .padding(Padding {
+    left: 20.0,
+    right: 20.0,
+    top: 5.0,
+    bottom: 5.0,
+  })
+  .into()
+}
@@ -0,0 +1,44 @@
+use iced::widget::{button, container, row, text, text_input};
+use iced::{Alignment, Element, Length, Padding, Theme};
+
+use crate::message::Message;
+use crate::pdf::MarginPrompt;
+
+pub const ID: &str = "margin-prompt";
+
+pub fn view(prompt: &MarginPrompt) -> Element<'_, Message> {
+  let label = format!("{} page margin", prompt.page_size);
+
+  let input = text_input("", &prompt.value)
+    .id(ID)
+    .on_input(Message::MarginPromptChanged)
+    .on_submit(Message::MarginPromptSubmitted)
+    .size(12)
+    .width(200);
+
+  let cancel = button(text("Cancel").size(12))
+    .on_press(Message::MarginPromptCancelled)
+    .style(button::text);
+
+  container(
+    row![text(label).size(12), input, cancel]
+      .spacing(10)
+      .align_y(Alignment::Center),
+  )
+  .style(|theme: &Theme| {
+    let base = container::Style::default();
+
+    container::Style {
+      background: container::primary(theme).background,
+      ..base
+    }
+  })
+  .width(Length::Fill)
+  .padding(Padding {
+    left: 20.0,
+    right: 20.0,
+    top: 5.0,
+    bottom: 5.0,
+  })
+  .into()
+}
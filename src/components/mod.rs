@@ -1,4 +1,14 @@
 pub mod action_bar;
+pub mod carets;
+pub mod completion;
 pub mod editor;
+pub mod gutter;
+pub mod invisibles;
+pub mod macro_prompt;
+pub mod margin_prompt;
+pub mod outline;
+pub mod popup;
+pub mod preview;
+pub mod spelling;
 pub mod status_bar;
 pub mod tabs;
@@ -0,0 +1,87 @@
+use iced::widget::{
+  Column, button, column, container, pick_list, row, scrollable, space, text, text_input,
+};
+use iced::{Alignment, Background, Element, Length, Padding, Theme};
+
+use crate::constants;
+use crate::file::File;
+use crate::message::{Message, OutlineAction};
+use crate::outline::{Depth, Panel};
+
+pub fn view<'a>(file: &'a File, panel: &'a Panel) -> Element<'a, Message> {
+  let filter = text_input("Filter headings", panel.filter())
+    .on_input(|filter| Message::Outline(OutlineAction::Filter(filter)))
+    .size(12);
+
+  let depth = pick_list(Depth::ALL, Some(panel.depth()), |depth| {
+    Message::Outline(OutlineAction::Depth(depth))
+  })
+  .text_size(12)
+  .padding(Padding {
+    left: 4.0,
+    right: 4.0,
+    top: 2.0,
+    bottom: 2.0,
+  })
+  .width(Length::Fill);
+
+  let entries = panel.entries(file.outline(), file.current_section());
+
+  let list: Element<'a, Message> = if entries.is_empty() {
+    text("No headings").size(12).style(text::secondary).into()
+  } else {
+    Column::with_children(entries.into_iter().map(|entry| {
+      let toggle: Element<'a, Message> = if entry.has_children {
+        button(text(if entry.is_collapsed { "▸" } else { "▾" }).size(12))
+          .padding(0)
+          .style(button::text)
+          .on_press(Message::Outline(OutlineAction::Collapse(entry.index)))
+          .into()
+      } else {
+        space().width(constants::OUTLINE_TOGGLE_WIDTH).into()
+      };
+
+      let is_current = entry.is_current;
+      let heading = button(text(&entry.heading.text).size(12))
+        .padding(Padding {
+          left: 4.0,
+          right: 4.0,
+          top: 1.0,
+          bottom: 1.0,
+        })
+        .width(Length::Fill)
+        .on_press(Message::Outline(OutlineAction::Jump(entry.heading.line)))
+        .style(move |theme: &Theme, status: button::Status| {
+          let base = button::text(theme, status);
+
+          if is_current || status == button::Status::Hovered {
+            button::Style {
+              background: Some(Background::Color(theme.palette().primary)),
+              ..base
+            }
+          } else {
+            base
+          }
+        });
+
+      row![
+        space().width(constants::OUTLINE_INDENT * (entry.heading.level - 1) as f32),
+        container(toggle).width(constants::OUTLINE_TOGGLE_WIDTH),
+        heading
+      ]
+      .align_y(Alignment::Center)
+      .into()
+    }))
+    .into()
+  };
+
+  container(column![filter, depth, scrollable(list).height(Length::Fill)].spacing(5))
+    .style(|theme: &Theme| container::Style {
+      background: container::primary(theme).background,
+      ..container::Style::default()
+    })
+    .width(constants::OUTLINE_WIDTH)
+    .height(Length::Fill)
+    .padding(5)
+    .into()
+}
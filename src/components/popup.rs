@@ -0,0 +1,51 @@
+use iced::widget::text_editor::Position;
+use iced::widget::{Column, button, column, container, row, space, text};
+use iced::{Element, Theme};
+
+use crate::file::File;
+use crate::message::Message;
+use crate::{constants, wrapping};
+
+pub fn item<'a>(
+  label: impl text::IntoFragment<'a>,
+  font_size: u32,
+  is_selected: bool,
+  on_press: Option<Message>,
+) -> Element<'a, Message> {
+  let style: fn(&Theme, button::Status) -> button::Style = if is_selected {
+    button::primary
+  } else {
+    button::text
+  };
+
+  button(text(label).size(font_size))
+    .on_press_maybe(on_press)
+    .padding([2, 6])
+    .style(style)
+    .into()
+}
+
+pub fn view<'a>(
+  file: &'a File,
+  position: Position,
+  font_size: u32,
+  wrap_columns: Option<usize>,
+  items: Vec<Element<'a, Message>>,
+) -> Element<'a, Message> {
+  let (row_index, columns) = wrapping::locate(
+    file.content(),
+    position,
+    wrap_columns.unwrap_or(usize::MAX),
+    file.tab_width(),
+  );
+  let font_size = font_size as f32;
+
+  let top = constants::EDITOR_PADDING + (row_index + 1) as f32 * font_size * constants::LINE_HEIGHT;
+  let left = constants::EDITOR_PADDING + columns as f32 * font_size * constants::CHARACTER_WIDTH;
+
+  let popup = container(Column::with_children(items))
+    .padding(2)
+    .style(container::bordered_box);
+
+  column![space().height(top), row![space().width(left), popup]].into()
+}
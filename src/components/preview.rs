@@ -0,0 +1,339 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+
+use iced::advanced::widget::{self, Id, Operation, operation};
+use iced::alignment::Horizontal;
+use iced::widget::{
+  Column, button, checkbox, column, container, image, markdown, mouse_area, rich_text, row,
+  scrollable, text,
+};
+use iced::{Background, ContentFit, Element, Length, Rectangle, Task, Theme, Vector};
+
+use crate::blocks::Block;
+use crate::components::editor;
+use crate::constants;
+use crate::file::File;
+use crate::front_matter::FrontMatter;
+use crate::images::{self, Entry, Source};
+use crate::message::Message;
+
+pub const ID: &str = "preview";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+  pub top: f32,
+  pub width: f32,
+  pub height: f32,
+  pub content_height: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+  pub editor: Option<Frame>,
+  pub preview: Option<Frame>,
+  pub blocks: Vec<f32>,
+}
+
+impl Layout {
+  pub fn block_bottom(&self, index: usize) -> Option<f32> {
+    self
+      .blocks
+      .get(index + 1)
+      .copied()
+      .or(self.preview.map(|preview| preview.content_height))
+  }
+}
+
+pub fn block_id(index: usize) -> Id {
+  Id::from(format!("{ID}-{index}"))
+}
+
+struct Viewer<'a> {
+  directory: Option<&'a Path>,
+  images: &'a images::Cache,
+  block: &'a Block,
+  next_task: Cell<usize>,
+}
+
+impl<'a> Viewer<'a> {
+  fn task(&self, settings: markdown::Settings, done: bool) -> Element<'a, Message> {
+    let index = self.next_task.get();
+    self.next_task.set(index + 1);
+
+    let line = self
+      .block
+      .tasks
+      .get(index)
+      .map(|offset| self.block.lines.start + offset);
+
+    let checkbox = match line {
+      Some(line) => checkbox(done).on_toggle(move |done| Message::TaskToggled(line, done)),
+      None => checkbox(done),
+    };
+
+    container(checkbox.size(settings.text_size))
+      .center_y(text::LineHeight::default().to_absolute(settings.text_size))
+      .into()
+  }
+
+  fn bullet(
+    &self,
+    settings: markdown::Settings,
+    marker: Option<Element<'a, Message>>,
+    bullet: &'a markdown::Bullet,
+  ) -> Element<'a, Message> {
+    let (marker, items) = match bullet {
+      markdown::Bullet::Point { items } => (marker, items),
+      markdown::Bullet::Task { items, done } => {
+        let task = self.task(settings, *done);
+
+        let marker = match marker {
+          Some(marker) => row![marker, task].spacing(settings.spacing).into(),
+          None => task,
+        };
+
+        (Some(marker), items)
+      }
+    };
+
+    let nested = markdown::Settings {
+      spacing: settings.spacing * 0.6,
+      ..settings
+    };
+
+    row![marker, markdown::view_with(items, nested, self)]
+      .spacing(settings.spacing)
+      .into()
+  }
+}
+
+impl<'a> markdown::Viewer<'a, Message> for Viewer<'a> {
+  fn on_link_click(url: markdown::Uri) -> Message {
+    Message::LinkClicked(url)
+  }
+
+  fn image(
+    &self,
+    settings: markdown::Settings,
+    url: &'a markdown::Uri,
+    _title: &'a str,
+    alt: &markdown::Text,
+  ) -> Element<'a, Message> {
+    let entry = Source::resolve(url, self.directory).map(|source| self.images.entry(&source));
+    let is_blocked = matches!(entry, Some(Entry::Blocked));
+
+    let label = match entry {
+      Some(Entry::Loaded(handle)) => {
+        return image(handle)
+          .content_fit(ContentFit::ScaleDown)
+          .width(Length::Shrink)
+          .into();
+      }
+      Some(Entry::Loading) => format!("Loading {url}"),
+      Some(Entry::Failed(error)) => format!("{error}: {url}"),
+      Some(Entry::Blocked) => format!("Remote image: {url}"),
+      None => format!("Unsupported image: {url}"),
+    };
+
+    let allow = is_blocked.then(|| {
+      button(text("Load remote images").size(settings.text_size * 0.8))
+        .on_press(Message::RemoteImagesAllowed)
+    });
+
+    container(
+      column![
+        text(label).size(settings.text_size * 0.8),
+        rich_text(alt.spans(settings.style)).on_link_click(Message::LinkClicked),
+        allow,
+      ]
+      .spacing(settings.spacing.0 / 2.0),
+    )
+    .padding(settings.spacing.0)
+    .style(container::dark)
+    .into()
+  }
+
+  fn unordered_list(
+    &self,
+    settings: markdown::Settings,
+    bullets: &'a [markdown::Bullet],
+  ) -> Element<'a, Message> {
+    column(bullets.iter().map(|bullet| {
+      let marker = matches!(bullet, markdown::Bullet::Point { .. })
+        .then(|| text("•").size(settings.text_size).into());
+
+      self.bullet(settings, marker, bullet)
+    }))
+    .spacing(settings.spacing * 0.75)
+    .padding([0.0, settings.spacing.0])
+    .into()
+  }
+
+  fn ordered_list(
+    &self,
+    settings: markdown::Settings,
+    start: u64,
+    bullets: &'a [markdown::Bullet],
+  ) -> Element<'a, Message> {
+    let digits = ((start + bullets.len() as u64).max(1) as f32)
+      .log10()
+      .ceil();
+
+    column(bullets.iter().enumerate().map(|(index, bullet)| {
+      let marker = text!("{}.", index as u64 + start)
+        .size(settings.text_size)
+        .align_x(Horizontal::Right)
+        .width(settings.text_size * ((digits / 2.0).ceil() + 1.0));
+
+      self.bullet(settings, Some(marker.into()), bullet)
+    }))
+    .spacing(settings.spacing * 0.75)
+    .into()
+  }
+}
+
+fn front_matter(front_matter: &FrontMatter, settings: markdown::Settings) -> Element<'_, Message> {
+  let size = settings.text_size * 0.85;
+
+  let fields = front_matter.fields.iter().map(|(key, value)| {
+    row![
+      text(key)
+        .size(size)
+        .font(settings.style.inline_code_font)
+        .width(Length::FillPortion(1)),
+      text(value.to_string())
+        .size(size)
+        .width(Length::FillPortion(3)),
+    ]
+    .spacing(settings.spacing)
+    .into()
+  });
+
+  let error = front_matter.error.as_ref().map(|error| {
+    text!(
+      "{} front matter error on line {}: {}",
+      front_matter.format,
+      error.line + 1,
+      error.message
+    )
+    .size(size)
+    .style(text::danger)
+  });
+
+  container(column(fields).push(error).spacing(settings.spacing.0 / 2.0))
+    .padding(settings.spacing.0)
+    .width(Length::Fill)
+    .style(container::dark)
+    .into()
+}
+
+pub fn view<'a>(file: &'a File, font_size: u32, images: &'a images::Cache) -> Element<'a, Message> {
+  let mut style: markdown::Style = Theme::Dark.into();
+  style.font = constants::CUSTOM_FONT;
+
+  let settings = markdown::Settings::with_text_size(font_size, style);
+  let directory = file.path().and_then(|path| path.parent());
+
+  let blocks = file.markdown().iter().enumerate().map(|(index, block)| {
+    let viewer = Viewer {
+      directory,
+      images,
+      block,
+      next_task: Cell::new(0),
+    };
+
+    mouse_area(container(markdown::view_with(&block.items, settings, &viewer)).id(block_id(index)))
+      .on_press(Message::PreviewBlockClicked(index))
+      .into()
+  });
+
+  let metadata = file
+    .front_matter()
+    .filter(|metadata| !metadata.fields.is_empty() || metadata.error.is_some())
+    .map(|metadata| front_matter(metadata, settings));
+
+  scrollable(
+    row![
+      Column::new()
+        .push(metadata)
+        .extend(blocks)
+        .spacing(settings.spacing)
+    ]
+    .padding(10),
+  )
+  .id(ID)
+  .on_scroll(|_| Message::PreviewScrolled)
+  .auto_scroll(true)
+  .direction(scrollable::Direction::Both {
+    vertical: scrollable::Scrollbar::new().spacing(1),
+    horizontal: scrollable::Scrollbar::new().spacing(1),
+  })
+  .style(|theme: &Theme, status: scrollable::Status| {
+    let mut style = scrollable::default(theme, status);
+    style.horizontal_rail.background = Some(Background::Color(theme.palette().background));
+    style.horizontal_rail.scroller.background = Background::Color(theme.palette().primary);
+    style.vertical_rail.background = Some(Background::Color(theme.palette().background));
+    style.vertical_rail.scroller.background = Background::Color(theme.palette().primary);
+    style
+  })
+  .height(Length::Fill)
+  .width(Length::Fill)
+  .into()
+}
+
+pub fn measure(blocks: usize) -> Task<Layout> {
+  widget::operate(Measure {
+    ids: (0..blocks).map(|index| (block_id(index), index)).collect(),
+    content_top: 0.0,
+    layout: Layout {
+      blocks: vec![0.0; blocks],
+      ..Layout::default()
+    },
+  })
+}
+
+struct Measure {
+  ids: HashMap<Id, usize>,
+  content_top: f32,
+  layout: Layout,
+}
+
+impl Operation<Layout> for Measure {
+  fn traverse(&mut self, operate: &mut dyn FnMut(&mut dyn Operation<Layout>)) {
+    operate(self);
+  }
+
+  fn scrollable(
+    &mut self,
+    id: Option<&Id>,
+    bounds: Rectangle,
+    content_bounds: Rectangle,
+    translation: Vector,
+    _state: &mut dyn operation::Scrollable,
+  ) {
+    let frame = Frame {
+      top: translation.y,
+      width: bounds.width,
+      height: bounds.height,
+      content_height: content_bounds.height,
+    };
+
+    if id == Some(&Id::new(ID)) {
+      self.content_top = content_bounds.y;
+      self.layout.preview = Some(frame);
+    } else if id == Some(&Id::new(editor::ID)) {
+      self.layout.editor = Some(frame);
+    }
+  }
+
+  fn container(&mut self, id: Option<&Id>, bounds: Rectangle) {
+    if let Some(index) = id.and_then(|id| self.ids.get(id)) {
+      self.layout.blocks[*index] = bounds.y - self.content_top;
+    }
+  }
+
+  fn finish(&self) -> operation::Outcome<Layout> {
+    operation::Outcome::Some(self.layout.clone())
+  }
+}
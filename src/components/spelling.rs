@@ -0,0 +1,114 @@
+use iced::widget::text::{self, Span};
+use iced::widget::{container, rich_text, span};
+use iced::{Element, Font};
+
+use crate::components::popup;
+use crate::file::File;
+use crate::message::{Message, SpellingAction};
+use crate::spelling::Menu;
+use crate::{constants, cursors, wrapping};
+
+pub fn underlines(
+  file: &File,
+  font_size: u32,
+  wrap_columns: Option<usize>,
+) -> Element<'_, Message> {
+  let columns = wrap_columns.unwrap_or(usize::MAX);
+  let misspellings = file.misspellings();
+  let tab_width = file.tab_width();
+  let mut spans: Vec<Span<'_, Message, Font>> = Vec::new();
+  let mut pending = String::new();
+
+  for (index, line) in file.content().lines().enumerate() {
+    if index > 0 {
+      pending.push('\n');
+    }
+
+    let words: Vec<_> = misspellings
+      .iter()
+      .filter(|(line, _)| *line == index)
+      .map(|(_, range)| range.clone())
+      .collect();
+
+    let text = &line.text;
+
+    for (rank, row) in wrapping::rows(text, columns, tab_width)
+      .into_iter()
+      .enumerate()
+    {
+      if rank > 0 {
+        pending.push('\n');
+      }
+
+      let row_text = &text[row.clone()];
+      let mut column = row.start;
+
+      for word in words.iter().filter(|word| {
+        word.start >= row.start
+          && word.end <= row.end
+          && text.is_char_boundary(word.start)
+          && text.is_char_boundary(word.end)
+      }) {
+        let start = cursors::visual_column(row_text, word.start - row.start, tab_width);
+        let end = cursors::visual_column(row_text, word.end - row.start, tab_width);
+        let gap = start - cursors::visual_column(row_text, column - row.start, tab_width);
+
+        pending.push_str(&" ".repeat(gap));
+        spans.push(span(std::mem::take(&mut pending)));
+        spans.push(span(" ".repeat(end - start)).underline(true));
+        column = word.end;
+      }
+    }
+  }
+
+  container(
+    rich_text(spans)
+      .size(font_size)
+      .line_height(constants::LINE_HEIGHT)
+      .wrapping(text::Wrapping::None)
+      .style(text::danger),
+  )
+  .padding(constants::EDITOR_PADDING)
+  .clip(true)
+  .into()
+}
+
+pub fn menu<'a>(
+  file: &'a File,
+  menu: &'a Menu,
+  font_size: u32,
+  wrap_columns: Option<usize>,
+) -> Element<'a, Message> {
+  let mut items: Vec<Element<'a, Message>> = menu
+    .suggestions
+    .iter()
+    .enumerate()
+    .map(|(index, suggestion)| {
+      popup::item(
+        suggestion.as_str(),
+        font_size,
+        false,
+        Some(Message::Spelling(SpellingAction::Replace(index))),
+      )
+    })
+    .collect();
+
+  if items.is_empty() {
+    items.push(popup::item("No suggestions", font_size, false, None));
+  }
+
+  items.push(popup::item(
+    format!("Add \"{}\" to dictionary", menu.word),
+    font_size,
+    false,
+    Some(Message::Spelling(SpellingAction::AddToDictionary)),
+  ));
+  items.push(popup::item(
+    "Ignore in this file",
+    font_size,
+    false,
+    Some(Message::Spelling(SpellingAction::IgnoreInFile)),
+  ));
+
+  popup::view(file, menu.start, font_size, wrap_columns, items)
+}
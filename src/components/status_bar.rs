@@ -1,26 +1,60 @@
 use crate::file::File;
-use iced::widget::{container, row, text};
+use crate::message::Message;
+use crate::spelling::Language;
+use iced::widget::{container, pick_list, row, text};
 use iced::{Element, Length, Padding, Theme};
 
-pub fn view(file: &File) -> Element<'_, crate::Message> {
+pub fn view<'a>(
+  file: &'a File,
+  is_recording: bool,
+  notice: Option<&'a str>,
+  spell_languages: Vec<Language>,
+) -> Element<'a, crate::Message> {
   let path_text = text(file.path_summary()).size(12);
   let cursor_text = text(file.position_summary()).size(12);
+  let cursors_text = file.cursor_summary().map(|summary| text(summary).size(12));
+  let indent_text = text(file.indent().to_string()).size(12);
+  let recording_text = is_recording.then(|| text("Recording macro").size(12).style(text::danger));
+  let notice_text = notice.map(|notice| text(notice).size(12).style(text::danger));
+  let editorconfig_text = file
+    .editorconfig()
+    .summary()
+    .map(|summary| text(summary).size(12));
+  let language_picker = pick_list(
+    spell_languages,
+    Some(Language(file.spell_language().map(String::from))),
+    Message::SpellLanguageSelected,
+  )
+  .text_size(12)
+  .padding(0);
 
-  container(row![cursor_text, path_text].spacing(20))
-    .style(|theme: &Theme| {
-      let base = container::Style::default();
+  container(
+    row![
+      recording_text,
+      notice_text,
+      cursor_text,
+      cursors_text,
+      indent_text,
+      editorconfig_text,
+      language_picker,
+      path_text
+    ]
+    .spacing(20),
+  )
+  .style(|theme: &Theme| {
+    let base = container::Style::default();
 
-      container::Style {
-        background: container::primary(theme).background,
-        ..base
-      }
-    })
-    .width(Length::Fill)
-    .padding(Padding {
-      left: 20.0,
-      right: 20.0,
-      top: 5.0,
-      bottom: 5.0,
-    })
-    .into()
+    container::Style {
+      background: container::primary(theme).background,
+      ..base
+    }
+  })
+  .width(Length::Fill)
+  .padding(Padding {
+    left: 20.0,
+    right: 20.0,
+    top: 5.0,
+    bottom: 5.0,
+  })
+  .into()
 }
@@ -1,9 +1,10 @@
 use crate::file::File;
+use crate::front_matter::FrontMatter;
 use crate::message::{FileAction, Message};
 use iced::{Background, Border, Padding, Theme, border};
 use iced::{
   Element,
-  widget::{button, container, row, scrollable, text},
+  widget::{button, container, row, scrollable, text, tooltip},
 };
 
 pub fn view<'a>(files: &'a [File], active_index: usize) -> Element<'a, Message> {
@@ -21,7 +22,20 @@ pub fn view<'a>(files: &'a [File], active_index: usize) -> Element<'a, Message>
       .padding(1)
       .on_press(Message::FileActionSelected(FileAction::Close(Some(index))));
 
-    button(
+    let front_matter = file.front_matter();
+    let details: Vec<String> = [
+      front_matter.and_then(FrontMatter::title).map(str::to_owned),
+      front_matter
+        .map(FrontMatter::tags)
+        .filter(|tags| !tags.is_empty())
+        .map(|tags| format!("Tags: {}", tags.join(", "))),
+      Some(file.path_summary()).filter(|path| !path.is_empty()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let tab = button(
       row![label, close_btn]
         .spacing(5)
         .align_y(iced::Alignment::Center),
@@ -58,8 +72,19 @@ pub fn view<'a>(files: &'a [File], active_index: usize) -> Element<'a, Message>
         },
         _ => button_style,
       }
-    })
-    .into()
+    });
+
+    if details.is_empty() {
+      tab.into()
+    } else {
+      tooltip(
+        tab,
+        container(text(details.join("\n"))).padding(5),
+        tooltip::Position::Bottom,
+      )
+      .style(container::dark)
+      .into()
+    }
   });
 
   scrollable(container(row(tabs)).padding(Padding {
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use iced::Font;
 
 pub const CUSTOM_FONT_BYTES: &[u8] = include_bytes!("./fonts/CaskaydiaCoveNFM-Regular.ttf");
@@ -5,4 +7,19 @@ pub const CUSTOM_FONT: Font = Font::with_name("CaskaydiaCove Nerd Font Mono");
 pub const DEFAULT_EDITOR_FONT_SIZE: u32 = 16;
 pub const MAX_EDITOR_FONT_SIZE: u32 = 80;
 pub const MIN_EDITOR_FONT_SIZE: u32 = 12;
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+pub const EDITOR_PADDING: f32 = 10.0;
+pub const LINE_HEIGHT: f32 = 1.3;
+pub const CHARACTER_WIDTH: f32 = 1200.0 / 2048.0;
+pub const CONFIG_DIRECTORY: &str = "zoeae";
 pub const ICON_BYTES: &[u8] = include_bytes!("./images/icon.ico");
+pub const DIVIDER_WIDTH: f32 = 4.0;
+pub const DEFAULT_SPLIT_RATIO: f32 = 0.5;
+pub const MIN_SPLIT_RATIO: f32 = 0.15;
+pub const MAX_SPLIT_RATIO: f32 = 0.85;
+pub const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(300);
+pub const SPELLING_DEBOUNCE: Duration = Duration::from_millis(300);
+pub const IMAGE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const OUTLINE_WIDTH: f32 = 220.0;
+pub const OUTLINE_INDENT: f32 = 12.0;
+pub const OUTLINE_TOGGLE_WIDTH: f32 = 14.0;
@@ -0,0 +1,416 @@
+use std::cmp::Ordering;
+
+use iced::widget::text_editor::{Action, Content, Cursor, Edit, Motion, Position};
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+  pub anchor: (usize, usize),
+  pub head: (usize, usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Insertion {
+  pub cursor: Cursor,
+  pub text: String,
+  pub shift: isize,
+  pub selected: usize,
+}
+
+impl Insertion {
+  pub fn new(cursor: Cursor, text: String) -> Self {
+    Insertion {
+      cursor,
+      text,
+      shift: 0,
+      selected: 0,
+    }
+  }
+}
+
+impl Block {
+  pub fn has_width(&self) -> bool {
+    self.anchor.1 != self.head.1
+  }
+}
+
+pub fn compare(a: &Position, b: &Position) -> Ordering {
+  (a.line, a.column).cmp(&(b.line, b.column))
+}
+
+pub fn bounds(cursor: &Cursor) -> (Position, Position) {
+  match cursor.selection {
+    Some(selection) if compare(&selection, &cursor.position).is_lt() => {
+      (selection, cursor.position)
+    }
+    Some(selection) => (cursor.position, selection),
+    None => (cursor.position, cursor.position),
+  }
+}
+
+pub fn caret(position: Position) -> Cursor {
+  Cursor {
+    position,
+    selection: None,
+  }
+}
+
+pub fn offset(content: &Content, position: Position) -> usize {
+  content
+    .lines()
+    .take(position.line)
+    .map(|line| line.text.len() + line.ending.as_str().len())
+    .sum::<usize>()
+    + position.column
+}
+
+pub fn position(content: &Content, offset: usize) -> Position {
+  let mut remaining = offset;
+  let mut last = Position { line: 0, column: 0 };
+
+  for (index, line) in content.lines().enumerate() {
+    let length = line.text.len();
+    let ending = line.ending.as_str().len();
+
+    if remaining <= length || ending == 0 {
+      return Position {
+        line: index,
+        column: floor_char_boundary(&line.text, remaining.min(length)),
+      };
+    }
+
+    remaining -= length + ending;
+    last = Position {
+      line: index,
+      column: length,
+    };
+  }
+
+  last
+}
+
+pub fn len(content: &Content) -> usize {
+  content
+    .lines()
+    .map(|line| line.text.len() + line.ending.as_str().len())
+    .sum()
+}
+
+pub fn floor_char_boundary(text: &str, index: usize) -> usize {
+  let mut index = index.min(text.len());
+
+  while !text.is_char_boundary(index) {
+    index -= 1;
+  }
+
+  index
+}
+
+pub fn place(content: &mut Content, cursor: Cursor) {
+  if content.cursor().selection.is_some() {
+    content.perform(Action::Move(Motion::Left));
+  }
+
+  content.move_to(cursor);
+}
+
+pub fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+pub fn word_start(text: &str, column: usize) -> usize {
+  text[..column]
+    .char_indices()
+    .rev()
+    .take_while(|(_, c)| is_word_char(*c))
+    .last()
+    .map_or(column, |(index, _)| index)
+}
+
+pub fn word_end(text: &str, column: usize) -> usize {
+  text[column..]
+    .char_indices()
+    .find(|(_, c)| !is_word_char(*c))
+    .map_or(text.len(), |(index, _)| column + index)
+}
+
+pub fn word_at(content: &Content, position: Position) -> Option<Cursor> {
+  let line = content.line(position.line)?;
+  let start = word_start(&line.text, position.column);
+  let end = word_end(&line.text, position.column);
+
+  if start == end {
+    return None;
+  }
+
+  Some(Cursor {
+    position: Position {
+      line: position.line,
+      column: end,
+    },
+    selection: Some(Position {
+      line: position.line,
+      column: start,
+    }),
+  })
+}
+
+pub fn line_at(content: &Content, position: Position) -> Option<Cursor> {
+  let line = content.line(position.line)?;
+
+  Some(Cursor {
+    position: Position {
+      line: position.line,
+      column: line.text.len(),
+    },
+    selection: Some(Position {
+      line: position.line,
+      column: 0,
+    }),
+  })
+}
+
+pub fn selected_text(content: &Content, text: &str, cursor: &Cursor) -> String {
+  let (start, end) = bounds(cursor);
+  text[offset(content, start)..offset(content, end)].to_owned()
+}
+
+pub fn dedup(cursors: &mut Vec<Cursor>) {
+  let mut seen: Vec<(Position, Position)> = Vec::with_capacity(cursors.len());
+
+  cursors.retain(|cursor| {
+    let range = bounds(cursor);
+
+    if seen.contains(&range) {
+      false
+    } else {
+      seen.push(range);
+      true
+    }
+  });
+}
+
+pub fn edit_all(
+  content: &mut Content,
+  cursors: &[Cursor],
+  edit_for: impl Fn(usize, &Cursor) -> Option<Edit>,
+) -> Vec<Cursor> {
+  let mut order: Vec<usize> = (0..cursors.len()).collect();
+  order.sort_by(|a, b| compare(&bounds(&cursors[*a]).0, &bounds(&cursors[*b]).0));
+
+  let mut distances_from_end = vec![0; cursors.len()];
+
+  for (rank, &index) in order.iter().enumerate().rev() {
+    let cursor = cursors[index];
+
+    place(content, cursor);
+
+    if let Some(edit) = edit_for(rank, &cursor) {
+      content.perform(Action::Edit(edit));
+    }
+
+    let at = offset(content, content.cursor().position);
+    distances_from_end[index] = len(content) - at;
+  }
+
+  let total = len(content);
+
+  distances_from_end
+    .into_iter()
+    .map(|distance| caret(position(content, total - distance)))
+    .collect()
+}
+
+pub fn perform_all(content: &mut Content, cursors: &[Cursor], action: &Action) -> Vec<Cursor> {
+  cursors
+    .iter()
+    .map(|cursor| {
+      place(content, *cursor);
+      content.perform(action.clone());
+      content.cursor()
+    })
+    .collect()
+}
+
+pub fn shifted(content: &Content, cursor: &Cursor, lines: isize) -> Option<Cursor> {
+  let position = cursor.position;
+  let target = position.line.checked_add_signed(lines)?;
+  let current = content.line(position.line)?;
+  let line = content.line(target)?;
+
+  let chars = current.text[..position.column.min(current.text.len())]
+    .chars()
+    .count();
+
+  let column = line
+    .text
+    .char_indices()
+    .nth(chars)
+    .map(|(index, _)| index)
+    .unwrap_or(line.text.len());
+
+  Some(caret(Position {
+    line: target,
+    column,
+  }))
+}
+
+pub fn next_occurrence(content: &Content, cursors: &[Cursor], needle: &str) -> Option<Cursor> {
+  if needle.is_empty() {
+    return None;
+  }
+
+  let text = content.text();
+
+  let taken: Vec<usize> = cursors
+    .iter()
+    .map(|cursor| offset(content, bounds(cursor).0))
+    .collect();
+
+  let from = cursors
+    .iter()
+    .map(|cursor| offset(content, bounds(cursor).1))
+    .max()
+    .unwrap_or(0);
+
+  let start = text[from..]
+    .match_indices(needle)
+    .map(|(index, _)| from + index)
+    .chain(text[..from].match_indices(needle).map(|(index, _)| index))
+    .find(|start| !taken.contains(start))?;
+
+  Some(Cursor {
+    position: position(content, start + needle.len()),
+    selection: Some(position(content, start)),
+  })
+}
+
+pub fn advance(width: usize, c: char, tab_width: usize) -> usize {
+  match c {
+    '\t' => (width / tab_width + 1) * tab_width,
+    _ => width + c.width().unwrap_or(0),
+  }
+}
+
+pub fn visual_column(text: &str, column: usize, tab_width: usize) -> usize {
+  text[..column.min(text.len())]
+    .chars()
+    .fold(0, |width, c| advance(width, c, tab_width))
+}
+
+pub fn byte_column(text: &str, visual: usize, round_up: bool, tab_width: usize) -> usize {
+  let mut width = 0;
+
+  for (index, c) in text.char_indices() {
+    let next = advance(width, c, tab_width);
+
+    if next == width {
+      continue;
+    }
+
+    if visual <= width || (visual < next && !round_up) {
+      return index;
+    }
+
+    if visual < next {
+      return index + c.len_utf8();
+    }
+
+    width = next;
+  }
+
+  text.len()
+}
+
+pub fn visual_position(content: &Content, position: Position, tab_width: usize) -> (usize, usize) {
+  let column = content.line(position.line).map_or(0, |line| {
+    visual_column(&line.text, position.column, tab_width)
+  });
+
+  (position.line, column)
+}
+
+pub fn block_cursors(content: &Content, block: &Block, tab_width: usize) -> Vec<Cursor> {
+  let (top, bottom) = (
+    block.anchor.0.min(block.head.0),
+    block.anchor.0.max(block.head.0),
+  );
+  let (left, right) = (
+    block.anchor.1.min(block.head.1),
+    block.anchor.1.max(block.head.1),
+  );
+  let is_head_right = block.head.1 >= block.anchor.1;
+
+  let mut cursors: Vec<Cursor> = (top..=bottom)
+    .filter_map(|line| {
+      let text = content.line(line)?.text;
+      let start = byte_column(&text, left, false, tab_width);
+      let end = byte_column(&text, right, true, tab_width);
+
+      if start == end {
+        return Some(caret(Position {
+          line,
+          column: start,
+        }));
+      }
+
+      let (position, selection) = if is_head_right {
+        (end, start)
+      } else {
+        (start, end)
+      };
+
+      Some(Cursor {
+        position: Position {
+          line,
+          column: position,
+        },
+        selection: Some(Position {
+          line,
+          column: selection,
+        }),
+      })
+    })
+    .collect();
+
+  if let Some(index) = cursors.iter().position(|c| c.position.line == block.head.0) {
+    let head = cursors.remove(index);
+    cursors.insert(0, head);
+  }
+
+  cursors
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn treats_letters_digits_and_underscores_as_word_characters() {
+    assert!(['a', 'Z', '7', '_', 'é', 'ß'].into_iter().all(is_word_char));
+    assert!(!['-', ' ', '\'', '.', '('].into_iter().any(is_word_char));
+  }
+
+  #[test]
+  fn finds_word_bounds_around_a_column() {
+    let text = "let snake_case = café;";
+
+    assert_eq!(word_start(text, 9), 4);
+    assert_eq!(word_end(text, 9), 14);
+    assert_eq!(word_start(text, 17), 17);
+    assert_eq!(word_end(text, 17), 22);
+  }
+
+  #[test]
+  fn selects_the_word_under_the_cursor() {
+    let content = Content::with_text("one two_2 three");
+    let cursor = word_at(&content, Position { line: 0, column: 6 }).unwrap();
+
+    assert_eq!(cursor.selection, Some(Position { line: 0, column: 4 }));
+    assert_eq!(cursor.position, Position { line: 0, column: 9 });
+    assert_eq!(
+      word_at(&content, Position { line: 0, column: 3 }).map(|c| c.position.column),
+      Some(3)
+    );
+  }
+}
@@ -0,0 +1,473 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::indent::{self, Indent};
+
+const FILE_NAME: &str = ".editorconfig";
+const BOM: char = '\u{FEFF}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentSize {
+  Tab,
+  Columns(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndOfLine {
+  Lf,
+  Crlf,
+  Cr,
+}
+
+impl EndOfLine {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      EndOfLine::Lf => "\n",
+      EndOfLine::Crlf => "\r\n",
+      EndOfLine::Cr => "\r",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+  Latin1,
+  Utf8,
+  Utf8Bom,
+  Utf16Be,
+  Utf16Le,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Properties {
+  pub indent_style: Option<indent::Style>,
+  pub indent_size: Option<IndentSize>,
+  pub tab_width: Option<usize>,
+  pub end_of_line: Option<EndOfLine>,
+  pub charset: Option<Charset>,
+  pub trim_trailing_whitespace: Option<bool>,
+  pub insert_final_newline: Option<bool>,
+  pub max_line_length: Option<usize>,
+}
+
+impl Properties {
+  fn from_values(values: &BTreeMap<String, String>) -> Self {
+    let value = |key: &str| values.get(key).map(String::as_str);
+    let flag = |key: &str| match value(key) {
+      Some("true") => Some(true),
+      Some("false") => Some(false),
+      _ => None,
+    };
+
+    Properties {
+      indent_style: match value("indent_style") {
+        Some("tab") => Some(indent::Style::Tabs),
+        Some("space") => Some(indent::Style::Spaces),
+        _ => None,
+      },
+      indent_size: match value("indent_size") {
+        Some("tab") => Some(IndentSize::Tab),
+        Some(size) => size.parse().ok().map(IndentSize::Columns),
+        None => None,
+      },
+      tab_width: value("tab_width").and_then(|width| width.parse().ok()),
+      end_of_line: match value("end_of_line") {
+        Some("lf") => Some(EndOfLine::Lf),
+        Some("crlf") => Some(EndOfLine::Crlf),
+        Some("cr") => Some(EndOfLine::Cr),
+        _ => None,
+      },
+      charset: match value("charset") {
+        Some("latin1") => Some(Charset::Latin1),
+        Some("utf-8") => Some(Charset::Utf8),
+        Some("utf-8-bom") => Some(Charset::Utf8Bom),
+        Some("utf-16be") => Some(Charset::Utf16Be),
+        Some("utf-16le") => Some(Charset::Utf16Le),
+        _ => None,
+      },
+      trim_trailing_whitespace: flag("trim_trailing_whitespace"),
+      insert_final_newline: flag("insert_final_newline"),
+      max_line_length: value("max_line_length").and_then(|length| length.parse().ok()),
+    }
+  }
+
+  pub fn indent(&self, detected: Indent) -> Indent {
+    let style = self.indent_style.unwrap_or(detected.style);
+    let tab_width = self.tab_width;
+
+    let size = match (self.indent_size, style) {
+      (Some(IndentSize::Columns(size)), _) => size,
+      (Some(IndentSize::Tab), _) | (None, indent::Style::Tabs) => {
+        tab_width.unwrap_or(detected.size)
+      }
+      (None, indent::Style::Spaces) => detected.size,
+    };
+
+    Indent {
+      style,
+      size: size.max(1),
+    }
+  }
+
+  pub fn summary(&self) -> Option<String> {
+    let mut parts = Vec::new();
+
+    if let Some(end_of_line) = self.end_of_line {
+      parts.push(
+        match end_of_line {
+          EndOfLine::Lf => "LF",
+          EndOfLine::Crlf => "CRLF",
+          EndOfLine::Cr => "CR",
+        }
+        .to_owned(),
+      );
+    }
+
+    if let Some(charset) = self.charset {
+      parts.push(
+        match charset {
+          Charset::Latin1 => "Latin-1",
+          Charset::Utf8 => "UTF-8",
+          Charset::Utf8Bom => "UTF-8 with BOM",
+          Charset::Utf16Be => "UTF-16 BE",
+          Charset::Utf16Le => "UTF-16 LE",
+        }
+        .to_owned(),
+      );
+    }
+
+    if self.trim_trailing_whitespace == Some(true) {
+      parts.push(String::from("Trim whitespace"));
+    }
+
+    if self.insert_final_newline == Some(true) {
+      parts.push(String::from("Final newline"));
+    }
+
+    if let Some(length) = self.max_line_length {
+      parts.push(format!("Max {length}"));
+    }
+
+    (!parts.is_empty()).then(|| format!("EditorConfig: {}", parts.join(", ")))
+  }
+}
+
+pub fn resolve(path: &Path) -> Properties {
+  let mut configs: Vec<(PathBuf, String)> = Vec::new();
+  let mut directory = path.parent();
+
+  while let Some(current) = directory {
+    if let Ok(text) = std::fs::read_to_string(current.join(FILE_NAME)) {
+      let is_root = is_root(&text);
+      configs.push((current.to_owned(), text));
+
+      if is_root {
+        break;
+      }
+    }
+
+    directory = current.parent();
+  }
+
+  let mut values = BTreeMap::new();
+
+  for (directory, text) in configs.iter().rev() {
+    if let Ok(relative) = path.strip_prefix(directory) {
+      let relative = relative.to_string_lossy().replace('\\', "/");
+      apply(text, &relative, &mut values);
+    }
+  }
+
+  Properties::from_values(&values)
+}
+
+fn entries(text: &str) -> impl Iterator<Item = Entry<'_>> {
+  text.lines().filter_map(|line| {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with(['#', ';']) {
+      return None;
+    }
+
+    if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+      return Some(Entry::Section(section));
+    }
+
+    let (key, value) = line.split_once('=')?;
+    Some(Entry::Pair(key.trim(), value.trim()))
+  })
+}
+
+enum Entry<'a> {
+  Section(&'a str),
+  Pair(&'a str, &'a str),
+}
+
+fn is_root(text: &str) -> bool {
+  entries(text)
+    .take_while(|entry| matches!(entry, Entry::Pair(..)))
+    .any(|entry| {
+      matches!(entry, Entry::Pair(key, value)
+        if key.eq_ignore_ascii_case("root") && value.eq_ignore_ascii_case("true"))
+    })
+}
+
+fn apply(text: &str, relative: &str, values: &mut BTreeMap<String, String>) {
+  let mut is_matching = false;
+
+  for entry in entries(text) {
+    match entry {
+      Entry::Section(section) => is_matching = section_matches(section, relative),
+      Entry::Pair(key, value) if is_matching => {
+        let key = key.to_ascii_lowercase();
+        let value = value.to_ascii_lowercase();
+
+        if value == "unset" {
+          values.remove(&key);
+        } else {
+          values.insert(key, value);
+        }
+      }
+      Entry::Pair(..) => {}
+    }
+  }
+}
+
+fn section_matches(section: &str, relative: &str) -> bool {
+  let (pattern, path) = if section.contains('/') {
+    (section.strip_prefix('/').unwrap_or(section), relative)
+  } else {
+    (section, relative.rsplit('/').next().unwrap_or(relative))
+  };
+
+  let pattern: Vec<char> = pattern.chars().collect();
+  let path: Vec<char> = path.chars().collect();
+
+  glob_matches(&pattern, &path)
+}
+
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+  match pattern.first() {
+    None => path.is_empty(),
+    Some('*') if pattern.get(1) == Some(&'*') => {
+      (0..=path.len()).any(|skip| glob_matches(&pattern[2..], &path[skip..]))
+    }
+    Some('*') => {
+      for skip in 0..=path.len() {
+        if glob_matches(&pattern[1..], &path[skip..]) {
+          return true;
+        }
+
+        if path.get(skip) == Some(&'/') {
+          break;
+        }
+      }
+
+      false
+    }
+    Some('?') => path.first().is_some_and(|c| *c != '/') && glob_matches(&pattern[1..], &path[1..]),
+    Some('[') if pattern.contains(&']') => {
+      let end = pattern.iter().position(|c| *c == ']').unwrap_or(0);
+      let class = &pattern[1..end];
+      let (is_negated, class) = match class.first() {
+        Some('!') => (true, &class[1..]),
+        _ => (false, class),
+      };
+
+      path
+        .first()
+        .is_some_and(|c| *c != '/' && class_contains(class, *c) != is_negated)
+        && glob_matches(&pattern[end + 1..], &path[1..])
+    }
+    Some('{') => match closing_brace(pattern) {
+      Some(end) => braces_match(&pattern[1..end], &pattern[end + 1..], path),
+      None => path.first() == Some(&'{') && glob_matches(&pattern[1..], &path[1..]),
+    },
+    Some('\\') if pattern.len() > 1 => {
+      path.first() == Some(&pattern[1]) && glob_matches(&pattern[2..], &path[1..])
+    }
+    Some(c) => path.first() == Some(c) && glob_matches(&pattern[1..], &path[1..]),
+  }
+}
+
+fn class_contains(class: &[char], c: char) -> bool {
+  let mut index = 0;
+
+  while index < class.len() {
+    if class.get(index + 1) == Some(&'-') && index + 2 < class.len() {
+      if (class[index]..=class[index + 2]).contains(&c) {
+        return true;
+      }
+
+      index += 3;
+    } else {
+      if class[index] == c {
+        return true;
+      }
+
+      index += 1;
+    }
+  }
+
+  false
+}
+
+fn closing_brace(pattern: &[char]) -> Option<usize> {
+  let mut depth = 0;
+
+  for (index, c) in pattern.iter().enumerate() {
+    match c {
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+
+        if depth == 0 {
+          return Some(index);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  None
+}
+
+fn braces_match(inner: &[char], rest: &[char], path: &[char]) -> bool {
+  let text: String = inner.iter().collect();
+
+  if let Some((low, high)) = text.split_once("..")
+    && let (Ok(low), Ok(high)) = (low.parse::<i64>(), high.parse::<i64>())
+  {
+    return (1..=path.len()).any(|length| {
+      let number: String = path[..length].iter().collect();
+
+      number
+        .parse::<i64>()
+        .is_ok_and(|n| (low.min(high)..=low.max(high)).contains(&n))
+        && glob_matches(rest, &path[length..])
+    });
+  }
+
+  let mut alternatives = Vec::new();
+  let mut depth = 0;
+  let mut start = 0;
+
+  for (index, c) in inner.iter().enumerate() {
+    match c {
+      '{' => depth += 1,
+      '}' => depth -= 1,
+      ',' if depth == 0 => {
+        alternatives.push(&inner[start..index]);
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+
+  alternatives.push(&inner[start..]);
+
+  if alternatives.len() == 1 {
+    let literal: Vec<char> = std::iter::once('{')
+      .chain(inner.iter().copied())
+      .chain(std::iter::once('}'))
+      .collect();
+
+    return path.starts_with(&literal) && glob_matches(rest, &path[literal.len()..]);
+  }
+
+  alternatives.into_iter().any(|alternative| {
+    let expanded: Vec<char> = alternative.iter().chain(rest).copied().collect();
+    glob_matches(&expanded, path)
+  })
+}
+
+pub fn decode(bytes: Vec<u8>, charset: Option<Charset>) -> Result<String, String> {
+  let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+    let units: Vec<u16> = bytes
+      .chunks_exact(2)
+      .map(|pair| from([pair[0], pair[1]]))
+      .collect();
+
+    String::from_utf16(&units).map_err(|e| e.to_string())
+  };
+
+  let text = match charset {
+    Some(Charset::Latin1) => bytes.iter().map(|&b| b as char).collect(),
+    Some(Charset::Utf16Be) => utf16(&bytes, u16::from_be_bytes)?,
+    Some(Charset::Utf16Le) => utf16(&bytes, u16::from_le_bytes)?,
+    Some(Charset::Utf8 | Charset::Utf8Bom) | None => {
+      String::from_utf8(bytes).map_err(|e| e.to_string())?
+    }
+  };
+
+  Ok(match text.strip_prefix(BOM) {
+    Some(stripped) if charset.is_some() => stripped.to_owned(),
+    _ => text,
+  })
+}
+
+pub fn encode(text: &str, charset: Option<Charset>) -> Result<Vec<u8>, String> {
+  let bytes = match charset {
+    Some(Charset::Latin1) => text
+      .chars()
+      .map(|c| u8::try_from(c).map_err(|_| format!("'{c}' cannot be saved as Latin-1")))
+      .collect::<Result<_, _>>()?,
+    Some(Charset::Utf8Bom) => {
+      let mut bytes = BOM.to_string().into_bytes();
+      bytes.extend_from_slice(text.as_bytes());
+      bytes
+    }
+    Some(Charset::Utf16Be) => std::iter::once(BOM as u16)
+      .chain(text.encode_utf16())
+      .flat_map(u16::to_be_bytes)
+      .collect(),
+    Some(Charset::Utf16Le) => std::iter::once(BOM as u16)
+      .chain(text.encode_utf16())
+      .flat_map(u16::to_le_bytes)
+      .collect(),
+    Some(Charset::Utf8) | None => text.as_bytes().to_vec(),
+  };
+
+  Ok(bytes)
+}
+
+pub fn normalize_line_endings(text: &str, end_of_line: EndOfLine) -> String {
+  text
+    .replace("\r\n", "\n")
+    .replace('\r', "\n")
+    .replace('\n', end_of_line.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn encodes_latin1_text() {
+    assert_eq!(
+      encode("café", Some(Charset::Latin1)),
+      Ok(vec![b'c', b'a', b'f', 0xE9])
+    );
+  }
+
+  #[test]
+  fn refuses_characters_outside_latin1() {
+    assert!(encode("naïve €", Some(Charset::Latin1)).is_err());
+  }
+
+  #[test]
+  fn round_trips_every_charset() {
+    let charsets = [
+      Charset::Latin1,
+      Charset::Utf8,
+      Charset::Utf8Bom,
+      Charset::Utf16Be,
+      Charset::Utf16Le,
+    ];
+
+    for charset in charsets {
+      let bytes = encode("café\n", Some(charset)).unwrap();
+      assert_eq!(decode(bytes, Some(charset)).unwrap(), "café\n");
+    }
+  }
+}
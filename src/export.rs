@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use iced::highlighter::{self, Stream};
+use iced::{Color, Font, Theme, font};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use crate::{constants, pdf, subset, transform};
+
+const LIST_MARKERS: &[char] = &['•', '◦', '▪'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+  Html { with_contents: bool },
+  Pdf(pdf::Settings),
+}
+
+impl Format {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Format::Html { .. } => "html",
+      Format::Pdf(_) => "pdf",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+  pub level: usize,
+  pub id: String,
+  pub text: String,
+}
+
+pub fn html(source: &str, name: &str, directory: Option<&Path>, with_contents: bool) -> String {
+  let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+    | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS
+    | Options::ENABLE_TABLES
+    | Options::ENABLE_STRIKETHROUGH
+    | Options::ENABLE_TASKLISTS
+    | Options::ENABLE_FOOTNOTES;
+
+  let mut events: Vec<Event> = Vec::new();
+  let mut headings: Vec<Heading> = Vec::new();
+  let mut slugs: HashMap<String, usize> = HashMap::new();
+  let mut code: Option<(String, String)> = None;
+  let mut heading: Option<usize> = None;
+  let mut is_metadata = false;
+
+  for event in Parser::new_ext(source, options) {
+    match event {
+      Event::Start(Tag::MetadataBlock(_)) => is_metadata = true,
+      Event::End(TagEnd::MetadataBlock(_)) => is_metadata = false,
+      _ if is_metadata => {}
+      Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language))) => {
+        let language = language.split_whitespace().next().unwrap_or_default();
+        code = Some((language.to_owned(), String::new()));
+      }
+      Event::Text(text) if code.is_some() => {
+        if let Some((_, content)) = &mut code {
+          content.push_str(&text);
+        }
+      }
+      Event::End(TagEnd::CodeBlock) if code.is_some() => {
+        if let Some((language, content)) = code.take() {
+          events.push(Event::Html(highlight(&content, &language).into()));
+        }
+      }
+      Event::Start(Tag::Heading { .. }) => {
+        heading = Some(events.len());
+        events.push(event);
+      }
+      Event::End(TagEnd::Heading(level)) => {
+        if let Some(start) = heading.take() {
+          let text = plain_text(&events[start + 1..]).trim().to_owned();
+
+          if let Event::Start(Tag::Heading { id, .. }) = &mut events[start] {
+            let slug = match id {
+              Some(id) => id.to_string(),
+              None => unique_slug(&mut slugs, &text),
+            };
+
+            *id = Some(CowStr::from(slug.clone()));
+            headings.push(Heading {
+              level: heading_level(level),
+              id: slug,
+              text,
+            });
+          }
+        }
+
+        events.push(Event::End(TagEnd::Heading(level)));
+      }
+      Event::Start(Tag::Image {
+        link_type,
+        dest_url,
+        title,
+        id,
+      }) => {
+        let dest_url = embed_image(&dest_url, directory)
+          .map(CowStr::from)
+          .unwrap_or(dest_url);
+
+        events.push(Event::Start(Tag::Image {
+          link_type,
+          dest_url,
+          title,
+          id,
+        }));
+      }
+      event => events.push(event),
+    }
+  }
+
+  let mut body = String::new();
+  pulldown_cmark::html::push_html(&mut body, events.into_iter());
+
+  let title = headings
+    .iter()
+    .find(|heading| heading.level == 1)
+    .map(|heading| heading.text.as_str())
+    .unwrap_or(name);
+
+  let contents = if with_contents {
+    table_of_contents(&headings)
+  } else {
+    String::new()
+  };
+
+  let characters = (' '..='~')
+    .chain(LIST_MARKERS.iter().copied())
+    .chain(contents.chars())
+    .chain(body.chars());
+
+  format!(
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+     <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+     <title>{}</title>\n<style>\n{}{}</style>\n</head>\n<body>\n{contents}<main>\n{body}</main>\n\
+     </body>\n</html>\n",
+    transform::html_encode(title),
+    font_face(characters),
+    stylesheet()
+  )
+}
+
+pub fn export(input: &Path, output: &Path, format: Format) -> Result<(), String> {
+  let source = std::fs::read_to_string(input).map_err(|e| format!("{}: {e}", input.display()))?;
+  let name = input
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .unwrap_or("Untitled");
+
+  let contents = match format {
+    Format::Html { with_contents } => {
+      html(&source, name, input.parent(), with_contents).into_bytes()
+    }
+    Format::Pdf(settings) => pdf::render(&source, name, input.parent(), settings)?,
+  };
+
+  std::fs::write(output, contents).map_err(|e| format!("{}: {e}", output.display()))
+}
+
+pub fn run(args: &[String]) -> Option<Result<(), String>> {
+  if !args.iter().any(|arg| arg == "--export") {
+    return None;
+  }
+
+  Some(arguments(args).and_then(|(input, output, format)| export(&input, &output, format)))
+}
+
+fn arguments(args: &[String]) -> Result<(PathBuf, PathBuf, Format), String> {
+  let mut format = None;
+  let mut input = None;
+  let mut output = None;
+  let mut with_contents = false;
+  let mut settings = pdf::Settings::default();
+  let mut args = args.iter().skip(1);
+
+  while let Some(arg) = args.next() {
+    let mut value = || match args.next() {
+      Some(value) => Ok(value.as_str()),
+      None => Err(format!("Missing value for {arg}")),
+    };
+
+    match arg.as_str() {
+      "--export" => format = Some(value()?),
+      "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+      "--toc" => with_contents = true,
+      "--page" => {
+        settings.page_size =
+          pdf::PageSize::parse(value()?).ok_or("Page size must be A4 or Letter")?;
+      }
+      "--margin" => {
+        settings.margin =
+          pdf::parse_length(value()?).ok_or("Margin must be a length such as 20mm")?;
+      }
+      "--font-size" => {
+        settings.font_size = pdf::parse_length(value()?)
+          .filter(|size| *size > 0.0)
+          .ok_or("Font size must be a positive number of points")?;
+      }
+      flag if flag.starts_with("--") => return Err(format!("Unknown option: {flag}")),
+      _ => input = Some(PathBuf::from(arg)),
+    }
+  }
+
+  let format = match format {
+    Some("html") => Format::Html { with_contents },
+    Some("pdf") => Format::Pdf(settings),
+    Some(format) => return Err(format!("Unsupported export format: {format}")),
+    None => return Err(String::from("Missing --export format")),
+  };
+
+  let input = input.ok_or(
+    "Usage: zoeae --export html|pdf <input> [-o <output>] [--toc] [--page A4|Letter] \
+     [--margin 20mm] [--font-size 12]",
+  )?;
+  let output = output.unwrap_or_else(|| input.with_extension(format.extension()));
+
+  Ok((input, output, format))
+}
+
+pub fn slug(text: &str) -> String {
+  text
+    .trim()
+    .to_lowercase()
+    .chars()
+    .filter_map(|c| match c {
+      c if c.is_alphanumeric() || c == '_' || c == '-' => Some(c),
+      c if c.is_whitespace() => Some('-'),
+      _ => None,
+    })
+    .collect()
+}
+
+pub fn unique_slug(slugs: &mut HashMap<String, usize>, text: &str) -> String {
+  let slug = slug(text);
+  let count = slugs.entry(slug.clone()).or_default();
+  *count += 1;
+
+  match *count {
+    1 => slug,
+    count => format!("{slug}-{}", count - 1),
+  }
+}
+
+fn heading_level(level: HeadingLevel) -> usize {
+  match level {
+    HeadingLevel::H1 => 1,
+    HeadingLevel::H2 => 2,
+    HeadingLevel::H3 => 3,
+    HeadingLevel::H4 => 4,
+    HeadingLevel::H5 => 5,
+    HeadingLevel::H6 => 6,
+  }
+}
+
+fn plain_text(events: &[Event]) -> String {
+  events
+    .iter()
+    .filter_map(|event| match event {
+      Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+      Event::SoftBreak | Event::HardBreak => Some(" "),
+      _ => None,
+    })
+    .collect()
+}
+
+fn table_of_contents(headings: &[Heading]) -> String {
+  if headings.is_empty() {
+    return String::new();
+  }
+
+  let top = headings
+    .iter()
+    .map(|heading| heading.level)
+    .min()
+    .unwrap_or(1);
+  let mut html = String::from("<nav class=\"contents\">\n<ul>\n");
+
+  for heading in headings {
+    let _ = writeln!(
+      html,
+      "<li class=\"level-{}\"><a href=\"#{}\">{}</a></li>",
+      heading.level - top + 1,
+      transform::html_encode(&heading.id),
+      transform::html_encode(&heading.text)
+    );
+  }
+
+  html.push_str("</ul>\n</nav>\n");
+  html
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+  pub text: String,
+  pub color: Option<Color>,
+  pub font: Option<Font>,
+}
+
+pub fn highlight_lines(code: &str, language: &str, theme: highlighter::Theme) -> Vec<Vec<Token>> {
+  let mut stream = Stream::new(&highlighter::Settings {
+    theme,
+    token: language.to_owned(),
+  });
+
+  code
+    .lines()
+    .map(|line| {
+      let mut tokens = Vec::new();
+      let mut end = 0;
+
+      for (range, highlight) in stream.highlight_line(line) {
+        let start = range.start.max(end);
+
+        if start > end {
+          tokens.push(Token {
+            text: line[end..start].to_owned(),
+            color: None,
+            font: None,
+          });
+        }
+
+        if range.end > start {
+          tokens.push(Token {
+            text: line[start..range.end].to_owned(),
+            color: highlight.color(),
+            font: highlight.font(),
+          });
+        }
+
+        end = end.max(range.end);
+      }
+
+      if end < line.len() {
+        tokens.push(Token {
+          text: line[end..].to_owned(),
+          color: None,
+          font: None,
+        });
+      }
+
+      stream.commit();
+      tokens
+    })
+    .collect()
+}
+
+fn highlight(code: &str, language: &str) -> String {
+  let mut html = String::from("<pre class=\"code\"><code>");
+
+  for line in highlight_lines(code, language, highlighter::Theme::Base16Ocean) {
+    for token in line {
+      let style = span_style(token.color, token.font);
+      let text = transform::html_encode(&token.text);
+
+      if style.is_empty() {
+        html.push_str(&text);
+      } else {
+        let _ = write!(html, "<span style=\"{style}\">{text}</span>");
+      }
+    }
+
+    html.push('\n');
+  }
+
+  html.push_str("</code></pre>\n");
+  html
+}
+
+fn span_style(color: Option<Color>, font: Option<Font>) -> String {
+  let mut style = String::new();
+
+  if let Some(color) = color {
+    let _ = write!(style, "color:{};", hex(color));
+  }
+
+  if let Some(font) = font {
+    if font.weight == font::Weight::Bold {
+      style.push_str("font-weight:bold;");
+    }
+
+    if font.style == font::Style::Italic {
+      style.push_str("font-style:italic;");
+    }
+  }
+
+  style
+}
+
+fn embed_image(url: &str, directory: Option<&Path>) -> Option<String> {
+  if url.is_empty() || url.contains("://") || url.starts_with("data:") || url.starts_with('#') {
+    return None;
+  }
+
+  let path = Path::new(url.split(['?', '#']).next().unwrap_or(url));
+  let path = match directory {
+    Some(directory) if path.is_relative() => directory.join(path),
+    _ => path.to_path_buf(),
+  };
+
+  let bytes = std::fs::read(&path).ok()?;
+
+  Some(format!(
+    "data:{};base64,{}",
+    mime_type(&path),
+    transform::base64_encode(&bytes)
+  ))
+}
+
+fn mime_type(path: &Path) -> &'static str {
+  let extension = path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .unwrap_or_default()
+    .to_lowercase();
+
+  match extension.as_str() {
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "svg" => "image/svg+xml",
+    "webp" => "image/webp",
+    "bmp" => "image/bmp",
+    "ico" => "image/x-icon",
+    "avif" => "image/avif",
+    _ => "application/octet-stream",
+  }
+}
+
+fn hex(color: Color) -> String {
+  let [r, g, b, a] = color.into_rgba8();
+
+  if a == u8::MAX {
+    format!("#{r:02x}{g:02x}{b:02x}")
+  } else {
+    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+  }
+}
+
+fn font_face(characters: impl Iterator<Item = char>) -> String {
+  match subset::subset(constants::CUSTOM_FONT_BYTES, characters) {
+    Ok(font) => format!(
+      "@font-face {{ font-family: '{}'; src: url(data:font/ttf;base64,{}) format('truetype'); }}\n",
+      font_name(),
+      transform::base64_encode(&font)
+    ),
+    Err(_) => String::new(),
+  }
+}
+
+fn font_name() -> &'static str {
+  match constants::CUSTOM_FONT.family {
+    font::Family::Name(name) => name,
+    _ => "monospace",
+  }
+}
+
+fn stylesheet() -> String {
+  let palette = Theme::Dark.palette();
+  let size = constants::DEFAULT_EDITOR_FONT_SIZE as f32;
+  let background = hex(palette.background);
+  let text = hex(palette.text);
+  let link = hex(palette.primary);
+  let code = hex(Color::from_rgb8(0x11, 0x11, 0x11));
+
+  format!(
+    "body {{ margin: 0; background: {background}; color: {text}; \
+     font-family: '{font}', monospace; font-size: {size}px; line-height: {line_height}; }}\n\
+     main {{ max-width: 60em; padding: 10px 2em; }}\n\
+     main > * {{ margin: 0 0 {spacing}px; }}\n\
+     h1 {{ font-size: {h1}px; }}\n\
+     h2 {{ font-size: {h2}px; }}\n\
+     h3 {{ font-size: {h3}px; }}\n\
+     h4 {{ font-size: {h4}px; }}\n\
+     h5, h6 {{ font-size: {size}px; }}\n\
+     a {{ color: {link}; }}\n\
+     code {{ font-family: inherit; background: {code}; color: #ffffff; \
+     border-radius: 4px; padding: 0 1px; }}\n\
+     pre {{ background: {code}; color: #ffffff; padding: {padding}px; border-radius: 2px; \
+     overflow-x: auto; font-size: {code_size}px; }}\n\
+     pre code {{ background: none; padding: 0; }}\n\
+     blockquote {{ margin-left: 0; padding-left: 1em; border-left: 4px solid {link}; }}\n\
+     table {{ border-collapse: collapse; }}\n\
+     th, td {{ border: 1px solid {text}40; padding: 4px 8px; }}\n\
+     img {{ max-width: 100%; }}\n\
+     hr {{ border: none; border-top: 1px solid {text}40; }}\n\
+     nav.contents {{ padding: 10px 2em; border-bottom: 1px solid {text}40; }}\n\
+     nav.contents ul {{ list-style: none; margin: 0; padding: 0; }}\n\
+     nav.contents .level-2 {{ padding-left: 1.5em; }}\n\
+     nav.contents .level-3 {{ padding-left: 3em; }}\n\
+     nav.contents .level-4 {{ padding-left: 4.5em; }}\n\
+     nav.contents .level-5, nav.contents .level-6 {{ padding-left: 6em; }}\n",
+    font = font_name(),
+    line_height = constants::LINE_HEIGHT,
+    spacing = size * 0.875,
+    h1 = size * 2.0,
+    h2 = size * 1.75,
+    h3 = size * 1.5,
+    h4 = size * 1.25,
+    code_size = size * 0.75,
+    padding = size * 0.75 / 4.0 + 5.0,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &str) -> Result<(PathBuf, PathBuf, Format), String> {
+    let args: Vec<String> = ["zoeae"]
+      .into_iter()
+      .chain(args.split_whitespace())
+      .map(String::from)
+      .collect();
+
+    arguments(&args)
+  }
+
+  #[test]
+  fn ignores_arguments_without_export() {
+    assert!(run(&[String::from("zoeae"), String::from("notes.md")]).is_none());
+  }
+
+  #[test]
+  fn derives_the_output_from_the_input() {
+    let (input, output, format) = parse("--export html notes.md --toc").unwrap();
+
+    assert_eq!(input, PathBuf::from("notes.md"));
+    assert_eq!(output, PathBuf::from("notes.html"));
+    assert_eq!(
+      format,
+      Format::Html {
+        with_contents: true
+      }
+    );
+  }
+
+  #[test]
+  fn reads_pdf_settings() {
+    let (_, output, format) =
+      parse("--export pdf in.md -o out.pdf --page Letter --font-size 10").unwrap();
+
+    let Format::Pdf(settings) = format else {
+      panic!("expected a PDF export");
+    };
+
+    assert_eq!(output, PathBuf::from("out.pdf"));
+    assert_eq!(settings.page_size, pdf::PageSize::Letter);
+    assert_eq!(settings.font_size, 10.0);
+  }
+
+  #[test]
+  fn reports_a_missing_format() {
+    assert_eq!(
+      parse("-o --export in.md").unwrap_err(),
+      "Missing --export format"
+    );
+    assert_eq!(
+      parse("in.md --export").unwrap_err(),
+      "Missing value for --export"
+    );
+  }
+
+  #[test]
+  fn rejects_bad_options() {
+    assert_eq!(
+      parse("--export docx in.md").unwrap_err(),
+      "Unsupported export format: docx"
+    );
+    assert_eq!(
+      parse("--export pdf in.md --colour").unwrap_err(),
+      "Unknown option: --colour"
+    );
+    assert!(parse("--export pdf in.md --font-size 0").is_err());
+    assert!(parse("--export pdf").is_err());
+  }
+
+  #[test]
+  fn numbers_repeated_slugs() {
+    let mut slugs = HashMap::new();
+
+    assert_eq!(unique_slug(&mut slugs, "Hello, World!"), "hello-world");
+    assert_eq!(unique_slug(&mut slugs, "Hello World"), "hello-world-1");
+  }
+
+  #[test]
+  fn escapes_the_title_and_links_headings() {
+    let page = html("# A & B\n", "notes", None, true);
+
+    assert!(page.contains("<title>A &amp; B</title>"));
+    assert!(page.contains("id=\"a--b\""));
+    assert!(page.contains("href=\"#a--b\""));
+    assert!(html("text", "<notes>", None, false).contains("<title>&lt;notes&gt;</title>"));
+  }
+}
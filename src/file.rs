@@ -1,59 +1,1953 @@
 use std::{
+  collections::{HashMap, HashSet},
   ffi,
+  ops::{Range, RangeInclusive},
   path::{Path, PathBuf},
+  sync::{
+    Arc,
+    atomic::{self, AtomicU64},
+  },
 };
 
-use iced::widget::{markdown, text_editor};
+use iced::widget::text_editor;
+
+use crate::blocks::{self, Block};
+use crate::editorconfig::{self, IndentSize, Properties};
+use crate::folding::{self, Folds};
+use crate::front_matter::{self, FrontMatter};
+use crate::highlighter::{Decoration, Decorations};
+use crate::history::{Change, Document, History, Kind};
+use crate::indent::{self, Indent};
+use crate::message::{LineAction, TableAction, TransformAction};
+use crate::outline::{self, Heading};
+use crate::snippets::{self, Snippet};
+use crate::spelling::{self, Dictionary};
+use crate::{
+  brackets, comments, completion, constants, cursors, lines, table, transform, whitespace,
+};
+
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
 
 pub struct File {
   needs_saving: bool,
   content: text_editor::Content,
   path: Option<PathBuf>,
-  markdown: Vec<markdown::Item>,
+  markdown: Vec<Block>,
+  outline: Vec<Heading>,
+  outline_revision: u64,
+  front_matter: Option<FrontMatter>,
+  front_matter_revision: u64,
+  revision: u64,
+  requested_revision: u64,
+  parsed_revision: u64,
+  cursors: Vec<text_editor::Cursor>,
+  block: Option<cursors::Block>,
+  history: History,
+  indent: Indent,
+  folds: Folds,
+  fold_regions: Vec<folding::Region>,
+  fold_regions_revision: u64,
+  editorconfig: Properties,
+  snippet: Option<snippets::Session>,
+  closers: Vec<brackets::Closer>,
+  spell_language: Option<String>,
+  ignored_words: HashSet<String>,
+  misspellings: Vec<(usize, Range<usize>)>,
+  spell_revision: u64,
+  words: HashMap<String, usize>,
+  words_revision: u64,
+  code_lines: Vec<bool>,
+  code_lines_revision: u64,
+}
+
+impl Default for File {
+  fn default() -> Self {
+    File {
+      needs_saving: true,
+      content: text_editor::Content::new(),
+      path: None,
+      markdown: Vec::new(),
+      outline: Vec::new(),
+      outline_revision: 0,
+      front_matter: None,
+      front_matter_revision: 0,
+      revision: next_revision(),
+      requested_revision: 0,
+      parsed_revision: 0,
+      cursors: Vec::new(),
+      block: None,
+      history: History::default(),
+      indent: Indent::default(),
+      folds: Folds::default(),
+      fold_regions: Vec::new(),
+      fold_regions_revision: 0,
+      editorconfig: Properties::default(),
+      snippet: None,
+      closers: Vec::new(),
+      spell_language: None,
+      ignored_words: HashSet::new(),
+      misspellings: Vec::new(),
+      spell_revision: 0,
+      words: HashMap::new(),
+      words_revision: 0,
+      code_lines: Vec::new(),
+      code_lines_revision: 0,
+    }
+  }
 }
 
-impl Default for File {
-  fn default() -> Self {
-    File {
-      needs_saving: true,
-      content: text_editor::Content::new(),
-      path: None,
-      markdown: Vec::new(),
+impl File {
+  pub fn from(content: &str, path: Option<PathBuf>) -> Self {
+    let text_editor_content = text_editor::Content::with_text(content);
+    let revision = next_revision();
+    let markdown = blocks::assemble(
+      &mut Vec::new(),
+      blocks::parse(blocks::request(revision, content.to_owned(), &[])),
+    );
+
+    File {
+      needs_saving: false,
+      content: text_editor_content,
+      path,
+      markdown,
+      outline: Vec::new(),
+      outline_revision: 0,
+      front_matter: None,
+      front_matter_revision: 0,
+      revision,
+      requested_revision: revision,
+      parsed_revision: revision,
+      cursors: Vec::new(),
+      block: None,
+      history: History::default(),
+      indent: indent::detect(content).unwrap_or_default(),
+      folds: Folds::default(),
+      fold_regions: Vec::new(),
+      fold_regions_revision: 0,
+      editorconfig: Properties::default(),
+      snippet: None,
+      closers: Vec::new(),
+      spell_language: None,
+      ignored_words: HashSet::new(),
+      misspellings: Vec::new(),
+      spell_revision: 0,
+      words: HashMap::new(),
+      words_revision: 0,
+      code_lines: Vec::new(),
+      code_lines_revision: 0,
+    }
+  }
+
+  pub fn content(&self) -> &text_editor::Content {
+    &self.content
+  }
+
+  pub fn set_content(&mut self, content: &str) {
+    self.content = text_editor::Content::with_text(content);
+    self.revision = next_revision();
+    self.cursors.clear();
+    self.block = None;
+    self.history.clear();
+    self.indent = indent::detect(content).unwrap_or_default();
+    self.folds.clear();
+    self.snippet = None;
+    self.closers.clear();
+    self.misspellings.clear();
+  }
+
+  pub fn set_editorconfig(&mut self, properties: Properties) {
+    self.indent = properties.indent(self.indent);
+    self.editorconfig = properties;
+  }
+
+  pub fn editorconfig(&self) -> &Properties {
+    &self.editorconfig
+  }
+
+  pub fn tab_width(&self) -> usize {
+    let indent_size = match self.editorconfig.indent_size {
+      Some(IndentSize::Columns(size)) => Some(size),
+      _ => None,
+    };
+
+    self
+      .editorconfig
+      .tab_width
+      .or(indent_size)
+      .filter(|width| *width > 0)
+      .unwrap_or(constants::DEFAULT_TAB_WIDTH)
+  }
+
+  pub fn text(&self) -> String {
+    self.folds.source_text(&self.content, self.line_ending())
+  }
+
+  pub fn encoded(&self) -> Result<Vec<u8>, String> {
+    let text = match self.editorconfig.end_of_line {
+      Some(end_of_line) => editorconfig::normalize_line_endings(&self.text(), end_of_line),
+      None => self.text(),
+    };
+
+    editorconfig::encode(&text, self.editorconfig.charset)
+  }
+
+  pub fn perform(&mut self, action: text_editor::Action) {
+    if !matches!(
+      action,
+      text_editor::Action::Edit(_)
+        | text_editor::Action::Move(_)
+        | text_editor::Action::Select(_)
+        | text_editor::Action::Scroll { .. }
+    ) {
+      self.snippet = None;
+    }
+
+    match &action {
+      text_editor::Action::Edit(edit) => {
+        let (first, last) = self.edited_lines(edit);
+        self.revealing(first, last, |file| file.dispatch(action));
+      }
+      _ => self.dispatch(action),
+    }
+
+    self.prune_closers();
+  }
+
+  fn prune_closers(&mut self) {
+    if self.closers.is_empty() {
+      return;
+    }
+
+    let cursors = self.cursors();
+    let content = &self.content;
+
+    self.closers.retain(|closer| {
+      let Some(column) = content
+        .line(closer.line)
+        .and_then(|line| closer.column(&line.text))
+      else {
+        return false;
+      };
+
+      cursors.iter().any(|cursor| {
+        cursor.selection.is_none()
+          && cursor.position.line == closer.line
+          && cursor.position.column <= column
+      })
+    });
+  }
+
+  fn edited_lines(&self, edit: &text_editor::Edit) -> (usize, usize) {
+    let (first, last) = self.cursor_lines();
+
+    let joins_previous = matches!(edit, text_editor::Edit::Backspace)
+      && self
+        .cursors()
+        .iter()
+        .any(|cursor| cursor.selection.is_none() && cursor.position.column == 0);
+
+    if joins_previous {
+      (first.saturating_sub(1), last)
+    } else {
+      (first, last)
+    }
+  }
+
+  fn cursor_lines(&self) -> (usize, usize) {
+    let cursors = self.cursors();
+    let first = cursors.iter().map(|c| cursors::bounds(c).0.line).min();
+    let last = cursors.iter().map(|c| cursors::bounds(c).1.line).max();
+
+    (first.unwrap_or(0), last.unwrap_or(0))
+  }
+
+  fn dispatch(&mut self, action: text_editor::Action) {
+    let position = self.content.cursor().position;
+    let block = self.block.take();
+
+    match &action {
+      text_editor::Action::Edit(edit) => {
+        let (first, last) = self.cursor_lines();
+        self.remember(Kind::of(edit), first, last);
+      }
+      text_editor::Action::Scroll { .. } => {}
+      _ => self.history.break_group(),
+    }
+
+    let expanded = match action {
+      text_editor::Action::SelectWord => cursors::word_at(&self.content, position),
+      text_editor::Action::SelectLine => cursors::line_at(&self.content, position),
+      _ => None,
+    };
+
+    if let Some(cursor) = expanded {
+      self.cursors.clear();
+      cursors::place(&mut self.content, cursor);
+      return;
+    }
+
+    if let text_editor::Action::Edit(text_editor::Edit::Enter) = action {
+      self.break_lines();
+      return;
+    }
+
+    if let text_editor::Action::Edit(text_editor::Edit::Insert(c)) = action
+      && self.insert_paired(c)
+    {
+      return;
+    }
+
+    if let text_editor::Action::Edit(text_editor::Edit::Backspace) = action
+      && self.delete_pair()
+    {
+      return;
+    }
+
+    if self.cursors.is_empty() {
+      self.content.perform(action);
+      return;
+    }
+
+    match action {
+      text_editor::Action::Edit(edit) => {
+        let pasted = match &edit {
+          text_editor::Edit::Paste(text) => Some(text.lines().collect::<Vec<_>>()),
+          _ => None,
+        };
+
+        let cursors = self.cursors();
+        let spread = pasted.filter(|lines| lines.len() == cursors.len());
+
+        let is_deletion = matches!(
+          edit,
+          text_editor::Edit::Backspace | text_editor::Edit::Delete
+        );
+        let skips_carets = is_deletion && block.is_some_and(|b| b.has_width());
+
+        let edited = cursors::edit_all(&mut self.content, &cursors, |rank, cursor| {
+          if skips_carets && cursor.selection.is_none() {
+            return None;
+          }
+
+          match &spread {
+            Some(lines) => Some(text_editor::Edit::Paste(Arc::new(lines[rank].to_owned()))),
+            None => Some(edit.clone()),
+          }
+        });
+
+        self.set_cursors(edited);
+      }
+      text_editor::Action::Move(_) | text_editor::Action::Select(_) => {
+        let cursors = self.cursors();
+        let moved = cursors::perform_all(&mut self.content, &cursors, &action);
+        self.set_cursors(moved);
+      }
+      text_editor::Action::Scroll { .. } => self.content.perform(action),
+      _ => {
+        self.cursors.clear();
+        self.content.perform(action);
+      }
+    }
+  }
+
+  pub fn cursors(&self) -> Vec<text_editor::Cursor> {
+    let mut cursors = vec![self.content.cursor()];
+    cursors.extend(self.cursors.iter().copied());
+    cursors
+  }
+
+  fn set_cursors(&mut self, mut cursors: Vec<text_editor::Cursor>) {
+    cursors::dedup(&mut cursors);
+
+    if cursors.is_empty() {
+      return;
+    }
+
+    let primary = cursors.remove(0);
+    cursors::place(&mut self.content, primary);
+    self.cursors = cursors;
+  }
+
+  pub fn trailing_carets(&self) -> impl Iterator<Item = text_editor::Position> + '_ {
+    self
+      .cursors
+      .iter()
+      .map(|cursor| cursor.position)
+      .filter(|position| {
+        self
+          .content
+          .line(position.line)
+          .is_some_and(|line| position.column >= line.text.len())
+      })
+  }
+
+  pub fn cursor_count(&self) -> usize {
+    self.cursors.len() + 1
+  }
+
+  pub fn add_cursor_at(&mut self, action: text_editor::Action) {
+    let mut cursors = self.cursors();
+    self.content.perform(action);
+    cursors.insert(0, self.content.cursor());
+    self.set_cursors(cursors);
+  }
+
+  pub fn select_block(&mut self, block: cursors::Block) {
+    let cursors = cursors::block_cursors(&self.content, &block, self.tab_width());
+    self.set_cursors(cursors);
+    self.block = Some(block);
+  }
+
+  pub fn drag_block(&mut self, action: text_editor::Action) {
+    let anchor = match self.block {
+      Some(block) => block.anchor,
+      None => cursors::visual_position(
+        &self.content,
+        self.content.cursor().position,
+        self.tab_width(),
+      ),
+    };
+
+    self.cursors.clear();
+    self.content.perform(action);
+
+    let head = cursors::visual_position(
+      &self.content,
+      self.content.cursor().position,
+      self.tab_width(),
+    );
+    self.select_block(cursors::Block { anchor, head });
+  }
+
+  pub fn extend_block(&mut self, lines: isize, columns: isize) {
+    let block = self.block.unwrap_or_else(|| {
+      let caret = cursors::visual_position(
+        &self.content,
+        self.content.cursor().position,
+        self.tab_width(),
+      );
+
+      cursors::Block {
+        anchor: caret,
+        head: caret,
+      }
+    });
+
+    let last_line = self.content.line_count().saturating_sub(1);
+
+    let head = (
+      block.head.0.saturating_add_signed(lines).min(last_line),
+      block.head.1.saturating_add_signed(columns),
+    );
+
+    self.select_block(cursors::Block { head, ..block });
+  }
+
+  pub fn add_cursor_vertically(&mut self, lines: isize) {
+    let cursors = self.cursors();
+
+    let edge = if lines < 0 {
+      cursors
+        .iter()
+        .min_by(|a, b| cursors::compare(&a.position, &b.position))
+    } else {
+      cursors
+        .iter()
+        .max_by(|a, b| cursors::compare(&a.position, &b.position))
+    };
+
+    if let Some(cursor) = edge.and_then(|c| cursors::shifted(&self.content, c, lines)) {
+      self.cursors.push(cursor);
+      self.set_cursors(self.cursors());
+    }
+  }
+
+  pub fn add_next_occurrence(&mut self) {
+    let primary = self.content.cursor();
+
+    if primary.selection.is_none() {
+      if let Some(word) = cursors::word_at(&self.content, primary.position) {
+        cursors::place(&mut self.content, word);
+      }
+
+      return;
+    }
+
+    let mut cursors = self.cursors();
+    let needle = cursors::selected_text(&self.content, &self.content.text(), &primary);
+
+    if let Some(next) = cursors::next_occurrence(&self.content, &cursors, &needle) {
+      cursors.insert(0, next);
+      self.set_cursors(cursors);
+    }
+  }
+
+  pub fn clear_cursors(&mut self) {
+    self.cursors.clear();
+    self.block = None;
+  }
+
+  pub fn selections(&self) -> Option<String> {
+    if self.cursors.is_empty() {
+      return self.content.selection();
+    }
+
+    let text = self.content.text();
+    let mut cursors = self.cursors();
+    cursors.sort_by(|a, b| cursors::compare(&a.position, &b.position));
+
+    let selected: Vec<String> = cursors
+      .iter()
+      .filter(|cursor| self.block.is_some() || cursor.selection.is_some())
+      .map(|cursor| cursors::selected_text(&self.content, &text, cursor))
+      .collect();
+
+    if selected.iter().all(String::is_empty) {
+      None
+    } else {
+      Some(selected.join("\n"))
+    }
+  }
+
+  pub fn delete_selections(&mut self) {
+    let (first, last) = self.cursor_lines();
+
+    self.revealing(first, last, |file| {
+      file.remember(Kind::Command, first, last);
+      let cursors = file.cursors();
+
+      let edited = cursors::edit_all(&mut file.content, &cursors, |_, cursor| {
+        cursor.selection.map(|_| text_editor::Edit::Delete)
+      });
+
+      file.set_cursors(edited);
+      file.block = None;
+    });
+  }
+
+  fn remember(&mut self, kind: Kind, first: usize, last: usize) {
+    self.revision = next_revision();
+
+    let last = last.min(self.content.line_count().saturating_sub(1));
+    let lines = self.folds.source_line(first).saturating_sub(1)..self.folds.source_line(last) + 2;
+
+    self
+      .history
+      .record(&source(&self.content, &self.folds), lines, kind);
+  }
+
+  pub fn begin_transaction(&mut self) {
+    self.history.hold(&source(&self.content, &self.folds));
+  }
+
+  pub fn end_transaction(&mut self) {
+    self.history.release(&source(&self.content, &self.folds));
+  }
+
+  fn restore(&mut self, change: Change) {
+    let folded = self.folds.source_ranges();
+
+    self.unfold_all();
+    self.revision = next_revision();
+    self.cursors.clear();
+    self.block = None;
+
+    let end = change.end();
+
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: end,
+        selection: (end != change.start).then_some(change.start),
+      },
+    );
+
+    let edit = if change.inserted.is_empty() {
+      text_editor::Edit::Delete
+    } else {
+      let text = change.inserted.replace('\n', self.line_ending());
+      text_editor::Edit::Paste(Arc::new(text))
+    };
+
+    self.content.perform(text_editor::Action::Edit(edit));
+
+    let added = change.inserted.matches('\n').count() as isize
+      - change.removed.matches('\n').count() as isize;
+
+    self.shift_misspellings(change.start.line, end.line, added);
+    cursors::place(&mut self.content, change.after);
+
+    let kept = folded.into_iter().filter_map(|range| {
+      if range.end <= change.start.line {
+        Some(range)
+      } else if range.start > end.line {
+        let shift = |line: usize| line.saturating_add_signed(added);
+        Some(shift(range.start)..shift(range.end))
+      } else {
+        None
+      }
+    });
+
+    for range in kept.collect::<Vec<_>>().into_iter().rev() {
+      self.fold_lines(range.start, range.end - 1);
+    }
+  }
+
+  pub fn undo(&mut self) -> bool {
+    match self.history.undo(&source(&self.content, &self.folds)) {
+      Some(change) => {
+        self.restore(change);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn redo(&mut self) -> bool {
+    match self.history.redo(&source(&self.content, &self.folds)) {
+      Some(change) => {
+        self.restore(change);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn replace(&mut self, start: text_editor::Position, end: text_editor::Position, text: &str) {
+    self.remember(Kind::Command, start.line, end.line);
+    self.cursors.clear();
+    self.block = None;
+
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: end,
+        selection: Some(start),
+      },
+    );
+
+    self
+      .content
+      .perform(text_editor::Action::Edit(text_editor::Edit::Paste(
+        Arc::new(text.to_owned()),
+      )));
+  }
+
+  pub fn word_before_cursor(&self) -> Option<(text_editor::Position, String)> {
+    let cursor = self.content.cursor();
+
+    if !self.cursors.is_empty() || cursor.selection.is_some() {
+      return None;
+    }
+
+    let position = cursor.position;
+    let line = self.line_text(position.line);
+    let before = &line[..position.column];
+
+    let start = cursors::word_start(&line, position.column);
+
+    Some((
+      text_editor::Position {
+        line: position.line,
+        column: start,
+      },
+      before[start..].to_owned(),
+    ))
+  }
+
+  pub fn trigger_snippet(&mut self, library: &snippets::Library) -> bool {
+    let Some((start, prefix)) = self.word_before_cursor() else {
+      return false;
+    };
+
+    let Some(snippet) = library.find(self.extension(), &prefix) else {
+      return false;
+    };
+
+    let end = self.content.cursor().position;
+    self.expand_snippet(snippet, start, end, String::new());
+    true
+  }
+
+  pub fn insert_snippet(&mut self, snippet: &Snippet) {
+    let primary = self.content.cursor();
+    let (start, end) = cursors::bounds(&primary);
+    let selection = self.content.selection().unwrap_or_default();
+
+    self.cursors.clear();
+    self.expand_snippet(snippet, start, end, selection);
+  }
+
+  fn expand_snippet(
+    &mut self,
+    snippet: &Snippet,
+    start: text_editor::Position,
+    end: text_editor::Position,
+    selection: String,
+  ) {
+    let line = self.line_text(start.line);
+    let variables = snippets::Variables {
+      date: snippets::today(),
+      filename: self
+        .path
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+      selection,
+    };
+
+    let expansion = snippets::expand(&snippet.body, &variables, indent::leading_whitespace(&line));
+
+    self.revealing(start.line, end.line, |file| {
+      file.replace(start, end, &expansion.text)
+    });
+
+    let base = cursors::offset(&self.content, start);
+    let session = snippets::Session::new(base, expansion, cursors::len(&self.content));
+
+    self.select_tab_stop(session);
+  }
+
+  pub fn end_snippet(&mut self) {
+    self.snippet = None;
+  }
+
+  pub fn jump_tab_stop(&mut self, step: isize) -> bool {
+    let Some(mut session) = self.snippet.take() else {
+      return false;
+    };
+
+    if !session.settle(cursors::len(&self.content)) {
+      return false;
+    }
+
+    session.advance(step);
+    self.select_tab_stop(session);
+    true
+  }
+
+  fn select_tab_stop(&mut self, session: snippets::Session) {
+    let cursors = session
+      .current()
+      .iter()
+      .map(|range| text_editor::Cursor {
+        position: cursors::position(&self.content, range.end),
+        selection: (!range.is_empty()).then(|| cursors::position(&self.content, range.start)),
+      })
+      .collect();
+
+    self.history.break_group();
+    self.set_cursors(cursors);
+
+    if !session.is_final() {
+      self.snippet = Some(session);
+    }
+  }
+
+  pub fn line_ending(&self) -> &'static str {
+    match self.content.line_ending() {
+      Some(text_editor::LineEnding::None) | None => "\n",
+      Some(ending) => ending.as_str(),
+    }
+  }
+
+  fn line_text(&self, line: usize) -> String {
+    self
+      .content
+      .line(line)
+      .map(|line| line.text.into_owned())
+      .unwrap_or_default()
+  }
+
+  fn replace_lines(&mut self, first: usize, last: usize, replacement: &[String]) {
+    let last_column = self.line_text(last).len();
+    let at = |line, column| text_editor::Position { line, column };
+
+    if !replacement.is_empty() {
+      let text = replacement.join(self.line_ending());
+      self.replace(at(first, 0), at(last, last_column), &text);
+    } else if last + 1 < self.content.line_count() {
+      self.replace(at(first, 0), at(last + 1, 0), "");
+    } else if first > 0 {
+      let column = self.line_text(first - 1).len();
+      self.replace(at(first - 1, column), at(last, last_column), "");
+    } else {
+      self.replace(at(first, 0), at(last, last_column), "");
+    }
+  }
+
+  pub fn apply_table_action(&mut self, action: TableAction) -> Option<bool> {
+    let cursor = self.content.cursor();
+
+    if !self.cursors.is_empty()
+      || cursor
+        .selection
+        .is_some_and(|selection| selection.line != cursor.position.line)
+    {
+      return None;
+    }
+
+    let position = cursor.position;
+    let range = table::bounds(position.line, |line| {
+      self.content.line(line).map(|line| line.text.into_owned())
+    })?;
+
+    let (first, last) = (range.start, range.end - 1);
+    let original: Vec<String> = range.map(|line| self.line_text(line)).collect();
+
+    let edit = table::apply(action, &original, position.line - first, position.column)?;
+    let is_changed = edit.lines != original;
+
+    if is_changed {
+      self.revealing(first, last, |file| {
+        file.replace_lines(first, last, &edit.lines)
+      });
+    }
+
+    let at = |column| text_editor::Position {
+      line: first + edit.line,
+      column,
+    };
+
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: at(edit.columns.end),
+        selection: (!edit.columns.is_empty()).then(|| at(edit.columns.start)),
+      },
+    );
+
+    Some(is_changed)
+  }
+
+  pub fn apply_line_action(&mut self, action: LineAction) {
+    let (first, last) = if action.spans_document() {
+      (0, usize::MAX)
+    } else {
+      let (first, last) = self.selected_lines();
+      (first.saturating_sub(1), last + 1)
+    };
+
+    self.revealing(first, last, |file| file.transform_lines(action));
+  }
+
+  fn transform_lines(&mut self, action: LineAction) {
+    let cursor = self.content.cursor();
+    let (start, end) = cursors::bounds(&cursor);
+    let count = self.content.line_count();
+
+    let mut first = start.line;
+    let mut last = end.line;
+
+    if last > first && end.column == 0 {
+      last -= 1;
+    }
+
+    let selected = (first, last);
+
+    if first == last && action.spans_document() {
+      first = 0;
+      last = count.saturating_sub(1);
+    }
+
+    match action {
+      LineAction::MoveUp if first == 0 => return,
+      LineAction::MoveUp => first -= 1,
+      LineAction::MoveDown if last + 1 >= count => return,
+      LineAction::MoveDown => last += 1,
+      LineAction::Join if first == last && last + 1 >= count => return,
+      LineAction::Join if first == last => last += 1,
+      _ => {}
+    }
+
+    let original: Vec<String> = (first..=last).map(|line| self.line_text(line)).collect();
+    let join_column = original.first().map_or(0, |line| line.trim_end().len());
+    let replacement = lines::transform(action, original);
+    let size = selected.1 - selected.0;
+
+    let (top, column) = match action {
+      LineAction::Duplicate => (selected.1 + 1, start.column),
+      LineAction::Delete => (first, 0),
+      LineAction::MoveUp => (selected.0 - 1, start.column),
+      LineAction::MoveDown => (selected.0 + 1, start.column),
+      LineAction::Join => (first, join_column),
+      _ => (selected.0, start.column),
+    };
+
+    self.replace_lines(first, last, &replacement);
+
+    let last_line = self.content.line_count().saturating_sub(1);
+    let top = top.min(last_line);
+
+    let position = |line: usize, column: usize| {
+      let text = self.line_text(line);
+
+      text_editor::Position {
+        line,
+        column: cursors::floor_char_boundary(&text, column),
+      }
+    };
+
+    let restored = if cursor.selection.is_some() && action != LineAction::Join {
+      let bottom = (top + size).min(last_line);
+
+      text_editor::Cursor {
+        position: position(bottom, usize::MAX),
+        selection: Some(position(top, 0)),
+      }
+    } else {
+      cursors::caret(position(top, column))
+    };
+
+    cursors::place(&mut self.content, restored);
+  }
+
+  pub fn indent(&self) -> Indent {
+    self.indent
+  }
+
+  fn edit_each(&mut self, insertions: Vec<cursors::Insertion>) {
+    let cursors: Vec<text_editor::Cursor> = insertions.iter().map(|i| i.cursor).collect();
+
+    let edited = cursors::edit_all(&mut self.content, &cursors, |_, cursor| {
+      insertions
+        .iter()
+        .find(|insertion| insertion.cursor == *cursor)
+        .map(|insertion| text_editor::Edit::Paste(Arc::new(insertion.text.clone())))
+    });
+
+    let adjusted = edited
+      .into_iter()
+      .zip(&insertions)
+      .map(|(cursor, insertion)| {
+        if insertion.shift == 0 && insertion.selected == 0 {
+          return cursor;
+        }
+
+        let at =
+          cursors::offset(&self.content, cursor.position).saturating_add_signed(insertion.shift);
+
+        text_editor::Cursor {
+          position: cursors::position(&self.content, at),
+          selection: (insertion.selected > 0)
+            .then(|| cursors::position(&self.content, at - insertion.selected)),
+        }
+      })
+      .collect();
+
+    self.set_cursors(adjusted);
+  }
+
+  fn insert_paired(&mut self, c: char) -> bool {
+    let close = brackets::closing(c);
+
+    if close.is_none() && !brackets::is_closing(c) {
+      return false;
+    }
+
+    let mut is_handled = false;
+    let mut skipped = Vec::new();
+    let mut opened = Vec::new();
+
+    let insertions = self
+      .cursors()
+      .into_iter()
+      .map(|cursor| {
+        let (start, end) = cursors::bounds(&cursor);
+        let first = self.line_text(start.line);
+        let previous = first[..cursors::floor_char_boundary(&first, start.column)]
+          .chars()
+          .next_back();
+        let line = self.line_text(end.line);
+        let next = line[cursors::floor_char_boundary(&line, end.column)..]
+          .chars()
+          .next();
+
+        if start != end {
+          return cursors::Insertion::new(cursor, c.to_string());
+        }
+
+        let closer = self.closers.iter().copied().find(|closer| {
+          closer.line == start.line
+            && closer.close == c
+            && closer.column(&line) == Some(start.column)
+        });
+
+        if let Some(closer) = closer {
+          is_handled = true;
+          skipped.push(closer);
+
+          return cursors::Insertion {
+            shift: c.len_utf8() as isize,
+            ..cursors::Insertion::new(cursors::caret(start), String::new())
+          };
+        }
+
+        match close {
+          Some(close) if brackets::should_close(c, previous, next) => {
+            is_handled = true;
+            opened.push(brackets::Closer {
+              line: start.line,
+              after: line.len() - end.column,
+              close,
+            });
+
+            cursors::Insertion {
+              shift: -(close.len_utf8() as isize),
+              ..cursors::Insertion::new(cursor, format!("{c}{close}"))
+            }
+          }
+          _ => cursors::Insertion::new(cursor, c.to_string()),
+        }
+      })
+      .collect();
+
+    if is_handled {
+      self.edit_each(insertions);
+      self.closers.retain(|closer| !skipped.contains(closer));
+      self.closers.extend(opened);
+    }
+
+    is_handled
+  }
+
+  fn delete_pair(&mut self) -> bool {
+    let cursors = self.cursors();
+
+    let pairs: Option<Vec<text_editor::Cursor>> = cursors
+      .iter()
+      .map(|cursor| {
+        if cursor.selection.is_some() {
+          return None;
+        }
+
+        let position = cursor.position;
+        let line = self.line_text(position.line);
+        let previous = line[..position.column].chars().next_back()?;
+        let next = line[position.column..].chars().next()?;
+
+        (brackets::closing(previous) == Some(next)).then_some(text_editor::Cursor {
+          position: text_editor::Position {
+            column: position.column + next.len_utf8(),
+            ..position
+          },
+          selection: Some(text_editor::Position {
+            column: position.column - previous.len_utf8(),
+            ..position
+          }),
+        })
+      })
+      .collect();
+
+    let Some(pairs) = pairs else {
+      return false;
+    };
+
+    self.edit_each(
+      pairs
+        .into_iter()
+        .map(|cursor| cursors::Insertion::new(cursor, String::new()))
+        .collect(),
+    );
+
+    true
+  }
+
+  pub fn matching_bracket(&self) -> Option<(text_editor::Position, text_editor::Position)> {
+    let cursor = self.content.cursor();
+
+    if cursor.selection.is_some() {
+      return None;
+    }
+
+    brackets::matching(&self.content, cursor.position)
+  }
+
+  pub fn select_line(&mut self, line: usize) {
+    let start = text_editor::Position { line, column: 0 };
+
+    if let Some(cursor) = cursors::line_at(&self.content, start) {
+      self.clear_cursors();
+      cursors::place(&mut self.content, cursor);
+    }
+  }
+
+  pub fn jump_to_bracket(&mut self) {
+    if let Some((_, other)) = self.matching_bracket() {
+      self.cursors.clear();
+      cursors::place(&mut self.content, cursors::caret(other));
+    }
+  }
+
+  fn break_lines(&mut self) {
+    let ending = self.line_ending();
+
+    let edits = self
+      .cursors()
+      .into_iter()
+      .map(|cursor| {
+        let (start, _) = cursors::bounds(&cursor);
+        let line = self.line_text(start.line);
+        let indentation = self.indent.line_break(&line[..start.column]);
+
+        cursors::Insertion::new(cursor, format!("{ending}{indentation}"))
+      })
+      .collect();
+
+    self.edit_each(edits);
+  }
+
+  fn selected_lines(&self) -> (usize, usize) {
+    let (start, end) = cursors::bounds(&self.content.cursor());
+
+    if end.line > start.line && end.column == 0 {
+      (start.line, end.line - 1)
+    } else {
+      (start.line, end.line)
+    }
+  }
+
+  fn rewrite_lines(&mut self, rewrite: impl FnOnce(&[String]) -> Vec<String>) {
+    let (first, last) = self.selected_lines();
+    self.revealing(first, last, |file| file.rewrite_selected_lines(rewrite));
+  }
+
+  fn rewrite_selected_lines(&mut self, rewrite: impl FnOnce(&[String]) -> Vec<String>) {
+    let cursor = self.content.cursor();
+    let (first, last) = self.selected_lines();
+    let original: Vec<String> = (first..=last).map(|line| self.line_text(line)).collect();
+    let replacement = rewrite(&original);
+
+    if replacement == original {
+      return;
+    }
+
+    self.replace_lines(first, last, &replacement);
+
+    if cursor.selection.is_none() && first == last {
+      let text = self.line_text(first);
+      let column = (cursor.position.column + text.len()).saturating_sub(original[0].len());
+
+      cursors::place(
+        &mut self.content,
+        cursors::caret(text_editor::Position {
+          line: first,
+          column: cursors::floor_char_boundary(&text, column),
+        }),
+      );
+
+      return;
+    }
+
+    let end = self.line_text(last).len();
+
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: text_editor::Position {
+          line: last,
+          column: end,
+        },
+        selection: Some(text_editor::Position {
+          line: first,
+          column: 0,
+        }),
+      },
+    );
+  }
+
+  fn reindent_lines(&mut self, reindent: impl Fn(&Indent, &str) -> String) {
+    let indent = self.indent;
+    self.rewrite_lines(|lines| lines.iter().map(|line| reindent(&indent, line)).collect());
+  }
+
+  pub fn toggle_line_comment(&mut self) {
+    if let Some(syntax) = comments::for_path(self.path.as_deref()) {
+      self.rewrite_lines(|lines| comments::toggle_lines(syntax, lines));
+    }
+  }
+
+  pub fn toggle_block_comment(&mut self) {
+    let Some((open, close)) = comments::for_path(self.path.as_deref()).and_then(|s| s.block) else {
+      return;
+    };
+
+    let (first, last) = self.selected_lines();
+    self.revealing(first, last, |file| file.wrap_block_comment(open, close));
+  }
+
+  fn wrap_block_comment(&mut self, open: &str, close: &str) {
+    let cursor = self.content.cursor();
+
+    let (start, end) = if cursor.selection.is_some() {
+      cursors::bounds(&cursor)
+    } else {
+      let line = self.line_text(cursor.position.line);
+      let whitespace = indent::leading_whitespace(&line).len();
+      let at = |column| text_editor::Position {
+        line: cursor.position.line,
+        column,
+      };
+
+      (at(whitespace), at(line.len()))
+    };
+
+    let selected = cursors::selected_text(
+      &self.content,
+      &self.content.text(),
+      &text_editor::Cursor {
+        position: end,
+        selection: Some(start),
+      },
+    );
+
+    let toggled = comments::toggle_block(open, close, &selected);
+    self.replace(start, end, &toggled);
+
+    if cursor.selection.is_some() {
+      let end = self.content.cursor().position;
+
+      cursors::place(
+        &mut self.content,
+        text_editor::Cursor {
+          position: end,
+          selection: Some(start),
+        },
+      );
+    }
+  }
+
+  pub fn indent_selection(&mut self) {
+    let (first, last) = self.selected_lines();
+
+    if first != last {
+      self.reindent_lines(Indent::indented);
+      return;
+    }
+
+    let (first, last) = self.cursor_lines();
+
+    self.revealing(first, last, |file| {
+      file.remember(Kind::Command, first, last);
+
+      let edits = file
+        .cursors()
+        .into_iter()
+        .map(|cursor| {
+          let (start, _) = cursors::bounds(&cursor);
+          let column = cursors::visual_position(&file.content, start, file.tab_width()).1;
+
+          cursors::Insertion::new(cursor, file.indent.unit_at(column))
+        })
+        .collect();
+
+      file.edit_each(edits);
+    });
+  }
+
+  pub fn outdent_selection(&mut self) {
+    self.reindent_lines(Indent::outdented);
+  }
+
+  pub fn convert_indentation(&mut self, style: indent::Style) {
+    let to = Indent {
+      style,
+      size: self.indent.size,
+    };
+
+    self.unfold_all();
+
+    let count = self.content.line_count();
+    let original: Vec<String> = (0..count).map(|line| self.line_text(line)).collect();
+    let replacement: Vec<String> = original
+      .iter()
+      .map(|line| self.indent.convert(line, to))
+      .collect();
+
+    self.indent = to;
+
+    if replacement != original {
+      let cursor = self.content.cursor();
+      self.replace_lines(0, count.saturating_sub(1), &replacement);
+      self.place_caret_near(cursor.position);
+    }
+  }
+
+  pub fn clean_up_whitespace(&mut self, defaults: whitespace::Cleanup) -> bool {
+    let cleanup = whitespace::Cleanup {
+      trim_trailing_whitespace: self
+        .editorconfig
+        .trim_trailing_whitespace
+        .unwrap_or(defaults.trim_trailing_whitespace),
+      insert_final_newline: self
+        .editorconfig
+        .insert_final_newline
+        .unwrap_or(defaults.insert_final_newline),
+      ..defaults
+    };
+
+    let original = self.source_lines();
+    let cleaned = whitespace::clean(&original, cleanup, self.is_markdown());
+
+    if cleaned == original {
+      return false;
+    }
+
+    self.unfold_all();
+
+    let cursor = self.content.cursor();
+    let last = self.content.line_count().saturating_sub(1);
+
+    self.replace_lines(0, last, &cleaned);
+    self.place_caret_near(cursor.position);
+
+    true
+  }
+
+  fn place_caret_near(&mut self, position: text_editor::Position) {
+    let line = position
+      .line
+      .min(self.content.line_count().saturating_sub(1));
+    let text = self.line_text(line);
+
+    cursors::place(
+      &mut self.content,
+      cursors::caret(text_editor::Position {
+        line,
+        column: cursors::floor_char_boundary(&text, position.column),
+      }),
+    );
+  }
+
+  pub fn apply_transform(&mut self, action: TransformAction) -> bool {
+    let (first, last) = self.selected_lines();
+    self.revealing(first, last, |file| file.transform_selection(action))
+  }
+
+  fn transform_selection(&mut self, action: TransformAction) -> bool {
+    let cursor = self.content.cursor();
+
+    if cursor.selection.is_none() {
+      return false;
+    }
+
+    let (start, end) = cursors::bounds(&cursor);
+    let selected = cursors::selected_text(&self.content, &self.content.text(), &cursor);
+
+    let Some(transformed) = transform::apply(action, &selected) else {
+      return false;
+    };
+
+    if transformed == selected {
+      return false;
+    }
+
+    self.replace(start, end, &transformed);
+
+    let end = self.content.cursor().position;
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: end,
+        selection: Some(start),
+      },
+    );
+
+    true
+  }
+
+  pub fn update_fold_regions(&mut self) {
+    if self.fold_regions_revision == self.revision {
+      return;
+    }
+
+    let lines: Vec<String> = self
+      .content
+      .lines()
+      .map(|line| line.text.into_owned())
+      .collect();
+
+    self.fold_regions_revision = self.revision;
+    self.fold_regions = folding::regions(&lines, self.is_markdown());
+  }
+
+  fn fold_regions(&mut self) -> Vec<folding::Region> {
+    self.update_fold_regions();
+    self.fold_regions.clone()
+  }
+
+  pub fn line_numbers(&self) -> Vec<usize> {
+    self.folds.line_numbers(self.content.line_count())
+  }
+
+  pub fn line_count(&self) -> usize {
+    self.content.line_count() + self.folds.hidden_count()
+  }
+
+  pub fn fold_markers(&self) -> Vec<Option<folding::Marker>> {
+    folding::markers(&self.fold_regions, &self.folds, self.content.line_count())
+  }
+
+  pub fn fold(&mut self) {
+    let line = self.content.cursor().position.line;
+
+    let innermost = self
+      .fold_regions()
+      .into_iter()
+      .filter(|region| region.start <= line && line <= region.end)
+      .max_by_key(|region| region.start);
+
+    if let Some(region) = innermost {
+      self.fold_lines(region.start, region.end);
+    }
+  }
+
+  pub fn unfold(&mut self) {
+    let line = self.content.cursor().position.line;
+    self.reveal(line, line);
+  }
+
+  pub fn toggle_fold(&mut self, line: usize) {
+    if self.folds.is_folded(line) {
+      self.reveal(line, line);
+      return;
+    }
+
+    let region = self
+      .fold_regions()
+      .into_iter()
+      .find(|region| region.start == line);
+
+    if let Some(region) = region {
+      self.fold_lines(region.start, region.end);
+    }
+  }
+
+  pub fn fold_level(&mut self, level: usize) {
+    self.unfold_all();
+
+    let mut limit = usize::MAX;
+
+    for region in self.fold_regions().into_iter().rev() {
+      if region.level == level && region.end < limit {
+        self.fold_lines(region.start, region.end);
+        limit = region.start;
+      }
     }
   }
-}
 
-impl File {
-  pub fn from(content: &str, path: Option<PathBuf>) -> Self {
-    let text_editor_content = text_editor::Content::with_text(content);
-    let markdown = markdown::parse(content).collect();
+  pub fn unfold_all(&mut self) {
+    self.reveal(0, usize::MAX);
+  }
 
-    File {
-      needs_saving: false,
-      content: text_editor_content,
-      path,
-      markdown,
+  fn fold_lines(&mut self, start: usize, end: usize) {
+    let mut hidden = Vec::with_capacity(end - start);
+
+    for line in start..=end {
+      if line > start {
+        hidden.push(self.line_text(line));
+      }
+
+      if let Some(folded) = self.folds.remove(line) {
+        hidden.extend(folded);
+      }
+    }
+
+    let cursor = self.content.cursor();
+    let header = text_editor::Position {
+      line: start,
+      column: self.line_text(start).len(),
+    };
+
+    let footer = text_editor::Position {
+      line: end,
+      column: self.line_text(end).len(),
+    };
+
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position: footer,
+        selection: Some(header),
+      },
+    );
+
+    self
+      .content
+      .perform(text_editor::Action::Edit(text_editor::Edit::Delete));
+
+    let removed = end - start;
+
+    self.folds.shift(end + 1, -(removed as isize));
+    self.folds.insert(start, hidden);
+    self.fold_regions_revision = 0;
+    self.shift_misspellings(start + 1, end, -(removed as isize));
+
+    let moved = |position: text_editor::Position| {
+      if position.line > end {
+        text_editor::Position {
+          line: position.line - removed,
+          ..position
+        }
+      } else if position.line > start {
+        header
+      } else {
+        position
+      }
+    };
+
+    let position = moved(cursor.position);
+    let selection = cursor
+      .selection
+      .map(moved)
+      .filter(|selection| *selection != position);
+
+    self.cursors.clear();
+    self.block = None;
+    cursors::place(
+      &mut self.content,
+      text_editor::Cursor {
+        position,
+        selection,
+      },
+    );
+
+    self.invalidate_spelling();
+  }
+
+  fn reveal(&mut self, first: usize, last: usize) {
+    while let Some(line) = self.folds.last_within(first, last) {
+      self.expand_fold(line);
     }
   }
 
-  pub fn content(&self) -> &text_editor::Content {
-    &self.content
+  fn expand_fold(&mut self, line: usize) {
+    let Some(hidden) = self.folds.remove(line) else {
+      return;
+    };
+
+    let count = hidden.len();
+    let cursors = self.cursors();
+    let ending = self.line_ending();
+    let header = text_editor::Position {
+      line,
+      column: self.line_text(line).len(),
+    };
+
+    cursors::place(&mut self.content, cursors::caret(header));
+
+    self
+      .content
+      .perform(text_editor::Action::Edit(text_editor::Edit::Paste(
+        Arc::new(format!("{ending}{}", hidden.join(ending))),
+      )));
+
+    self.folds.shift(line + 1, count as isize);
+    self.fold_regions_revision = 0;
+    self.shift_misspellings(line + 1, line, count as isize);
+
+    let shift = |position: text_editor::Position| {
+      if position.line > line {
+        text_editor::Position {
+          line: position.line + count,
+          ..position
+        }
+      } else {
+        position
+      }
+    };
+
+    self.set_cursors(
+      cursors
+        .into_iter()
+        .map(|cursor| text_editor::Cursor {
+          position: shift(cursor.position),
+          selection: cursor.selection.map(shift),
+        })
+        .collect(),
+    );
+
+    self.invalidate_spelling();
+  }
+
+  fn revealing<R>(&mut self, first: usize, last: usize, edit: impl FnOnce(&mut Self) -> R) -> R {
+    self.reveal(first, last);
+
+    let count = self.content.line_count();
+    let last_line = last.min(count - 1);
+    let is_indexed = self.words_revision == self.revision;
+    let shape = (self.code_lines_revision == self.revision && first == last_line)
+      .then(|| blocks::code_shape(&self.line_text(first)));
+
+    if is_indexed {
+      self.index_words(first.saturating_sub(1)..=last_line + 1, false);
+    }
+
+    let result = edit(self);
+    let delta = self.content.line_count() as isize - count as isize;
+
+    self.folds.shift(first, delta);
+    self.shift_misspellings(first, last, delta);
+
+    if is_indexed {
+      let end = (last_line + 1).saturating_add_signed(delta);
+
+      self.index_words(first.saturating_sub(1)..=end, true);
+      self.words_revision = self.revision;
+    }
+
+    if delta == 0 && shape == Some(blocks::code_shape(&self.line_text(first))) {
+      self.code_lines_revision = self.revision;
+    }
+
+    result
   }
 
-  pub fn content_mut(&mut self) -> &mut text_editor::Content {
-    &mut self.content
+  pub fn spell_language(&self) -> Option<&str> {
+    self.spell_language.as_deref()
   }
 
-  pub fn set_content(&mut self, content: &str) {
-    self.content = text_editor::Content::with_text(content);
+  pub fn set_spell_language(&mut self, language: Option<String>) {
+    self.spell_language = language;
+    self.invalidate_spelling();
+  }
+
+  pub fn ignore_word(&mut self, word: String) {
+    self.ignored_words.insert(word);
+    self.invalidate_spelling();
+  }
+
+  pub fn invalidate_spelling(&mut self) {
+    self.spell_revision = 0;
+  }
+
+  pub fn needs_spelling(&self) -> bool {
+    self.spell_revision != self.revision
+  }
+
+  fn shift_misspellings(&mut self, first: usize, last: usize, added: isize) {
+    self.misspellings.retain_mut(|(line, _)| {
+      let is_edited = (first..=last).contains(line);
+
+      if *line > last {
+        *line = line.saturating_add_signed(added);
+      }
+
+      !is_edited
+    });
+  }
+
+  pub fn misspellings(&self) -> &[(usize, Range<usize>)] {
+    &self.misspellings
+  }
+
+  pub fn check_spelling(
+    &mut self,
+    dictionary: Option<&mut Dictionary>,
+    personal: &HashSet<String>,
+  ) {
+    if self.spell_revision == self.revision {
+      return;
+    }
+
+    self.misspellings.clear();
+    self.spell_revision = self.revision;
+
+    let Some(dictionary) = dictionary else {
+      return;
+    };
+
+    let lines = self.source_lines();
+    let skips = if self.is_markdown() {
+      spelling::markdown_skips(&lines)
+    } else {
+      vec![Some(Vec::new()); lines.len()]
+    };
+
+    for (index, source) in self.line_numbers().into_iter().enumerate() {
+      let (line, Some(skipped)) = (&lines[source], &skips[source]) else {
+        continue;
+      };
+
+      for range in spelling::words(line, skipped) {
+        let word = &line[range.clone()];
+
+        if !personal.contains(word) && !self.ignored_words.contains(word) && !dictionary.check(word)
+        {
+          self.misspellings.push((index, range));
+        }
+      }
+    }
+  }
+
+  pub fn misspelling_at(
+    &self,
+    position: text_editor::Position,
+  ) -> Option<(text_editor::Position, text_editor::Position, String)> {
+    let (line, range) = self.misspellings.iter().find(|(line, range)| {
+      *line == position.line && range.start <= position.column && position.column <= range.end
+    })?;
+
+    let word = self.line_text(*line).get(range.clone())?.to_owned();
+
+    Some((
+      text_editor::Position {
+        line: *line,
+        column: range.start,
+      },
+      text_editor::Position {
+        line: *line,
+        column: range.end,
+      },
+      word,
+    ))
+  }
+
+  pub fn decorations(&self) -> Decorations {
+    let mut decorations = Decorations::new();
+
+    if let Some((at, other)) = self.matching_bracket() {
+      for position in [at, other] {
+        decorations
+          .entry(position.line)
+          .or_default()
+          .push((position.column..position.column + 1, Decoration::Bracket));
+      }
+    }
+
+    for cursor in &self.cursors {
+      let (start, end) = cursors::bounds(cursor);
+
+      if cursor.selection.is_some() {
+        for line in start.line..=end.line {
+          let from = if line == start.line { start.column } else { 0 };
+          let to = if line == end.line {
+            end.column
+          } else {
+            usize::MAX
+          };
+
+          decorations
+            .entry(line)
+            .or_default()
+            .push((from..to, Decoration::Selection));
+        }
+      }
+
+      let caret = cursor.position;
+      let line = self
+        .content
+        .line(caret.line)
+        .map(|line| line.text.into_owned());
+      let text = line.as_deref().unwrap_or_default();
+
+      if let Some(c) = text
+        .get(caret.column..)
+        .and_then(|rest| rest.chars().next())
+      {
+        decorations.entry(caret.line).or_default().push((
+          caret.column..caret.column + c.len_utf8(),
+          Decoration::Cursor,
+        ));
+      }
+    }
+
+    decorations
+  }
+
+  pub fn markdown(&self) -> &[Block] {
+    &self.markdown
   }
 
-  pub fn markdown(&self) -> Vec<&markdown::Item> {
-    self.markdown.iter().collect()
+  pub fn revision(&self) -> u64 {
+    self.revision
+  }
+
+  pub fn markdown_revision(&self) -> u64 {
+    self.parsed_revision
   }
 
   pub fn update_markdown(&mut self) {
-    self.markdown = markdown::parse(&self.content.text()).collect();
+    if self.parsed_revision == self.revision {
+      return;
+    }
+
+    self.requested_revision = self.revision;
+    let request = blocks::request(self.revision, self.markdown_text(), &self.markdown);
+    self.apply_markdown(blocks::parse(request));
+  }
+
+  pub fn request_markdown(&mut self) -> Option<blocks::Request> {
+    if self.requested_revision == self.revision {
+      return None;
+    }
+
+    self.requested_revision = self.revision;
+    Some(blocks::request(
+      self.revision,
+      self.markdown_text(),
+      &self.markdown,
+    ))
+  }
+
+  fn markdown_text(&self) -> String {
+    self.folds.source_text(&self.content, "\n")
+  }
+
+  pub fn is_awaiting(&self, parsed: &blocks::Parsed) -> bool {
+    parsed.revision == self.requested_revision && parsed.revision != self.parsed_revision
+  }
+
+  pub fn apply_markdown(&mut self, parsed: blocks::Parsed) {
+    if self.is_awaiting(&parsed) {
+      self.parsed_revision = parsed.revision;
+      self.markdown = blocks::assemble(&mut self.markdown, parsed);
+    }
+  }
+
+  pub fn source_lines(&self) -> Vec<String> {
+    self.folds.source_lines(&self.content)
+  }
+
+  pub fn outline(&self) -> &[Heading] {
+    &self.outline
+  }
+
+  pub fn update_outline(&mut self) {
+    if self.outline_revision != self.revision {
+      self.outline_revision = self.revision;
+      self.outline = outline::headings(&self.source_lines());
+    }
+  }
+
+  pub fn words(&self) -> impl Iterator<Item = &str> {
+    self.words.keys().map(String::as_str)
+  }
+
+  pub fn update_words(&mut self) {
+    if self.words_revision != self.revision {
+      self.words_revision = self.revision;
+      self.words.clear();
+
+      for line in self.source_lines() {
+        count_words(&mut self.words, &line, true);
+      }
+    }
+  }
+
+  fn index_words(&mut self, lines: RangeInclusive<usize>, is_added: bool) {
+    for line in lines {
+      match self.content.line(line) {
+        Some(line) => count_words(&mut self.words, &line.text, is_added),
+        None => break,
+      }
+    }
+  }
+
+  pub fn is_code_line(&self, line: usize) -> bool {
+    self
+      .code_lines
+      .get(self.folds.source_line(line))
+      .is_some_and(|is_code| *is_code)
+  }
+
+  pub fn update_code_lines(&mut self) {
+    if self.code_lines_revision != self.revision {
+      self.code_lines_revision = self.revision;
+      self.code_lines = blocks::code_lines(&self.source_lines());
+    }
+  }
+
+  pub fn front_matter(&self) -> Option<&FrontMatter> {
+    self.front_matter.as_ref()
+  }
+
+  pub fn update_front_matter(&mut self) {
+    if self.front_matter_revision == self.revision {
+      return;
+    }
+
+    self.front_matter_revision = self.revision;
+
+    let opens = self
+      .content
+      .line(0)
+      .is_some_and(|line| front_matter::opens(&line.text));
+
+    self.front_matter = if opens {
+      front_matter::parse(&self.source_lines())
+    } else {
+      None
+    };
+  }
+
+  pub fn title(&self) -> &str {
+    self
+      .front_matter
+      .as_ref()
+      .and_then(FrontMatter::title)
+      .unwrap_or(self.display_name())
+  }
+
+  pub fn current_section(&self) -> Option<usize> {
+    outline::section_at(
+      &self.outline,
+      self.source_line(self.content.cursor().position.line),
+    )
+  }
+
+  pub fn block_at(&self, line: usize) -> Option<usize> {
+    self
+      .markdown
+      .partition_point(|block| block.lines.start <= line)
+      .checked_sub(1)
+  }
+
+  pub fn source_line(&self, line: usize) -> usize {
+    self.folds.source_line(line)
+  }
+
+  pub fn content_line(&self, line: usize) -> usize {
+    self
+      .folds
+      .content_line(line)
+      .min(self.content.line_count().saturating_sub(1))
+  }
+
+  pub fn set_task(&mut self, line: usize, done: bool) -> bool {
+    let header = self.content_line(line);
+    self.reveal(header, header);
+
+    let line = self.content_line(line);
+    let text = self.line_text(line);
+
+    let Some(column) = task_marker(&text) else {
+      return false;
+    };
+
+    let mark = if done { "x" } else { " " };
+
+    if text[column + 1..].starts_with(mark) {
+      return false;
+    }
+
+    let cursors = self.cursors();
+    let at = |column| text_editor::Position { line, column };
+
+    self.replace(at(column + 1), at(column + 2), mark);
+    self.set_cursors(cursors);
+    true
+  }
+
+  pub fn move_to_source_line(&mut self, line: usize) {
+    let line = self.content_line(line);
+
+    self.clear_cursors();
+    cursors::place(
+      &mut self.content,
+      cursors::caret(text_editor::Position { line, column: 0 }),
+    );
   }
 
   pub fn path(&self) -> Option<&PathBuf> {
@@ -72,6 +1966,10 @@ impl File {
       .and_then(ffi::OsStr::to_str)
   }
 
+  pub fn is_markdown(&self) -> bool {
+    matches!(self.extension(), Some("md" | "markdown"))
+  }
+
   pub fn display_name(&self) -> &str {
     self
       .path
@@ -81,9 +1979,22 @@ impl File {
       .unwrap_or("New file")
   }
 
+  pub fn cursor_summary(&self) -> Option<String> {
+    match self.cursor_count() {
+      1 => None,
+      count => Some(format!("{count} cursors")),
+    }
+  }
+
   pub fn position_summary(&self) -> String {
-    let pos = self.content.cursor().position;
-    format!("Ln {}, Col {}", pos.line, pos.column)
+    let position = self.content.cursor().position;
+    let line = self.folds.source_line(position.line);
+    let column = self
+      .content
+      .line(position.line)
+      .map_or(0, |text| text.text[..position.column].chars().count());
+
+    format!("Ln {}, Col {}", line + 1, column + 1)
   }
 
   pub fn path_summary(&self) -> String {
@@ -102,3 +2013,241 @@ impl File {
     self.needs_saving = state
   }
 }
+
+struct Source<'a> {
+  content: &'a text_editor::Content,
+  folds: &'a Folds,
+}
+
+impl Document for Source<'_> {
+  fn line_count(&self) -> usize {
+    self.content.line_count() + self.folds.hidden_count()
+  }
+
+  fn lines(&self, range: Range<usize>) -> String {
+    self.folds.source_slice(self.content, range).join("\n")
+  }
+
+  fn cursor(&self) -> text_editor::Cursor {
+    let cursor = self.content.cursor();
+
+    text_editor::Cursor {
+      position: self.folds.source_position(cursor.position),
+      selection: cursor
+        .selection
+        .map(|selection| self.folds.source_position(selection)),
+    }
+  }
+}
+
+fn count_words(words: &mut HashMap<String, usize>, text: &str, is_added: bool) {
+  for word in completion::words(text) {
+    if is_added {
+      *words.entry(word.to_owned()).or_default() += 1;
+    } else if let Some(count) = words.get_mut(word) {
+      *count -= 1;
+
+      if *count == 0 {
+        words.remove(word);
+      }
+    }
+  }
+}
+
+fn source<'a>(content: &'a text_editor::Content, folds: &'a Folds) -> Source<'a> {
+  Source { content, folds }
+}
+
+fn task_marker(line: &str) -> Option<usize> {
+  let rest = line.trim_start_matches([' ', '\t', '>']);
+  let digits = rest.chars().take_while(char::is_ascii_digit).count();
+
+  let rest = if digits > 0 {
+    rest[digits..].strip_prefix(['.', ')'])?
+  } else {
+    rest.strip_prefix(['-', '*', '+'])?
+  };
+
+  let rest = rest.trim_start_matches([' ', '\t']);
+
+  matches!(rest.get(..3), Some("[ ]" | "[x]" | "[X]")).then_some(line.len() - rest.len())
+}
+
+fn next_revision() -> u64 {
+  NEXT_REVISION.fetch_add(1, atomic::Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(line: usize, column: usize) -> text_editor::Position {
+    text_editor::Position { line, column }
+  }
+
+  fn type_text(file: &mut File, text: &str) {
+    for c in text.chars() {
+      file.perform(text_editor::Action::Edit(text_editor::Edit::Insert(c)));
+    }
+  }
+
+  #[test]
+  fn types_over_auto_inserted_closers() {
+    let mut file = File::from("", None);
+
+    type_text(&mut file, "f(ab)");
+
+    assert_eq!(file.content.text(), "f(ab)");
+    assert_eq!(file.content.cursor().position, at(0, 5));
+  }
+
+  #[test]
+  fn types_closers_that_were_already_in_the_text() {
+    let mut file = File::from("f()", None);
+    cursors::place(&mut file.content, cursors::caret(at(0, 2)));
+
+    type_text(&mut file, ")");
+
+    assert_eq!(file.content.text(), "f())");
+  }
+
+  #[test]
+  fn forgets_auto_inserted_closers_once_the_cursor_leaves_them() {
+    let mut file = File::from("", None);
+
+    type_text(&mut file, "(");
+    file.perform(text_editor::Action::Move(text_editor::Motion::Right));
+    file.perform(text_editor::Action::Move(text_editor::Motion::Left));
+    type_text(&mut file, ")");
+
+    assert_eq!(file.content.text(), "())");
+  }
+
+  #[test]
+  fn replaces_selections_with_typed_openers() {
+    let mut file = File::from("*word*", None);
+    file.path = Some(PathBuf::from("notes.md"));
+    cursors::place(
+      &mut file.content,
+      text_editor::Cursor {
+        position: at(0, 5),
+        selection: Some(at(0, 1)),
+      },
+    );
+
+    type_text(&mut file, "(");
+
+    assert_eq!(file.content.text(), "*(*");
+  }
+
+  #[test]
+  fn keeps_misspellings_outside_the_edited_lines() {
+    let mut file = File::from("teh\nok wrod\nmispelt\n", None);
+    file.misspellings = vec![(0, 0..3), (1, 3..7), (2, 0..7)];
+    file.spell_revision = file.revision;
+
+    cursors::place(&mut file.content, cursors::caret(at(1, 2)));
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Enter));
+
+    assert_eq!(file.misspellings(), [(0, 0..3), (3, 0..7)]);
+    assert!(file.needs_spelling());
+  }
+
+  #[test]
+  fn keeps_the_word_index_in_step_with_edits() {
+    let mut file = File::from("alpha beta\ngamma\n", None);
+    file.update_words();
+
+    cursors::place(&mut file.content, cursors::caret(at(1, 5)));
+
+    for c in " delta\nepsilon".chars() {
+      let edit = match c {
+        '\n' => text_editor::Edit::Enter,
+        c => text_editor::Edit::Insert(c),
+      };
+
+      file.perform(text_editor::Action::Edit(edit));
+    }
+
+    cursors::place(&mut file.content, cursors::caret(at(0, 10)));
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Delete));
+
+    let mut incremental: Vec<String> = file.words().map(String::from).collect();
+    incremental.sort_unstable();
+
+    file.words_revision = 0;
+    file.update_words();
+
+    let mut rebuilt: Vec<String> = file.words().map(String::from).collect();
+    rebuilt.sort_unstable();
+
+    assert_eq!(incremental, rebuilt);
+    assert!(rebuilt.contains(&String::from("betagamma")));
+    assert!(!rebuilt.contains(&String::from("beta")));
+  }
+
+  #[test]
+  fn reuses_code_lines_for_plain_typing() {
+    let mut file = File::from("```\ncode\n```\ntext\n", Some(PathBuf::from("notes.md")));
+    file.update_code_lines();
+
+    cursors::place(&mut file.content, cursors::caret(at(3, 4)));
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Insert('s')));
+    assert_eq!(file.code_lines_revision, file.revision);
+    assert!(file.is_code_line(1) && !file.is_code_line(3));
+
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Insert('`')));
+    assert_ne!(file.code_lines_revision, file.revision);
+  }
+
+  #[test]
+  fn keeps_folds_across_undo_and_redo() {
+    let mut file = File::from("# A\na1\n# B\nb1\nb2\n", Some(PathBuf::from("notes.md")));
+
+    file.toggle_fold(2);
+    cursors::place(&mut file.content, cursors::caret(at(0, 3)));
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Insert('!')));
+
+    assert!(file.undo());
+    assert!(file.folds.is_folded(2));
+    assert_eq!(file.content.line_count(), 4);
+    assert_eq!(file.text(), "# A\na1\n# B\nb1\nb2\n");
+
+    assert!(file.redo());
+    assert!(file.folds.is_folded(2));
+    assert_eq!(file.text(), "# A!\na1\n# B\nb1\nb2\n");
+  }
+
+  #[test]
+  fn drops_folds_that_overlap_an_undone_change() {
+    let mut file = File::from("# A\na1\n# B\nb1\n", Some(PathBuf::from("notes.md")));
+
+    cursors::place(&mut file.content, cursors::caret(at(3, 2)));
+    file.perform(text_editor::Action::Edit(text_editor::Edit::Insert('!')));
+    file.toggle_fold(2);
+
+    assert!(file.undo());
+    assert!(!file.folds.is_folded(2));
+    assert_eq!(file.text(), "# A\na1\n# B\nb1\n");
+  }
+
+  #[test]
+  fn refreshes_fold_regions_after_edits_and_folds() {
+    let mut file = File::from("# A\na1\n# B\nb1\n", Some(PathBuf::from("notes.md")));
+
+    file.update_fold_regions();
+    assert_eq!(file.fold_markers()[2], Some(folding::Marker::Foldable));
+
+    file.toggle_fold(0);
+    file.update_fold_regions();
+    assert_eq!(
+      file.fold_markers(),
+      [
+        Some(folding::Marker::Folded),
+        Some(folding::Marker::Foldable),
+        None,
+        None,
+      ],
+    );
+  }
+}
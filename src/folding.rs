@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use iced::widget::text_editor::{Content, Position};
+
+use crate::blocks;
+use crate::indent::{self, Indent};
+
+pub const MAX_HEADING_LEVEL: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+  pub start: usize,
+  pub end: usize,
+  pub level: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Fold {
+  line: usize,
+  hidden: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Folds(Vec<Fold>);
+
+impl Folds {
+  pub fn clear(&mut self) {
+    self.0.clear();
+  }
+
+  pub fn is_folded(&self, line: usize) -> bool {
+    self.0.iter().any(|fold| fold.line == line)
+  }
+
+  pub fn last_within(&self, first: usize, last: usize) -> Option<usize> {
+    self
+      .0
+      .iter()
+      .rev()
+      .map(|fold| fold.line)
+      .find(|line| (first..=last).contains(line))
+  }
+
+  pub fn insert(&mut self, line: usize, hidden: Vec<String>) {
+    let index = self.0.partition_point(|fold| fold.line < line);
+    self.0.insert(index, Fold { line, hidden });
+  }
+
+  pub fn remove(&mut self, line: usize) -> Option<Vec<String>> {
+    let index = self.0.iter().position(|fold| fold.line == line)?;
+    Some(self.0.remove(index).hidden)
+  }
+
+  pub fn shift(&mut self, from: usize, delta: isize) {
+    for fold in self.0.iter_mut().filter(|fold| fold.line >= from) {
+      fold.line = fold.line.saturating_add_signed(delta);
+    }
+  }
+
+  pub fn source_ranges(&self) -> Vec<Range<usize>> {
+    let mut hidden = 0;
+
+    self
+      .0
+      .iter()
+      .map(|fold| {
+        let header = fold.line + hidden;
+        hidden += fold.hidden.len();
+
+        header..header + fold.hidden.len() + 1
+      })
+      .collect()
+  }
+
+  pub fn hidden_count(&self) -> usize {
+    self.0.iter().map(|fold| fold.hidden.len()).sum()
+  }
+
+  pub fn source_lines(&self, content: &Content) -> Vec<String> {
+    let mut lines = Vec::with_capacity(content.line_count() + self.hidden_count());
+    let mut folds = self.0.iter().peekable();
+
+    for (index, line) in content.lines().enumerate() {
+      lines.push(line.text.into_owned());
+
+      while let Some(fold) = folds.next_if(|fold| fold.line == index) {
+        lines.extend(fold.hidden.iter().cloned());
+      }
+    }
+
+    lines
+  }
+
+  pub fn source_slice(&self, content: &Content, lines: Range<usize>) -> Vec<String> {
+    let mut slice = Vec::with_capacity(lines.len());
+    let mut line = self.content_line(lines.start);
+    let mut source = self.source_line(line);
+    let mut folds = self.0[self.0.partition_point(|fold| fold.line < line)..]
+      .iter()
+      .peekable();
+
+    while source < lines.end
+      && let Some(text) = content.line(line)
+    {
+      if source >= lines.start {
+        slice.push(text.text.into_owned());
+      }
+
+      source += 1;
+
+      if let Some(fold) = folds.next_if(|fold| fold.line == line) {
+        for hidden in &fold.hidden {
+          if lines.contains(&source) {
+            slice.push(hidden.clone());
+          }
+
+          source += 1;
+        }
+      }
+
+      line += 1;
+    }
+
+    slice
+  }
+
+  pub fn source_text(&self, content: &Content, ending: &str) -> String {
+    if self.0.is_empty() {
+      return content.text();
+    }
+
+    self.source_lines(content).join(ending)
+  }
+
+  pub fn line_numbers(&self, line_count: usize) -> Vec<usize> {
+    let mut hidden = 0;
+    let mut folds = self.0.iter().peekable();
+
+    (0..line_count)
+      .map(|line| {
+        let source = line + hidden;
+
+        while let Some(fold) = folds.next_if(|fold| fold.line == line) {
+          hidden += fold.hidden.len();
+        }
+
+        source
+      })
+      .collect()
+  }
+
+  pub fn source_line(&self, line: usize) -> usize {
+    let hidden: usize = self
+      .0
+      .iter()
+      .filter(|fold| fold.line < line)
+      .map(|fold| fold.hidden.len())
+      .sum();
+
+    line + hidden
+  }
+
+  pub fn source_position(&self, position: Position) -> Position {
+    Position {
+      line: self.source_line(position.line),
+      ..position
+    }
+  }
+
+  pub fn content_line(&self, line: usize) -> usize {
+    let mut hidden = 0;
+
+    for fold in &self.0 {
+      let header = fold.line + hidden;
+
+      if line <= header {
+        break;
+      }
+
+      if line <= header + fold.hidden.len() {
+        return fold.line;
+      }
+
+      hidden += fold.hidden.len();
+    }
+
+    line - hidden
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+  Foldable,
+  Folded,
+}
+
+pub fn regions(lines: &[String], is_markdown: bool) -> Vec<Region> {
+  let mut regions = if is_markdown {
+    heading_regions(lines)
+  } else {
+    let mut regions = bracket_regions(lines);
+    let starts: HashSet<usize> = regions.iter().map(|region| region.start).collect();
+
+    regions.extend(
+      indentation_regions(lines)
+        .into_iter()
+        .filter(|region| !starts.contains(&region.start)),
+    );
+
+    regions
+  };
+
+  regions.sort_by_key(|region| (region.start, std::cmp::Reverse(region.end)));
+  nest(&mut regions);
+  regions
+}
+
+pub fn markers(regions: &[Region], folds: &Folds, line_count: usize) -> Vec<Option<Marker>> {
+  let mut markers = vec![None; line_count];
+
+  for region in regions {
+    markers[region.start] = Some(Marker::Foldable);
+  }
+
+  for fold in &folds.0 {
+    if let Some(marker) = markers.get_mut(fold.line) {
+      *marker = Some(Marker::Folded);
+    }
+  }
+
+  markers
+}
+
+pub fn heading_level(line: &str) -> Option<usize> {
+  let level = line.chars().take_while(|c| *c == '#').count();
+  let rest = &line[level..];
+
+  ((1..=MAX_HEADING_LEVEL).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])))
+    .then_some(level)
+}
+
+fn last_content_line(lines: &[String], start: usize, before: usize) -> usize {
+  (start + 1..before)
+    .rev()
+    .find(|&line| !lines[line].trim().is_empty())
+    .unwrap_or(start)
+}
+
+fn heading_regions(lines: &[String]) -> Vec<Region> {
+  let mut headings = Vec::new();
+  let is_code = blocks::code_lines(lines);
+
+  for (index, line) in lines.iter().enumerate() {
+    if !is_code[index]
+      && let Some(level) = heading_level(line)
+    {
+      headings.push((index, level));
+    }
+  }
+
+  headings
+    .iter()
+    .enumerate()
+    .filter_map(|(rank, &(start, level))| {
+      let next = headings[rank + 1..]
+        .iter()
+        .find(|(_, other)| *other <= level)
+        .map_or(lines.len(), |(line, _)| *line);
+
+      let end = last_content_line(lines, start, next);
+
+      (end > start).then_some(Region {
+        start,
+        end,
+        level: 0,
+      })
+    })
+    .collect()
+}
+
+fn bracket_regions(lines: &[String]) -> Vec<Region> {
+  let mut open = Vec::new();
+  let mut regions = Vec::new();
+
+  for (index, line) in lines.iter().enumerate() {
+    let trailing = line.trim_end().len();
+
+    for (column, c) in line.char_indices() {
+      match c {
+        '{' | '[' | '(' => open.push((index, column + 1 == trailing)),
+        '}' | ']' | ')' => {
+          if let Some((start, is_trailing)) = open.pop()
+            && is_trailing
+            && index > start + 1
+          {
+            regions.push(Region {
+              start,
+              end: index - 1,
+              level: 0,
+            });
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  regions
+}
+
+fn indentation_regions(lines: &[String]) -> Vec<Region> {
+  let indent = Indent::default();
+  let mut open: Vec<(usize, usize)> = Vec::new();
+  let mut regions = Vec::new();
+  let mut last = 0;
+
+  let mut close = |start: usize, last: usize| {
+    if last > start {
+      regions.push(Region {
+        start,
+        end: last,
+        level: 0,
+      });
+    }
+  };
+
+  for (index, line) in lines.iter().enumerate() {
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    let width = indent.width(indent::leading_whitespace(line));
+
+    while let Some(&(start, other)) = open.last()
+      && other >= width
+    {
+      open.pop();
+      close(start, last);
+    }
+
+    open.push((index, width));
+    last = index;
+  }
+
+  while let Some((start, _)) = open.pop() {
+    close(start, last);
+  }
+
+  regions
+}
+
+fn nest(regions: &mut [Region]) {
+  let mut ends: Vec<usize> = Vec::new();
+
+  for region in regions {
+    while ends.last().is_some_and(|&end| end < region.start) {
+      ends.pop();
+    }
+
+    region.level = ends.len() + 1;
+    ends.push(region.end);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+  }
+
+  #[test]
+  fn nests_heading_regions() {
+    let regions = regions(&lines("# A\ntext\n## B\nmore\n\n# C\nlast\n"), true);
+
+    assert_eq!(
+      regions,
+      [
+        Region {
+          start: 0,
+          end: 3,
+          level: 1,
+        },
+        Region {
+          start: 2,
+          end: 3,
+          level: 2,
+        },
+        Region {
+          start: 5,
+          end: 6,
+          level: 1,
+        },
+      ],
+    );
+  }
+
+  #[test]
+  fn maps_lines_through_folds() {
+    let mut folds = Folds::default();
+    folds.insert(1, vec![String::from("a"), String::from("b")]);
+    folds.insert(3, vec![String::from("c")]);
+
+    assert_eq!(folds.source_ranges(), [1..4, 5..7]);
+    assert_eq!(folds.source_line(2), 4);
+    assert_eq!(folds.source_line(4), 7);
+    assert_eq!(folds.content_line(3), 1);
+    assert_eq!(folds.content_line(4), 2);
+    assert_eq!(folds.content_line(7), 4);
+    assert_eq!(folds.line_numbers(5), [0, 1, 4, 5, 7]);
+    assert_eq!(folds.hidden_count(), 3);
+  }
+
+  #[test]
+  fn shifts_and_removes_folds() {
+    let mut folds = Folds::default();
+    folds.insert(2, vec![String::from("x")]);
+    folds.shift(1, 3);
+
+    assert!(folds.is_folded(5));
+    assert_eq!(folds.last_within(0, 9), Some(5));
+    assert_eq!(folds.remove(5), Some(vec![String::from("x")]));
+    assert_eq!(folds.last_within(0, 9), None);
+  }
+}
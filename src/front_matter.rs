@@ -0,0 +1,278 @@
+use std::fmt::Display;
+
+use yaml_rust2::{Yaml, YamlLoader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  Yaml,
+  Toml,
+}
+
+impl Format {
+  fn from_delimiter(line: &str) -> Option<Format> {
+    match line.trim_end() {
+      "---" => Some(Format::Yaml),
+      "+++" => Some(Format::Toml),
+      _ => None,
+    }
+  }
+
+  fn closes(&self, line: &str) -> bool {
+    match self {
+      Format::Yaml => matches!(line.trim_end(), "---" | "..."),
+      Format::Toml => line.trim_end() == "+++",
+    }
+  }
+}
+
+impl Display for Format {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Format::Yaml => write!(f, "YAML"),
+      Format::Toml => write!(f, "TOML"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+  Text(String),
+  List(Vec<String>),
+}
+
+impl Display for Value {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Value::Text(text) => write!(f, "{text}"),
+      Value::List(items) => write!(f, "{}", items.join(", ")),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+  pub line: usize,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+  pub format: Format,
+  pub fields: Vec<(String, Value)>,
+  pub error: Option<Error>,
+}
+
+impl FrontMatter {
+  pub fn field(&self, key: &str) -> Option<&Value> {
+    self
+      .fields
+      .iter()
+      .find(|(name, _)| name.eq_ignore_ascii_case(key))
+      .map(|(_, value)| value)
+  }
+
+  pub fn title(&self) -> Option<&str> {
+    match self.field("title")? {
+      Value::Text(title) if !title.trim().is_empty() => Some(title.trim()),
+      _ => None,
+    }
+  }
+
+  pub fn tags(&self) -> Vec<&str> {
+    match self.field("tags") {
+      Some(Value::List(tags)) => tags.iter().map(String::as_str).collect(),
+      Some(Value::Text(tags)) => tags
+        .split([',', ' '])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .collect(),
+      None => Vec::new(),
+    }
+  }
+}
+
+pub fn opens(line: &str) -> bool {
+  Format::from_delimiter(line).is_some()
+}
+
+pub fn end<S: AsRef<str>>(lines: &[S]) -> Option<usize> {
+  let format = Format::from_delimiter(lines.first()?.as_ref())?;
+
+  lines
+    .iter()
+    .skip(1)
+    .position(|line| format.closes(line.as_ref()))
+    .map(|end| end + 2)
+}
+
+pub fn parse<S: AsRef<str>>(lines: &[S]) -> Option<FrontMatter> {
+  let format = Format::from_delimiter(lines.first()?.as_ref())?;
+  let end = end(lines)?;
+
+  let text = lines[1..end - 1]
+    .iter()
+    .map(|line| format!("{}\n", line.as_ref()))
+    .collect::<String>();
+
+  let (fields, error) = match format {
+    Format::Yaml => yaml(&text),
+    Format::Toml => toml(&text),
+  };
+
+  Some(FrontMatter {
+    format,
+    fields,
+    error: error.map(|error| Error {
+      line: error.line + 1,
+      ..error
+    }),
+  })
+}
+
+fn yaml(text: &str) -> (Vec<(String, Value)>, Option<Error>) {
+  let documents = match YamlLoader::load_from_str(text) {
+    Ok(documents) => documents,
+    Err(error) => {
+      let message = error.to_string();
+      let message = message
+        .rsplit_once(" at byte ")
+        .map_or(message.as_str(), |(message, _)| message)
+        .to_owned();
+
+      return (
+        Vec::new(),
+        Some(Error {
+          line: error.marker().line().saturating_sub(1),
+          message,
+        }),
+      );
+    }
+  };
+
+  match documents.first() {
+    None | Some(Yaml::Null) => (Vec::new(), None),
+    Some(Yaml::Hash(hash)) => {
+      let fields = hash
+        .iter()
+        .map(|(key, value)| (yaml_scalar(key), yaml_value(value)))
+        .collect();
+
+      (fields, None)
+    }
+    Some(_) => (
+      Vec::new(),
+      Some(Error {
+        line: 0,
+        message: "Front matter must be a mapping of keys to values".to_owned(),
+      }),
+    ),
+  }
+}
+
+fn yaml_value(value: &Yaml) -> Value {
+  match value {
+    Yaml::Array(items) => Value::List(items.iter().map(yaml_scalar).collect()),
+    _ => Value::Text(yaml_scalar(value)),
+  }
+}
+
+fn yaml_scalar(value: &Yaml) -> String {
+  match value {
+    Yaml::String(text) | Yaml::Real(text) => text.clone(),
+    Yaml::Integer(number) => number.to_string(),
+    Yaml::Boolean(boolean) => boolean.to_string(),
+    Yaml::Array(items) => format!(
+      "[{}]",
+      items.iter().map(yaml_scalar).collect::<Vec<_>>().join(", ")
+    ),
+    Yaml::Hash(hash) => format!(
+      "{{{}}}",
+      hash
+        .iter()
+        .map(|(key, value)| format!("{}: {}", yaml_scalar(key), yaml_scalar(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => String::new(),
+  }
+}
+
+fn toml(text: &str) -> (Vec<(String, Value)>, Option<Error>) {
+  let document = match text.parse::<toml_edit::DocumentMut>() {
+    Ok(document) => document,
+    Err(error) => {
+      let line = error.span().map_or(0, |span| {
+        text[..span.start.min(text.len())].matches('\n').count()
+      });
+
+      return (
+        Vec::new(),
+        Some(Error {
+          line,
+          message: error.message().trim().to_owned(),
+        }),
+      );
+    }
+  };
+
+  let fields = document
+    .iter()
+    .map(|(key, item)| {
+      let value = match item {
+        toml_edit::Item::Value(toml_edit::Value::Array(items)) => {
+          Value::List(items.iter().map(toml_scalar).collect())
+        }
+        _ => Value::Text(toml_item(item)),
+      };
+
+      (key.to_owned(), value)
+    })
+    .collect();
+
+  (fields, None)
+}
+
+fn toml_item(item: &toml_edit::Item) -> String {
+  match item {
+    toml_edit::Item::Value(value) => toml_scalar(value),
+    toml_edit::Item::Table(table) => format!(
+      "{{{}}}",
+      table
+        .iter()
+        .map(|(key, item)| format!("{key} = {}", toml_item(item)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    toml_edit::Item::ArrayOfTables(tables) => format!(
+      "[{}]",
+      tables
+        .iter()
+        .map(|table| toml_item(&toml_edit::Item::Table(table.clone())))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    toml_edit::Item::None => String::new(),
+  }
+}
+
+fn toml_scalar(value: &toml_edit::Value) -> String {
+  match value {
+    toml_edit::Value::String(text) => text.value().clone(),
+    toml_edit::Value::Integer(number) => number.value().to_string(),
+    toml_edit::Value::Float(number) => number.value().to_string(),
+    toml_edit::Value::Boolean(boolean) => boolean.value().to_string(),
+    toml_edit::Value::Datetime(datetime) => datetime.value().to_string(),
+    toml_edit::Value::Array(items) => format!(
+      "[{}]",
+      items.iter().map(toml_scalar).collect::<Vec<_>>().join(", ")
+    ),
+    toml_edit::Value::InlineTable(table) => format!(
+      "{{{}}}",
+      table
+        .iter()
+        .map(|(key, value)| format!("{key} = {}", toml_scalar(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+  }
+}
@@ -1,16 +1,340 @@
 use std::path::PathBuf;
 
-use crate::io;
-use crate::message::{FileAction, ViewAction};
+use crate::blocks;
+use crate::components::preview::{self, Layout};
+use crate::components::{macro_prompt, margin_prompt};
+use crate::editorconfig::Properties;
+use crate::export::Format;
+use crate::macros::{self, Prompt, PromptKind, Repeat};
+use crate::message::{
+  CompletionAction, EditAction, FileAction, GutterTarget, LineAction, MacroAction, OutlineAction,
+  Pane, Pointer, ScrollSync, SpellingAction, TableAction, TransformAction, ViewAction,
+};
+use crate::snippets::Snippet;
+use crate::spelling::{self, Language};
 use crate::{Message, state::State};
-use iced::Task;
-use iced::widget::text_editor;
+use crate::{constants, images, indent, io, pdf};
+use iced::widget::{operation, scrollable, text_editor};
+use iced::{Task, clipboard, keyboard};
 
 pub fn edit(state: &mut State, action: text_editor::Action) -> Task<Message> {
+  let is_scroll = matches!(action, text_editor::Action::Scroll { .. });
   state.apply_edit(action);
+
+  if is_scroll {
+    Task::none()
+  } else {
+    sync_scroll(state, ScrollSync::Cursor)
+  }
+}
+
+pub fn modifiers_changed(state: &mut State, modifiers: keyboard::Modifiers) -> Task<Message> {
+  state.set_modifiers(modifiers);
+  Task::none()
+}
+
+pub fn gutter_moved(state: &mut State, target: Option<GutterTarget>) -> Task<Message> {
+  state.set_gutter_target(target);
+  Task::none()
+}
+
+pub fn gutter_pressed(state: &mut State) -> Task<Message> {
+  state.press_gutter();
+  Task::none()
+}
+
+pub fn completion(state: &mut State, action: CompletionAction) -> Task<Message> {
+  state.apply_completion_action(action);
+  Task::none()
+}
+
+pub fn snippet_selected(state: &mut State, snippet: Snippet) -> Task<Message> {
+  state.insert_snippet(&snippet);
   Task::none()
 }
 
+pub fn spelling(state: &mut State, action: SpellingAction) -> Task<Message> {
+  match state.apply_spelling_action(action) {
+    Some(contents) => Task::perform(
+      io::save_config(spelling::PERSONAL_FILE, contents),
+      Message::ConfigSaved,
+    ),
+    None => Task::none(),
+  }
+}
+
+pub fn schedule_spelling(state: &mut State) -> Task<Message> {
+  match state.schedule_spelling() {
+    Some(revision) => Task::perform(
+      tokio::time::sleep(constants::SPELLING_DEBOUNCE),
+      move |_| Message::SpellingDebounced(revision),
+    ),
+    None => Task::none(),
+  }
+}
+
+pub fn check_spelling(state: &mut State, revision: u64) -> Task<Message> {
+  match state.check_spelling(revision) {
+    Some(installed) => Task::perform(
+      spelling::load_in_background(installed),
+      Message::DictionaryLoaded,
+    ),
+    None => Task::none(),
+  }
+}
+
+pub fn dictionary_loaded(state: &mut State, loaded: spelling::Loaded) -> Task<Message> {
+  state.apply_dictionary(loaded);
+  Task::none()
+}
+
+pub fn dictionaries_found(state: &mut State, installed: Vec<spelling::Installed>) -> Task<Message> {
+  state.set_installed_dictionaries(installed);
+  Task::none()
+}
+
+pub fn spell_language_selected(state: &mut State, language: Language) -> Task<Message> {
+  state.set_spell_language(language);
+  Task::none()
+}
+
+pub fn editor_pointer_moved(state: &mut State, pointer: Pointer) -> Task<Message> {
+  state.set_pointer(pointer);
+  Task::none()
+}
+
+pub fn macro_action(state: &mut State, action: MacroAction) -> Task<Message> {
+  match action {
+    MacroAction::ToggleRecording => {
+      state.toggle_recording();
+      Task::none()
+    }
+    MacroAction::Play => {
+      let steps = state.recorded_macro();
+      play_macro(state, steps, Repeat::Times(1))
+    }
+    MacroAction::PlayRepeatedly => {
+      state.open_macro_prompt(Prompt::new(PromptKind::Repeat));
+      operation::focus(macro_prompt::ID)
+    }
+    MacroAction::PlayToEnd => {
+      let steps = state.recorded_macro();
+      play_macro(state, steps, Repeat::ToEnd)
+    }
+    MacroAction::Save => {
+      state.open_macro_prompt(Prompt::new(PromptKind::Name));
+      operation::focus(macro_prompt::ID)
+    }
+    MacroAction::PlaySaved(slot) => {
+      let steps = state.saved_macro(slot);
+      play_macro(state, steps, Repeat::Times(1))
+    }
+  }
+}
+
+pub fn macro_prompt_changed(state: &mut State, value: String) -> Task<Message> {
+  state.set_macro_prompt_value(value);
+  Task::none()
+}
+
+pub fn macro_prompt_submitted(state: &mut State) -> Task<Message> {
+  let Some(prompt) = state.take_macro_prompt() else {
+    return Task::none();
+  };
+
+  let value = prompt.value.trim();
+
+  match prompt.kind {
+    PromptKind::Repeat => match value.parse::<usize>() {
+      Ok(times) if times > 0 => {
+        let steps = state.recorded_macro();
+        play_macro(
+          state,
+          steps,
+          Repeat::Times(times.min(macros::MAX_REPETITIONS)),
+        )
+      }
+      _ => Task::none(),
+    },
+    PromptKind::Name => match state.save_macro(value.to_owned()) {
+      Some(contents) => Task::perform(
+        io::save_config(macros::CONFIG_FILE, contents),
+        Message::ConfigSaved,
+      ),
+      None => Task::none(),
+    },
+  }
+}
+
+pub fn macro_prompt_cancelled(state: &mut State) -> Task<Message> {
+  state.take_macro_prompt();
+  Task::none()
+}
+
+pub fn margin_prompt_changed(state: &mut State, value: String) -> Task<Message> {
+  state.set_margin_prompt_value(value);
+  Task::none()
+}
+
+pub fn margin_prompt_submitted(state: &mut State) -> Task<Message> {
+  let Some(prompt) = state.margin_prompt() else {
+    return Task::none();
+  };
+
+  match prompt.settings(pdf::points_from_pixels(state.font_size())) {
+    Ok(settings) => {
+      state.close_margin_prompt();
+      export(state, Format::Pdf(settings))
+    }
+    Err(error) => {
+      state.set_notice(Some(error));
+      Task::none()
+    }
+  }
+}
+
+pub fn margin_prompt_cancelled(state: &mut State) -> Task<Message> {
+  state.close_margin_prompt();
+  Task::none()
+}
+
+pub fn schedule_preview(state: &mut State) -> Task<Message> {
+  match state.schedule_preview() {
+    Some(revision) => Task::perform(tokio::time::sleep(constants::PREVIEW_DEBOUNCE), move |_| {
+      Message::PreviewDebounced(revision)
+    }),
+    None => Task::none(),
+  }
+}
+
+pub fn preview_debounced(state: &mut State, revision: u64) -> Task<Message> {
+  match state.refresh_preview(revision) {
+    Some(request) => Task::perform(
+      blocks::parse_in_background(request),
+      Message::MarkdownParsed,
+    ),
+    None => Task::none(),
+  }
+}
+
+pub fn markdown_parsed(state: &mut State, result: Result<blocks::Parsed, String>) -> Task<Message> {
+  if let Ok(parsed) = result {
+    state.apply_markdown(parsed);
+  }
+
+  Task::none()
+}
+
+pub fn split_divider_pressed(state: &mut State) -> Task<Message> {
+  state.press_divider();
+  Task::none()
+}
+
+pub fn split_divider_moved(state: &mut State, ratio: f32) -> Task<Message> {
+  state.move_divider(ratio);
+  Task::none()
+}
+
+pub fn split_divider_released(state: &mut State) -> Task<Message> {
+  state.release_divider();
+  Task::none()
+}
+
+pub fn pane_hovered(state: &mut State, pane: Pane) -> Task<Message> {
+  state.hover_pane(pane);
+  Task::none()
+}
+
+pub fn preview_block_clicked(state: &mut State, index: usize) -> Task<Message> {
+  state.move_to_block(index);
+  sync_scroll(state, ScrollSync::Block(index))
+}
+
+pub fn editor_scrolled(state: &mut State, viewport: scrollable::Viewport) -> Task<Message> {
+  state.set_editor_viewport(viewport);
+  sync_scroll(state, ScrollSync::Editor)
+}
+
+pub fn sync_scroll(state: &State, sync: ScrollSync) -> Task<Message> {
+  if !state.follows(sync) {
+    return Task::none();
+  }
+
+  preview::measure(state.active_file().markdown().len())
+    .map(move |layout| Message::PreviewMeasured(sync, layout))
+}
+
+pub fn preview_measured(state: &mut State, sync: ScrollSync, layout: Layout) -> Task<Message> {
+  let targets = match sync {
+    ScrollSync::Heading(line) => state.scroll_to_heading(line, &layout),
+    _ => state.sync_scroll(sync, &layout).into_iter().collect(),
+  };
+
+  Task::batch(targets.into_iter().map(|(id, y)| {
+    operation::scroll_to(
+      id,
+      scrollable::AbsoluteOffset {
+        x: None,
+        y: Some(y),
+      },
+    )
+  }))
+}
+
+pub fn outline(state: &mut State, action: OutlineAction) -> Task<Message> {
+  match action {
+    OutlineAction::Filter(filter) => state.set_outline_filter(filter),
+    OutlineAction::Depth(depth) => state.set_outline_depth(depth),
+    OutlineAction::Collapse(index) => state.toggle_outline_collapsed(index),
+    OutlineAction::Jump(line) => {
+      state.jump_to_heading(line);
+      return sync_scroll(state, ScrollSync::Heading(line));
+    }
+  }
+
+  Task::none()
+}
+
+pub fn config_saved(_result: Result<(), String>) -> Task<Message> {
+  Task::none()
+}
+
+fn play_macro(state: &mut State, steps: Vec<Message>, repeat: Repeat) -> Task<Message> {
+  if steps.is_empty() {
+    return Task::none();
+  }
+
+  let times = match repeat {
+    Repeat::Times(times) => times,
+    Repeat::ToEnd => macros::MAX_REPETITIONS,
+  };
+
+  let mut tasks = Vec::new();
+  state.begin_playback();
+
+  for _ in 0..times {
+    let before = state.active_file().content().cursor().position;
+
+    for step in &steps {
+      tasks.push(crate::handle(state, step.clone()));
+    }
+
+    let content = state.active_file().content();
+    let after = content.cursor().position;
+    let last = content.line_count().saturating_sub(1);
+    let is_at_end =
+      after.line >= last && after.column >= content.line(last).map_or(0, |line| line.text.len());
+    let has_advanced = (after.line, after.column) > (before.line, before.column);
+
+    if repeat == Repeat::ToEnd && (is_at_end || !has_advanced) {
+      break;
+    }
+  }
+
+  state.end_playback();
+  Task::batch(tasks)
+}
+
 pub fn switch_tab(state: &mut State, index: usize) -> Task<Message> {
   state.switch_tab(index);
   Task::none()
@@ -21,6 +345,46 @@ pub fn link_clicked(url: String) -> Task<Message> {
   Task::none()
 }
 
+pub fn load_images(state: &mut State) -> Task<Message> {
+  Task::batch(
+    state
+      .request_images()
+      .into_iter()
+      .map(|source| Task::perform(images::load(source), Message::ImageLoaded)),
+  )
+}
+
+pub fn image_loaded(state: &mut State, loaded: images::Loaded) -> Task<Message> {
+  state.apply_image(loaded);
+  Task::none()
+}
+
+pub fn images_polled(state: &mut State) -> Task<Message> {
+  Task::perform(
+    images::changed(state.images().watched()),
+    Message::ImagesChanged,
+  )
+}
+
+pub fn images_changed(state: &mut State, paths: Vec<PathBuf>) -> Task<Message> {
+  Task::batch(
+    state
+      .reload_images(paths)
+      .into_iter()
+      .map(|source| Task::perform(images::load(source), Message::ImageLoaded)),
+  )
+}
+
+pub fn remote_images_allowed(state: &mut State) -> Task<Message> {
+  state.allow_remote_images();
+  Task::none()
+}
+
+pub fn task_toggled(state: &mut State, line: usize, done: bool) -> Task<Message> {
+  state.toggle_task(line, done);
+  Task::none()
+}
+
 pub fn file_action(state: &mut State, action: FileAction) -> Task<Message> {
   match action {
     FileAction::New => {
@@ -35,49 +399,316 @@ pub fn file_action(state: &mut State, action: FileAction) -> Task<Message> {
       }
     }
     FileAction::Open => Task::perform(io::open_file(), Message::FileOpened),
-    FileAction::Save => {
-      let (current_path, content) = state.active_file_data();
-      let path = current_path.cloned();
-      Task::perform(io::save_file(path, content), Message::FileSaved)
+    FileAction::Save => match state.active_file_path() {
+      Some(path) => save(state, path),
+      None => Task::perform(io::pick_save_path(), Message::SavePathPicked),
+    },
+    FileAction::SaveAs => Task::perform(io::pick_save_path(), Message::SavePathPicked),
+    FileAction::ExportHtml => export(
+      state,
+      Format::Html {
+        with_contents: false,
+      },
+    ),
+    FileAction::ExportHtmlWithContents => export(
+      state,
+      Format::Html {
+        with_contents: true,
+      },
+    ),
+    FileAction::ExportPdf(page_size) => {
+      state.open_margin_prompt(pdf::MarginPrompt::new(page_size));
+      operation::focus(margin_prompt::ID)
     }
-    FileAction::SaveAs => {
-      let (_, content) = state.active_file_data();
-      Task::perform(io::save_file(None, content), Message::FileSaved)
+    FileAction::ToggleTrimTrailingWhitespace => {
+      state.toggle_cleanup(|cleanup| &mut cleanup.trim_trailing_whitespace);
+      Task::none()
+    }
+    FileAction::ToggleInsertFinalNewline => {
+      state.toggle_cleanup(|cleanup| &mut cleanup.insert_final_newline);
+      Task::none()
+    }
+    FileAction::ToggleTrimFinalNewlines => {
+      state.toggle_cleanup(|cleanup| &mut cleanup.trim_final_newlines);
+      Task::none()
     }
   }
 }
 
-pub fn opened_file(state: &mut State, result: Result<(PathBuf, String), String>) -> Task<Message> {
+pub fn edit_action(state: &mut State, action: EditAction) -> Task<Message> {
+  match action {
+    EditAction::Copy => match state.active_selection() {
+      Some(text) => clipboard::write(text),
+      None => Task::none(),
+    },
+    EditAction::Cut => match state.active_selection() {
+      Some(text) => {
+        state.delete_active_selection();
+        clipboard::write(text)
+      }
+      None => Task::none(),
+    },
+    EditAction::AddCursorAbove => {
+      state.add_cursor_above();
+      Task::none()
+    }
+    EditAction::AddCursorBelow => {
+      state.add_cursor_below();
+      Task::none()
+    }
+    EditAction::AddNextOccurrence => {
+      state.add_next_occurrence();
+      Task::none()
+    }
+    EditAction::ClearCursors => {
+      state.clear_cursors();
+      Task::none()
+    }
+    EditAction::Undo => {
+      state.undo();
+      Task::none()
+    }
+    EditAction::Redo => {
+      state.redo();
+      Task::none()
+    }
+    EditAction::Indent if state.is_completing() => {
+      state.accept_completion();
+      Task::none()
+    }
+    EditAction::Indent => {
+      state.indent_selection();
+      Task::none()
+    }
+    EditAction::Outdent => {
+      state.outdent_selection();
+      Task::none()
+    }
+    EditAction::IndentUsingSpaces => {
+      state.convert_indentation(indent::Style::Spaces);
+      Task::none()
+    }
+    EditAction::IndentUsingTabs => {
+      state.convert_indentation(indent::Style::Tabs);
+      Task::none()
+    }
+    EditAction::JumpToBracket => {
+      state.jump_to_bracket();
+      Task::none()
+    }
+    EditAction::ToggleLineComment => {
+      state.toggle_line_comment();
+      Task::none()
+    }
+    EditAction::ToggleBlockComment => {
+      state.toggle_block_comment();
+      Task::none()
+    }
+    EditAction::TriggerCompletion => {
+      state.complete(true);
+      Task::none()
+    }
+    EditAction::ColumnSelectUp => {
+      state.extend_column_selection(-1, 0);
+      Task::none()
+    }
+    EditAction::ColumnSelectDown => {
+      state.extend_column_selection(1, 0);
+      Task::none()
+    }
+    EditAction::ColumnSelectLeft => {
+      state.extend_column_selection(0, -1);
+      Task::none()
+    }
+    EditAction::ColumnSelectRight => {
+      state.extend_column_selection(0, 1);
+      Task::none()
+    }
+  }
+}
+
+pub fn line_action(state: &mut State, action: LineAction) -> Task<Message> {
+  state.apply_line_action(action);
+  Task::none()
+}
+
+pub fn transform_action(state: &mut State, action: TransformAction) -> Task<Message> {
+  state.apply_transform(action);
+  Task::none()
+}
+
+pub fn table_action(state: &mut State, action: TableAction) -> Task<Message> {
+  state.apply_table_action(action);
+  Task::none()
+}
+
+pub fn opened_file(
+  state: &mut State,
+  result: Result<(PathBuf, String, Properties), String>,
+) -> Task<Message> {
   match result {
-    Ok((path, content)) => {
-      state.open_file(path, content);
+    Ok((path, content, properties)) => {
+      state.open_file(path, content, properties);
+      state.set_notice(None);
     }
-    Err(_error) => {}
+    Err(error) => show_error(state, error),
   };
 
   Task::none()
 }
 
+pub fn save_path_picked(
+  state: &mut State,
+  result: Result<(PathBuf, Properties), String>,
+) -> Task<Message> {
+  match result {
+    Ok((path, properties)) => {
+      state.set_active_file_editorconfig(properties);
+      save(state, path)
+    }
+    Err(error) => {
+      show_error(state, error);
+      Task::none()
+    }
+  }
+}
+
+fn save(state: &mut State, path: PathBuf) -> Task<Message> {
+  state.clean_up_whitespace();
+
+  match state.encode_active_file() {
+    Ok(content) => Task::perform(io::save_file(path, content), Message::FileSaved),
+    Err(error) => {
+      show_error(state, error);
+      Task::none()
+    }
+  }
+}
+
 pub fn saved_file(state: &mut State, result: Result<PathBuf, String>) -> Task<Message> {
   match result {
     Ok(path) => {
       state.set_active_file_path(path);
       state.set_active_file_save_status(false);
+      state.set_notice(None);
     }
-    Err(_error) => {}
+    Err(error) => show_error(state, error),
   };
 
   Task::none()
 }
 
+fn export(state: &State, format: Format) -> Task<Message> {
+  let (name, source, directory) = state.export_source();
+  Task::perform(
+    io::export_file(name, source, directory, format),
+    Message::FileExported,
+  )
+}
+
+pub fn exported_file(state: &mut State, result: Result<PathBuf, String>) -> Task<Message> {
+  match result {
+    Ok(_) => state.set_notice(None),
+    Err(error) => show_error(state, error),
+  };
+
+  Task::none()
+}
+
+fn show_error(state: &mut State, error: String) {
+  if error != io::CANCELLED {
+    state.set_notice(Some(error));
+  }
+}
+
 pub fn view_action(state: &mut State, action: ViewAction) -> Task<Message> {
   match action {
     ViewAction::Increase => state.increase_font(),
     ViewAction::Decrease => state.decrease_font(),
     ViewAction::Reset => state.reset_font(),
     ViewAction::TogglePreview => state.toggle_preview(),
+    ViewAction::ToggleSplit => state.toggle_split(),
     ViewAction::ToggleWordWrap => state.toggle_word_wrap(),
+    ViewAction::ToggleLineNumbers => state.toggle_line_numbers(),
+    ViewAction::ToggleRelativeLineNumbers => state.toggle_relative_line_numbers(),
+    ViewAction::ToggleWhitespace => state.toggle_whitespace(),
+    ViewAction::Fold => state.fold(),
+    ViewAction::Unfold => state.unfold(),
+    ViewAction::FoldLevel(level) => state.fold_level(level),
+    ViewAction::UnfoldAll => state.unfold_all(),
+    ViewAction::ToggleOutline => state.toggle_outline(),
   }
 
   Task::none()
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::editorconfig::Charset;
+  use iced::widget::text_editor::{Action, Edit, Motion};
+  use iced::window;
+
+  fn state(content: &str) -> State {
+    let mut state = State::new(window::Id::unique());
+    state.open_file(
+      PathBuf::from("macro.md"),
+      String::from(content),
+      Properties::default(),
+    );
+    state
+  }
+
+  #[test]
+  fn playing_to_end_stops_when_the_cursor_does_not_advance() {
+    let mut state = state("abc\n");
+    let steps = vec![
+      Message::Edit(Action::Edit(Edit::Insert('x'))),
+      Message::Edit(Action::Move(Motion::Left)),
+    ];
+
+    let _ = play_macro(&mut state, steps, Repeat::ToEnd);
+
+    assert_eq!(state.active_file().content().text(), "xabc\n");
+  }
+
+  #[test]
+  fn playing_to_end_stops_at_the_end_of_the_document() {
+    let mut state = state("a\nb\nc");
+    let steps = vec![
+      Message::Edit(Action::Edit(Edit::Insert('-'))),
+      Message::Edit(Action::Move(Motion::Down)),
+      Message::Edit(Action::Move(Motion::Home)),
+    ];
+
+    let _ = play_macro(&mut state, steps, Repeat::ToEnd);
+
+    assert_eq!(state.active_file().content().text(), "-a\n-b\n-c");
+  }
+
+  #[test]
+  fn save_as_applies_the_editorconfig_of_the_new_path() {
+    let mut state = state("price: 5 €\n");
+    let properties = Properties {
+      charset: Some(Charset::Latin1),
+      ..Properties::default()
+    };
+
+    let _ = save_path_picked(&mut state, Ok((PathBuf::from("latin1.md"), properties)));
+
+    assert_eq!(
+      state.active_file().editorconfig().charset,
+      Some(Charset::Latin1)
+    );
+    assert_eq!(state.notice(), Some("'€' cannot be saved as Latin-1"));
+  }
+
+  #[test]
+  fn cancelled_save_dialogs_are_not_errors() {
+    let mut state = state("");
+
+    let _ = save_path_picked(&mut state, Err(String::from(io::CANCELLED)));
+
+    assert_eq!(state.notice(), None);
+  }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use iced::advanced::text::highlighter::{self, Format};
+use iced::{Font, Theme};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoration {
+  Cursor,
+  Selection,
+  Bracket,
+}
+
+pub type Decorations = BTreeMap<usize, Vec<(Range<usize>, Decoration)>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+  pub syntax: iced::highlighter::Settings,
+  pub decorations: Arc<Decorations>,
+}
+
+pub enum Highlight {
+  Syntax(iced::highlighter::Highlight),
+  Decoration(Decoration),
+}
+
+impl Highlight {
+  pub fn to_format(&self, theme: &Theme) -> Format<Font> {
+    match self {
+      Highlight::Syntax(highlight) => highlight.to_format(),
+      Highlight::Decoration(decoration) => {
+        let color = match decoration {
+          Decoration::Cursor => theme.palette().danger,
+          Decoration::Selection => theme.palette().warning,
+          Decoration::Bracket => theme.palette().success,
+        };
+
+        Format {
+          color: Some(color),
+          font: None,
+        }
+      }
+    }
+  }
+}
+
+pub struct Highlighter {
+  syntax: iced::highlighter::Highlighter,
+  settings: Settings,
+}
+
+impl highlighter::Highlighter for Highlighter {
+  type Settings = Settings;
+  type Highlight = Highlight;
+
+  type Iterator<'a> = Box<dyn Iterator<Item = (Range<usize>, Self::Highlight)> + 'a>;
+
+  fn new(settings: &Self::Settings) -> Self {
+    Highlighter {
+      syntax: iced::highlighter::Highlighter::new(&settings.syntax),
+      settings: settings.clone(),
+    }
+  }
+
+  fn update(&mut self, new_settings: &Self::Settings) {
+    if self.settings.syntax != new_settings.syntax {
+      self.syntax.update(&new_settings.syntax);
+    } else if let Some(line) =
+      first_changed_line(&self.settings.decorations, &new_settings.decorations)
+    {
+      self.syntax.change_line(line);
+    }
+
+    self.settings = new_settings.clone();
+  }
+
+  fn change_line(&mut self, line: usize) {
+    self.syntax.change_line(line);
+  }
+
+  fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+    let index = self.syntax.current_line();
+    let length = line.len();
+
+    let decorations = self
+      .settings
+      .decorations
+      .get(&index)
+      .into_iter()
+      .flatten()
+      .map(move |(range, decoration)| {
+        (
+          range.start.min(length)..range.end.min(length),
+          Highlight::Decoration(*decoration),
+        )
+      })
+      .filter(|(range, _)| !range.is_empty());
+
+    Box::new(
+      self
+        .syntax
+        .highlight_line(line)
+        .map(|(range, highlight)| (range, Highlight::Syntax(highlight)))
+        .chain(decorations),
+    )
+  }
+
+  fn current_line(&self) -> usize {
+    self.syntax.current_line()
+  }
+}
+
+fn first_changed_line(old: &Decorations, new: &Decorations) -> Option<usize> {
+  old
+    .keys()
+    .chain(new.keys())
+    .filter(|line| old.get(line) != new.get(line))
+    .min()
+    .copied()
+}
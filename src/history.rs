@@ -0,0 +1,410 @@
+use std::ops::Range;
+
+use iced::widget::text_editor::{Cursor, Edit, Position};
+
+const LIMIT: usize = 200;
+
+pub trait Document {
+  fn line_count(&self) -> usize;
+  fn lines(&self, range: Range<usize>) -> String;
+  fn cursor(&self) -> Cursor;
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+  first: usize,
+  suffix: usize,
+  text: String,
+  cursor: Cursor,
+}
+
+impl Snapshot {
+  fn of(document: &impl Document, lines: Range<usize>) -> Self {
+    let count = document.line_count();
+    let first = lines.start.min(count.saturating_sub(1));
+    let end = lines.end.clamp(first + 1, count.max(1));
+
+    Self {
+      first,
+      suffix: count - end,
+      text: document.lines(first..end),
+      cursor: document.cursor(),
+    }
+  }
+
+  fn current(&self, document: &impl Document) -> Self {
+    let end = document
+      .line_count()
+      .saturating_sub(self.suffix)
+      .max(self.first);
+
+    Self {
+      first: self.first,
+      suffix: self.suffix,
+      text: document.lines(self.first..end),
+      cursor: document.cursor(),
+    }
+  }
+
+  fn widen(&mut self, document: &impl Document, lines: Range<usize>) {
+    let count = document.line_count();
+    let end = count.saturating_sub(self.suffix);
+
+    if lines.start < self.first {
+      let before = document.lines(lines.start..self.first);
+      self.text = format!("{before}\n{}", self.text);
+      self.first = lines.start;
+    }
+
+    if lines.end.min(count) > end {
+      let after = document.lines(end..lines.end.min(count));
+      self.text = format!("{}\n{after}", self.text);
+      self.suffix = count - lines.end.min(count);
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+  pub start: Position,
+  pub removed: String,
+  pub inserted: String,
+  pub before: Cursor,
+  pub after: Cursor,
+}
+
+impl Change {
+  fn between(old: &Snapshot, new: &Snapshot) -> Option<Self> {
+    let prefix = old
+      .text
+      .char_indices()
+      .zip(new.text.chars())
+      .find(|((_, a), b)| a != b)
+      .map_or(old.text.len().min(new.text.len()), |((index, _), _)| index);
+
+    let suffix = old.text[prefix..]
+      .chars()
+      .rev()
+      .zip(new.text[prefix..].chars().rev())
+      .take_while(|(a, b)| a == b)
+      .map(|(a, _)| a.len_utf8())
+      .sum::<usize>();
+
+    let removed = &old.text[prefix..old.text.len() - suffix];
+    let inserted = &new.text[prefix..new.text.len() - suffix];
+
+    if removed.is_empty() && inserted.is_empty() {
+      return None;
+    }
+
+    let first = Position {
+      line: old.first,
+      column: 0,
+    };
+
+    Some(Self {
+      start: end_of(first, &old.text[..prefix]),
+      removed: removed.to_owned(),
+      inserted: inserted.to_owned(),
+      before: old.cursor,
+      after: new.cursor,
+    })
+  }
+
+  pub fn end(&self) -> Position {
+    end_of(self.start, &self.removed)
+  }
+
+  fn inverse(self) -> Self {
+    Self {
+      start: self.start,
+      removed: self.inserted,
+      inserted: self.removed,
+      before: self.after,
+      after: self.before,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+  Typing,
+  Deleting,
+  Command,
+}
+
+impl Kind {
+  pub fn of(edit: &Edit) -> Self {
+    match edit {
+      Edit::Insert(c) if !c.is_whitespace() => Kind::Typing,
+      Edit::Backspace | Edit::Delete => Kind::Deleting,
+      _ => Kind::Command,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+  base: Option<Snapshot>,
+  undo: Vec<Change>,
+  redo: Vec<Change>,
+  last: Option<Kind>,
+  is_held: bool,
+}
+
+impl History {
+  pub fn record(&mut self, document: &impl Document, lines: Range<usize>, kind: Kind) {
+    if self.is_held {
+      return;
+    }
+
+    let is_grouped = kind != Kind::Command && self.last == Some(kind);
+
+    self.last = Some(kind);
+    self.redo.clear();
+
+    if is_grouped && let Some(base) = &mut self.base {
+      base.widen(document, lines);
+      return;
+    }
+
+    self.close(document);
+    self.base = Some(Snapshot::of(document, lines));
+  }
+
+  pub fn hold(&mut self, document: &impl Document) {
+    self.record(document, 0..usize::MAX, Kind::Command);
+    self.is_held = true;
+  }
+
+  pub fn release(&mut self, document: &impl Document) {
+    self.is_held = false;
+    self.last = None;
+    self.close(document);
+  }
+
+  pub fn break_group(&mut self) {
+    self.last = None;
+  }
+
+  pub fn undo(&mut self, document: &impl Document) -> Option<Change> {
+    self.close(document);
+
+    let change = self.undo.pop()?;
+
+    self.redo.push(change.clone());
+    self.last = None;
+
+    Some(change.inverse())
+  }
+
+  pub fn redo(&mut self, document: &impl Document) -> Option<Change> {
+    self.close(document);
+
+    let change = self.redo.pop()?;
+
+    self.undo.push(change.clone());
+    self.last = None;
+
+    Some(change)
+  }
+
+  pub fn clear(&mut self) {
+    self.base = None;
+    self.undo.clear();
+    self.redo.clear();
+    self.last = None;
+  }
+
+  fn close(&mut self, document: &impl Document) {
+    let Some(change) = self
+      .base
+      .take()
+      .and_then(|base| Change::between(&base, &base.current(document)))
+    else {
+      return;
+    };
+
+    self.undo.push(change);
+
+    if self.undo.len() > LIMIT {
+      self.undo.remove(0);
+    }
+  }
+}
+
+fn end_of(start: Position, text: &str) -> Position {
+  match text.rsplit_once('\n') {
+    Some((before, after)) => Position {
+      line: start.line + before.matches('\n').count() + 1,
+      column: after.len(),
+    },
+    None => Position {
+      line: start.line,
+      column: start.column + text.len(),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct Text(Vec<String>);
+
+  impl Text {
+    fn new(text: &str) -> Self {
+      Self(text.split('\n').map(String::from).collect())
+    }
+
+    fn set(&mut self, text: &str) {
+      *self = Self::new(text);
+    }
+
+    fn apply(&mut self, change: &Change) {
+      let text = self.0.join("\n");
+      let offset = |position: Position| {
+        self.0[..position.line]
+          .iter()
+          .map(|line| line.len() + 1)
+          .sum::<usize>()
+          + position.column
+      };
+
+      let (start, end) = (offset(change.start), offset(change.end()));
+      let text = format!("{}{}{}", &text[..start], change.inserted, &text[end..]);
+
+      self.set(&text);
+    }
+  }
+
+  impl Document for Text {
+    fn line_count(&self) -> usize {
+      self.0.len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> String {
+      self.0[range.start.min(self.0.len())..range.end.min(self.0.len())].join("\n")
+    }
+
+    fn cursor(&self) -> Cursor {
+      Cursor {
+        position: Position { line: 0, column: 0 },
+        selection: None,
+      }
+    }
+  }
+
+  #[test]
+  fn groups_typing_into_one_change() {
+    let mut history = History::default();
+    let mut text = Text::new("one\ntwo\nthree");
+
+    for typed in ["one\ntwox\nthree", "one\ntwoxy\nthree"] {
+      history.record(&text, 0..3, Kind::Typing);
+      text.set(typed);
+    }
+
+    let change = history.undo(&text).unwrap();
+
+    assert_eq!(change.start, Position { line: 1, column: 3 });
+    assert_eq!(change.removed, "xy");
+    assert_eq!(change.inserted, "");
+    assert!(history.undo(&text).is_none());
+  }
+
+  #[test]
+  fn records_only_the_edited_lines() {
+    let mut history = History::default();
+    let mut text = Text::new("a\nb\nc\nd\ne");
+
+    history.record(&text, 2..3, Kind::Command);
+    text.set("a\nb\nc!\nd\ne");
+
+    let base = history.base.as_ref().unwrap();
+    assert_eq!((base.first, base.suffix, base.text.as_str()), (2, 2, "c"));
+
+    let change = history.undo(&text).unwrap();
+    assert_eq!(change.start, Position { line: 2, column: 1 });
+    assert_eq!(change.removed, "!");
+  }
+
+  #[test]
+  fn widens_a_group_that_leaves_its_lines() {
+    let mut history = History::default();
+    let mut text = Text::new("a\nb\nc\nd");
+
+    history.record(&text, 2..3, Kind::Deleting);
+    text.set("a\nb\n\nd");
+    history.record(&text, 0..3, Kind::Deleting);
+    text.set("a\nd");
+
+    let change = history.undo(&text).unwrap();
+    text.apply(&change);
+
+    assert_eq!(text.0, ["a", "b", "c", "d"]);
+  }
+
+  #[test]
+  fn redoes_an_undone_change() {
+    let mut history = History::default();
+    let mut text = Text::new("a\nb");
+
+    history.record(&text, 1..2, Kind::Command);
+    text.set("a\nb\nc");
+
+    let undo = history.undo(&text).unwrap();
+    text.apply(&undo);
+    assert_eq!(text.0, ["a", "b"]);
+
+    let redo = history.redo(&text).unwrap();
+    text.apply(&redo);
+    assert_eq!(text.0, ["a", "b", "c"]);
+  }
+
+  #[test]
+  fn commands_break_groups() {
+    let mut history = History::default();
+    let mut text = Text::new("");
+
+    for (kind, typed) in [
+      (Kind::Typing, "a"),
+      (Kind::Command, "a "),
+      (Kind::Typing, "a b"),
+    ] {
+      history.record(&text, 0..1, kind);
+      text.set(typed);
+    }
+
+    assert_eq!(history.undo(&text).unwrap().removed, "b");
+    assert_eq!(history.undo(&text).unwrap().removed, " ");
+    assert_eq!(history.undo(&text).unwrap().removed, "a");
+  }
+
+  #[test]
+  fn holds_a_transaction_as_one_change() {
+    let mut history = History::default();
+    let mut text = Text::new("a\nb");
+
+    history.hold(&text);
+    history.record(&text, 0..1, Kind::Typing);
+    text.set("x\nb");
+    history.record(&text, 1..2, Kind::Command);
+    text.set("x\ny");
+    history.release(&text);
+
+    let change = history.undo(&text).unwrap();
+    text.apply(&change);
+
+    assert_eq!(text.0, ["a", "b"]);
+    assert!(history.undo(&text).is_none());
+  }
+
+  #[test]
+  fn classifies_edits() {
+    assert_eq!(Kind::of(&Edit::Insert('a')), Kind::Typing);
+    assert_eq!(Kind::of(&Edit::Insert(' ')), Kind::Command);
+    assert_eq!(Kind::of(&Edit::Backspace), Kind::Deleting);
+    assert_eq!(Kind::of(&Edit::Enter), Kind::Command);
+  }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use iced::widget::image::Handle;
+
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REDIRECTS: u32 = 5;
+const MAX_REMOTE_BYTES: u64 = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+  Local(PathBuf),
+  Remote(String),
+}
+
+impl Source {
+  pub fn resolve(url: &str, directory: Option<&Path>) -> Option<Source> {
+    let url = url.trim();
+
+    if url.is_empty() {
+      return None;
+    }
+
+    if url.starts_with("http://") || url.starts_with("https://") {
+      return Some(Source::Remote(url.to_owned()));
+    }
+
+    if let Some(path) = url.strip_prefix("file://") {
+      return Some(Source::Local(PathBuf::from(decode(path))));
+    }
+
+    if url.contains("://") || url.starts_with("data:") {
+      return None;
+    }
+
+    let path = PathBuf::from(decode(url.split(['?', '#']).next().unwrap_or(url)));
+
+    Some(Source::Local(match directory {
+      Some(directory) if path.is_relative() => directory.join(path).components().collect(),
+      _ => path,
+    }))
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum Entry {
+  Loading,
+  Loaded(Handle),
+  Failed(String),
+  Blocked,
+}
+
+#[derive(Debug, Clone)]
+pub struct Loaded {
+  source: Source,
+  result: Result<(Handle, Option<SystemTime>), String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Cache {
+  entries: HashMap<Source, Entry>,
+  modified: HashMap<PathBuf, Option<SystemTime>>,
+  allows_remote: bool,
+}
+
+impl Cache {
+  pub fn entry(&self, source: &Source) -> Entry {
+    match source {
+      Source::Remote(_) if !self.allows_remote => Entry::Blocked,
+      _ => self.entries.get(source).cloned().unwrap_or(Entry::Loading),
+    }
+  }
+
+  pub fn is_watching(&self) -> bool {
+    !self.modified.is_empty()
+  }
+
+  pub fn allow_remote(&mut self) {
+    self.allows_remote = true;
+  }
+
+  pub fn request(&mut self, sources: impl IntoIterator<Item = Source>) -> Vec<Source> {
+    let mut requested = Vec::new();
+
+    for source in sources {
+      if matches!(source, Source::Remote(_)) && !self.allows_remote {
+        continue;
+      }
+
+      if !self.entries.contains_key(&source) {
+        self.entries.insert(source.clone(), Entry::Loading);
+        requested.push(source);
+      }
+    }
+
+    requested
+  }
+
+  pub fn apply(&mut self, loaded: Loaded) {
+    let (entry, modified) = match loaded.result {
+      Ok((handle, modified)) => (Entry::Loaded(handle), modified),
+      Err(error) => (Entry::Failed(error), None),
+    };
+
+    if let Source::Local(path) = &loaded.source {
+      self.modified.insert(path.clone(), modified);
+    }
+
+    self.entries.insert(loaded.source, entry);
+  }
+
+  pub fn watched(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+    self
+      .modified
+      .iter()
+      .map(|(path, modified)| (path.clone(), *modified))
+      .collect()
+  }
+
+  pub fn reload(&mut self, paths: Vec<PathBuf>) -> Vec<Source> {
+    paths
+      .into_iter()
+      .filter_map(|path| self.modified.remove(&path).map(|_| Source::Local(path)))
+      .collect()
+  }
+}
+
+pub async fn load(source: Source) -> Loaded {
+  let result = {
+    let source = source.clone();
+
+    tokio::task::spawn_blocking(move || read(&source))
+      .await
+      .map_err(|error| error.to_string())
+      .and_then(|result| result)
+  };
+
+  Loaded { source, result }
+}
+
+pub async fn changed(watched: Vec<(PathBuf, Option<SystemTime>)>) -> Vec<PathBuf> {
+  let mut changed = Vec::new();
+
+  for (path, previous) in watched {
+    let modified = tokio::fs::metadata(&path)
+      .await
+      .and_then(|metadata| metadata.modified())
+      .ok();
+
+    if modified != previous {
+      changed.push(path);
+    }
+  }
+
+  changed
+}
+
+fn read(source: &Source) -> Result<(Handle, Option<SystemTime>), String> {
+  let (bytes, modified) = match source {
+    Source::Local(path) => {
+      let bytes = std::fs::read(path).map_err(|error| match error.kind() {
+        io::ErrorKind::NotFound => "Image not found".to_owned(),
+        _ => error.to_string(),
+      })?;
+      let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+      (bytes, modified)
+    }
+    Source::Remote(url) => (fetch(url)?, None),
+  };
+
+  let image = image::load_from_memory(&bytes)
+    .map_err(|error| error.to_string())?
+    .to_rgba8();
+  let (width, height) = image.dimensions();
+
+  Ok((Handle::from_rgba(width, height, image.into_raw()), modified))
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+  let agent: ureq::Agent = ureq::Agent::config_builder()
+    .timeout_global(Some(REMOTE_TIMEOUT))
+    .max_redirects(MAX_REDIRECTS)
+    .user_agent("zoeae")
+    .build()
+    .into();
+
+  agent
+    .get(url)
+    .call()
+    .map_err(|error| error.to_string())?
+    .body_mut()
+    .with_config()
+    .limit(MAX_REMOTE_BYTES)
+    .read_to_vec()
+    .map_err(|error| error.to_string())
+}
+
+fn decode(path: &str) -> String {
+  let bytes = path.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+
+  while index < bytes.len() {
+    let escaped = (bytes[index] == b'%')
+      .then(|| path.get(index + 1..index + 3))
+      .flatten()
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+    match escaped {
+      Some(byte) => {
+        decoded.push(byte);
+        index += 3;
+      }
+      None => {
+        decoded.push(bytes[index]);
+        index += 1;
+      }
+    }
+  }
+
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolves_relative_paths_against_the_file_directory() {
+    let directory = Path::new("/notes/daily");
+
+    assert_eq!(
+      Source::resolve("../images/my%20cat.png?size=2#top", Some(directory)),
+      Some(Source::Local(PathBuf::from(
+        "/notes/daily/../images/my cat.png"
+      )))
+    );
+    assert_eq!(
+      Source::resolve("/tmp/a.png", Some(directory)),
+      Some(Source::Local(PathBuf::from("/tmp/a.png")))
+    );
+    assert_eq!(
+      Source::resolve("file:///tmp/a%2Bb.png", None),
+      Some(Source::Local(PathBuf::from("/tmp/a+b.png")))
+    );
+  }
+
+  #[test]
+  fn resolves_remote_and_unsupported_urls() {
+    assert_eq!(
+      Source::resolve(" https://example.com/a.png ", None),
+      Some(Source::Remote(String::from("https://example.com/a.png")))
+    );
+    assert_eq!(Source::resolve("ftp://example.com/a.png", None), None);
+    assert_eq!(Source::resolve("data:image/png;base64,AAAA", None), None);
+    assert_eq!(Source::resolve("  ", None), None);
+  }
+
+  #[test]
+  fn decodes_percent_escapes_and_keeps_invalid_ones() {
+    assert_eq!(decode("a%20b%zz%2"), "a b%zz%2");
+    assert_eq!(decode("caf%C3%A9"), "café");
+  }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+const DEFAULT_SIZE: usize = 4;
+const OPENERS: &[char] = &['{', '[', '('];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+  Spaces,
+  Tabs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Indent {
+  pub style: Style,
+  pub size: usize,
+}
+
+impl Default for Indent {
+  fn default() -> Self {
+    Indent {
+      style: Style::Spaces,
+      size: DEFAULT_SIZE,
+    }
+  }
+}
+
+impl Display for Indent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.style {
+      Style::Spaces => write!(f, "Spaces: {}", self.size),
+      Style::Tabs => write!(f, "Tabs"),
+    }
+  }
+}
+
+impl Indent {
+  pub fn unit(&self) -> String {
+    match self.style {
+      Style::Spaces => " ".repeat(self.size),
+      Style::Tabs => String::from("\t"),
+    }
+  }
+
+  pub fn unit_at(&self, visual_column: usize) -> String {
+    match self.style {
+      Style::Spaces => " ".repeat(self.size - visual_column % self.size),
+      Style::Tabs => String::from("\t"),
+    }
+  }
+
+  pub fn width(&self, whitespace: &str) -> usize {
+    whitespace.chars().fold(0, |width, c| match c {
+      '\t' => (width / self.size + 1) * self.size,
+      _ => width + 1,
+    })
+  }
+
+  pub fn render(&self, width: usize) -> String {
+    match self.style {
+      Style::Spaces => " ".repeat(width),
+      Style::Tabs => "\t".repeat(width / self.size) + &" ".repeat(width % self.size),
+    }
+  }
+
+  pub fn indented(&self, line: &str) -> String {
+    if line.trim().is_empty() {
+      line.to_owned()
+    } else {
+      self.unit() + line
+    }
+  }
+
+  pub fn outdented(&self, line: &str) -> String {
+    if let Some(rest) = line.strip_prefix('\t') {
+      return rest.to_owned();
+    }
+
+    let spaces = line
+      .chars()
+      .take(self.size)
+      .take_while(|c| *c == ' ')
+      .count();
+
+    line[spaces..].to_owned()
+  }
+
+  pub fn line_break(&self, before_caret: &str) -> String {
+    let mut indentation = leading_whitespace(before_caret).to_owned();
+
+    if before_caret.trim_end().ends_with(OPENERS) {
+      indentation.push_str(&self.unit());
+    }
+
+    indentation
+  }
+
+  pub fn convert(&self, line: &str, to: Indent) -> String {
+    let whitespace = leading_whitespace(line);
+    let width = self.width(whitespace);
+
+    Indent {
+      size: self.size,
+      ..to
+    }
+    .render(width)
+      + &line[whitespace.len()..]
+  }
+}
+
+pub fn leading_whitespace(line: &str) -> &str {
+  let end = line
+    .find(|c: char| c != ' ' && c != '\t')
+    .unwrap_or(line.len());
+
+  &line[..end]
+}
+
+pub fn detect(text: &str) -> Option<Indent> {
+  let mut tabs = 0;
+  let mut spaces = 0;
+  let mut deltas = [0usize; 9];
+  let mut previous = 0;
+
+  for line in text.lines().filter(|line| !line.trim().is_empty()) {
+    let whitespace = leading_whitespace(line);
+
+    if whitespace.starts_with('\t') {
+      tabs += 1;
+      previous = 0;
+      continue;
+    }
+
+    let width = whitespace.len();
+
+    if width > 0 {
+      spaces += 1;
+    }
+
+    let delta = width.abs_diff(previous);
+
+    if (2..=8).contains(&delta) {
+      deltas[delta] += 1;
+    }
+
+    previous = width;
+  }
+
+  if tabs == 0 && spaces == 0 {
+    return None;
+  }
+
+  if tabs > spaces {
+    return Some(Indent {
+      style: Style::Tabs,
+      size: DEFAULT_SIZE,
+    });
+  }
+
+  let size = (2..=8)
+    .max_by_key(|&size| (deltas[size], std::cmp::Reverse(size)))
+    .filter(|&size| deltas[size] > 0)
+    .unwrap_or(DEFAULT_SIZE);
+
+  Some(Indent {
+    style: Style::Spaces,
+    size,
+  })
+}
@@ -1,54 +1,118 @@
+use std::env;
 use std::path::PathBuf;
 
 use rfd::AsyncFileDialog;
 use tokio::fs;
 
-pub async fn open_file() -> Result<(PathBuf, String), String> {
+use crate::constants;
+use crate::editorconfig::{self, Properties};
+use crate::export::{self, Format};
+use crate::pdf;
+
+pub const CANCELLED: &str = "Dialog cancelled";
+
+pub fn config_path(file: &str) -> Option<PathBuf> {
+  let base = env::var_os("XDG_CONFIG_HOME")
+    .or_else(|| env::var_os("APPDATA"))
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+  Some(base.join(constants::CONFIG_DIRECTORY).join(file))
+}
+
+pub async fn open_file() -> Result<(PathBuf, String, Properties), String> {
   let handle_result = AsyncFileDialog::new()
     .set_directory("/")
     .pick_file()
     .await
-    .ok_or(String::from("Dialog cancelled"));
+    .ok_or(String::from(CANCELLED));
 
   match handle_result {
-    Ok(handle) => {
-      let path = handle.path().to_owned();
-      let content_result = fs::read_to_string(&path).await.map_err(|e| e.to_string());
-
-      match content_result {
-        Ok(content) => Ok((path, content)),
-        Err(e) => Err(e),
-      }
-    }
+    Ok(handle) => load_file(handle.path().to_owned()).await,
     Err(e) => Err(e),
   }
 }
 
-pub async fn save_file(path: Option<PathBuf>, text: String) -> Result<PathBuf, String> {
-  let save_path = match path {
-    Some(p) => p,
-    None => AsyncFileDialog::new()
-      .set_directory("/")
-      .save_file()
-      .await
-      .ok_or(String::from("Dialog cancelled"))?
-      .path()
-      .to_owned(),
-  };
+pub async fn pick_save_path() -> Result<(PathBuf, Properties), String> {
+  let path = AsyncFileDialog::new()
+    .set_directory("/")
+    .save_file()
+    .await
+    .ok_or(String::from(CANCELLED))?
+    .path()
+    .to_owned();
 
-  let save_result = fs::write(&save_path, text).await.map_err(|e| e.to_string());
+  let lookup = path.clone();
+  let properties = tokio::task::spawn_blocking(move || editorconfig::resolve(&lookup))
+    .await
+    .map_err(|e| e.to_string())?;
 
-  match save_result {
-    Ok(_) => Ok(save_path),
-    Err(err) => Err(err),
-  }
+  Ok((path, properties))
+}
+
+pub async fn save_file(path: PathBuf, contents: Vec<u8>) -> Result<PathBuf, String> {
+  fs::write(&path, contents)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(path)
 }
 
-pub async fn load_file(path: PathBuf) -> Result<(PathBuf, String), String> {
-  let content_result = fs::read_to_string(&path).await.map_err(|e| e.to_string());
+pub async fn export_file(
+  name: String,
+  source: String,
+  directory: Option<PathBuf>,
+  format: Format,
+) -> Result<PathBuf, String> {
+  let path = AsyncFileDialog::new()
+    .set_file_name(format!("{name}.{}", format.extension()))
+    .save_file()
+    .await
+    .ok_or(String::from(CANCELLED))?
+    .path()
+    .to_owned();
 
-  match content_result {
-    Ok(content) => Ok((path, content)),
+  let contents = tokio::task::spawn_blocking(move || match format {
+    Format::Html { with_contents } => {
+      Ok(export::html(&source, &name, directory.as_deref(), with_contents).into_bytes())
+    }
+    Format::Pdf(settings) => pdf::render(&source, &name, directory.as_deref(), settings),
+  })
+  .await
+  .map_err(|e| e.to_string())??;
+
+  fs::write(&path, contents)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(path)
+}
+
+pub async fn load_file(path: PathBuf) -> Result<(PathBuf, String, Properties), String> {
+  let bytes_result = fs::read(&path).await.map_err(|e| e.to_string());
+
+  match bytes_result {
+    Ok(bytes) => {
+      let lookup = path.clone();
+      let properties = tokio::task::spawn_blocking(move || editorconfig::resolve(&lookup))
+        .await
+        .map_err(|e| e.to_string())?;
+      let content = editorconfig::decode(bytes, properties.charset)?;
+
+      Ok((path, content, properties))
+    }
     Err(e) => Err(e),
   }
 }
+
+pub async fn save_config(file: &'static str, contents: String) -> Result<(), String> {
+  let path = config_path(file).ok_or(String::from("No configuration directory"))?;
+
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  fs::write(&path, contents).await.map_err(|e| e.to_string())
+}
@@ -1,17 +1,37 @@
+use iced::keyboard::key::Named;
 use iced::keyboard::{self, Key, Modifiers};
+use iced::widget::text_editor::{Binding, KeyPress};
 
-use crate::message::{FileAction, Message, ViewAction};
+use crate::message::{
+  CompletionAction, EditAction, FileAction, LineAction, MacroAction, Message, SpellingAction,
+  ViewAction,
+};
+
+pub enum KeyCode {
+  Character(&'static str),
+  Named(Named),
+}
 
 pub struct Keybinding {
-  key: &'static str,
+  key: KeyCode,
   modifiers: Modifiers,
   message: Message,
 }
 
 impl Keybinding {
   pub fn should_handle(&self, key_pressed: &Key, modifiers: &Modifiers) -> bool {
-    let key = keyboard::Key::Character(self.key.into());
-    modifiers.contains(self.modifiers) && key_pressed == &key
+    let key = match self.key {
+      KeyCode::Character(c) => keyboard::Key::Character(c.into()),
+      KeyCode::Named(named) => keyboard::Key::Named(named),
+    };
+
+    let is_matching = if self.modifiers.is_empty() {
+      modifiers.is_empty()
+    } else {
+      modifiers.contains(self.modifiers)
+    };
+
+    is_matching && key_pressed == &key
   }
 
   pub fn message(&self) -> Message {
@@ -21,53 +41,339 @@ impl Keybinding {
 
 pub const ALL: &[Keybinding] = &[
   Keybinding {
-    key: "o",
+    key: KeyCode::Character("o"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::ViewActionSelected(ViewAction::ToggleOutline),
+  },
+  Keybinding {
+    key: KeyCode::Character("o"),
     modifiers: Modifiers::CTRL,
     message: Message::FileActionSelected(FileAction::Open),
   },
   Keybinding {
-    key: "n",
+    key: KeyCode::Character("n"),
     modifiers: Modifiers::CTRL,
     message: Message::FileActionSelected(FileAction::New),
   },
   Keybinding {
-    key: "s",
+    key: KeyCode::Character("s"),
     modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
     message: Message::FileActionSelected(FileAction::SaveAs),
   },
   Keybinding {
-    key: "s",
+    key: KeyCode::Character("s"),
     modifiers: Modifiers::CTRL,
     message: Message::FileActionSelected(FileAction::Save),
   },
   Keybinding {
-    key: "w",
+    key: KeyCode::Character("w"),
     modifiers: Modifiers::CTRL,
     message: Message::FileActionSelected(FileAction::Close(None)),
   },
   Keybinding {
-    key: "p",
+    key: KeyCode::Character("r"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::MacroActionSelected(MacroAction::ToggleRecording),
+  },
+  Keybinding {
+    key: KeyCode::Character("p"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::MacroActionSelected(MacroAction::Play),
+  },
+  Keybinding {
+    key: KeyCode::Character("p"),
     modifiers: Modifiers::CTRL,
     message: Message::ViewActionSelected(ViewAction::TogglePreview),
   },
   Keybinding {
-    key: "=",
+    key: KeyCode::Character("="),
     modifiers: Modifiers::CTRL,
     message: Message::ViewActionSelected(ViewAction::Increase),
   },
   Keybinding {
-    key: "-",
+    key: KeyCode::Character("-"),
     modifiers: Modifiers::CTRL,
     message: Message::ViewActionSelected(ViewAction::Decrease),
   },
   Keybinding {
-    key: "0",
+    key: KeyCode::Character("1"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(1)),
+  },
+  Keybinding {
+    key: KeyCode::Character("2"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(2)),
+  },
+  Keybinding {
+    key: KeyCode::Character("3"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(3)),
+  },
+  Keybinding {
+    key: KeyCode::Character("4"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(4)),
+  },
+  Keybinding {
+    key: KeyCode::Character("5"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(5)),
+  },
+  Keybinding {
+    key: KeyCode::Character("6"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(6)),
+  },
+  Keybinding {
+    key: KeyCode::Character("7"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(7)),
+  },
+  Keybinding {
+    key: KeyCode::Character("8"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(8)),
+  },
+  Keybinding {
+    key: KeyCode::Character("9"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::MacroActionSelected(MacroAction::PlaySaved(9)),
+  },
+  Keybinding {
+    key: KeyCode::Character("0"),
     modifiers: Modifiers::CTRL,
     message: Message::ViewActionSelected(ViewAction::Reset),
   },
   Keybinding {
-    key: "z",
+    key: KeyCode::Character("z"),
     modifiers: Modifiers::ALT,
     message: Message::ViewActionSelected(ViewAction::ToggleWordWrap),
   },
+  Keybinding {
+    key: KeyCode::Character("z"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::EditActionSelected(EditAction::Redo),
+  },
+  Keybinding {
+    key: KeyCode::Character("z"),
+    modifiers: Modifiers::CTRL,
+    message: Message::EditActionSelected(EditAction::Undo),
+  },
+  Keybinding {
+    key: KeyCode::Character("y"),
+    modifiers: Modifiers::CTRL,
+    message: Message::EditActionSelected(EditAction::Redo),
+  },
+  Keybinding {
+    key: KeyCode::Character("d"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::LineActionSelected(LineAction::Duplicate),
+  },
+  Keybinding {
+    key: KeyCode::Character("k"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::LineActionSelected(LineAction::Delete),
+  },
+  Keybinding {
+    key: KeyCode::Character("j"),
+    modifiers: Modifiers::CTRL,
+    message: Message::LineActionSelected(LineAction::Join),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::F9),
+    modifiers: Modifiers::empty(),
+    message: Message::LineActionSelected(LineAction::SortAscending),
+  },
+  Keybinding {
+    key: KeyCode::Character("d"),
+    modifiers: Modifiers::CTRL,
+    message: Message::EditActionSelected(EditAction::AddNextOccurrence),
+  },
+  Keybinding {
+    key: KeyCode::Character("/"),
+    modifiers: Modifiers::CTRL,
+    message: Message::EditActionSelected(EditAction::ToggleLineComment),
+  },
+  Keybinding {
+    key: KeyCode::Character("a"),
+    modifiers: Modifiers::SHIFT.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::ToggleBlockComment),
+  },
+  Keybinding {
+    key: KeyCode::Character("\\"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::EditActionSelected(EditAction::JumpToBracket),
+  },
+  Keybinding {
+    key: KeyCode::Character("\\"),
+    modifiers: Modifiers::CTRL,
+    message: Message::ViewActionSelected(ViewAction::ToggleSplit),
+  },
+  Keybinding {
+    key: KeyCode::Character("."),
+    modifiers: Modifiers::CTRL,
+    message: Message::Spelling(SpellingAction::Open),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::Space),
+    modifiers: Modifiers::CTRL,
+    message: Message::EditActionSelected(EditAction::TriggerCompletion),
+  },
+  Keybinding {
+    key: KeyCode::Character("["),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::ViewActionSelected(ViewAction::Fold),
+  },
+  Keybinding {
+    key: KeyCode::Character("]"),
+    modifiers: Modifiers::CTRL.union(Modifiers::SHIFT),
+    message: Message::ViewActionSelected(ViewAction::Unfold),
+  },
+  Keybinding {
+    key: KeyCode::Character("["),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::ViewActionSelected(ViewAction::FoldLevel(1)),
+  },
+  Keybinding {
+    key: KeyCode::Character("]"),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::ViewActionSelected(ViewAction::UnfoldAll),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowUp),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::AddCursorAbove),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowDown),
+    modifiers: Modifiers::CTRL.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::AddCursorBelow),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowUp),
+    modifiers: Modifiers::SHIFT.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::ColumnSelectUp),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowDown),
+    modifiers: Modifiers::SHIFT.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::ColumnSelectDown),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowLeft),
+    modifiers: Modifiers::SHIFT.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::ColumnSelectLeft),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowRight),
+    modifiers: Modifiers::SHIFT.union(Modifiers::ALT),
+    message: Message::EditActionSelected(EditAction::ColumnSelectRight),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowUp),
+    modifiers: Modifiers::ALT,
+    message: Message::LineActionSelected(LineAction::MoveUp),
+  },
+  Keybinding {
+    key: KeyCode::Named(Named::ArrowDown),
+    modifiers: Modifiers::ALT,
+    message: Message::LineActionSelected(LineAction::MoveDown),
+  },
 ];
+
+pub fn editor_binding(
+  key_press: KeyPress,
+  has_many_cursors: bool,
+  is_completing: bool,
+  has_spelling_menu: bool,
+) -> Option<Binding<Message>> {
+  if has_spelling_menu
+    && key_press.modifiers.is_empty()
+    && key_press.key.as_ref() == Key::Named(Named::Escape)
+  {
+    return Some(Binding::Custom(Message::Spelling(SpellingAction::Dismiss)));
+  }
+
+  if is_completing && key_press.modifiers.is_empty() {
+    let action = match key_press.key.as_ref() {
+      Key::Named(Named::Enter) => Some(CompletionAction::Accept),
+      Key::Named(Named::ArrowDown) => Some(CompletionAction::Next),
+      Key::Named(Named::ArrowUp) => Some(CompletionAction::Previous),
+      Key::Named(Named::Escape) => Some(CompletionAction::Dismiss),
+      _ => None,
+    };
+
+    if let Some(action) = action {
+      return Some(Binding::Custom(Message::Completion(action)));
+    }
+  }
+
+  if key_press.key.as_ref() == Key::Named(Named::Tab) {
+    let action = if key_press.modifiers.is_empty() {
+      Some(EditAction::Indent)
+    } else if key_press.modifiers == Modifiers::SHIFT {
+      Some(EditAction::Outdent)
+    } else {
+      None
+    };
+
+    if let Some(action) = action {
+      return Some(Binding::Custom(Message::EditActionSelected(action)));
+    }
+  }
+
+  if ALL
+    .iter()
+    .any(|kb| kb.should_handle(&key_press.key, &key_press.modifiers))
+  {
+    return None;
+  }
+
+  let binding = Binding::from_key_press(key_press)?;
+
+  if !has_many_cursors {
+    return Some(binding);
+  }
+
+  let action = match binding {
+    Binding::Copy => EditAction::Copy,
+    Binding::Cut => EditAction::Cut,
+    Binding::Unfocus => EditAction::ClearCursors,
+    _ => return Some(binding),
+  };
+
+  Some(Binding::Custom(Message::EditActionSelected(action)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn handled(key: Key, modifiers: Modifiers) -> Option<Message> {
+    ALL
+      .iter()
+      .find(|binding| binding.should_handle(&key, &modifiers))
+      .map(Keybinding::message)
+  }
+
+  #[test]
+  fn matches_unmodified_keys_exactly() {
+    let f9 = Key::Named(Named::F9);
+
+    assert!(matches!(
+      handled(f9.clone(), Modifiers::empty()),
+      Some(Message::LineActionSelected(LineAction::SortAscending))
+    ));
+    assert!(handled(f9.clone(), Modifiers::SHIFT).is_none());
+    assert!(handled(f9, Modifiers::CTRL).is_none());
+  }
+
+  #[test]
+  fn matches_modified_bindings() {
+    assert!(matches!(
+      handled(Key::Character("d".into()), Modifiers::CTRL),
+      Some(Message::EditActionSelected(EditAction::AddNextOccurrence))
+    ));
+  }
+}
@@ -0,0 +1,126 @@
+use std::cmp::Ordering;
+
+use crate::message::LineAction;
+
+pub fn transform(action: LineAction, lines: Vec<String>) -> Vec<String> {
+  match action {
+    LineAction::Duplicate => lines.iter().chain(lines.iter()).cloned().collect(),
+    LineAction::Delete => Vec::new(),
+    LineAction::MoveUp => rotate(lines, 1),
+    LineAction::MoveDown => rotate(lines, -1),
+    LineAction::Join => vec![join(&lines)],
+    LineAction::SortAscending => sorted(lines, |a, b| a.cmp(b)),
+    LineAction::SortDescending => sorted(lines, |a, b| b.cmp(a)),
+    LineAction::SortNatural => sorted(lines, natural_cmp),
+    LineAction::SortCaseInsensitive => sorted(lines, |a, b| {
+      a.to_lowercase()
+        .cmp(&b.to_lowercase())
+        .then_with(|| a.cmp(b))
+    }),
+    LineAction::RemoveDuplicates => {
+      let mut seen = std::collections::HashSet::new();
+      lines
+        .into_iter()
+        .filter(|line| seen.insert(line.clone()))
+        .collect()
+    }
+    LineAction::Reverse => lines.into_iter().rev().collect(),
+    LineAction::RemoveBlank => lines
+      .into_iter()
+      .filter(|line| !line.trim().is_empty())
+      .collect(),
+  }
+}
+
+fn rotate(mut lines: Vec<String>, by: isize) -> Vec<String> {
+  if lines.is_empty() {
+    return lines;
+  }
+
+  if by > 0 {
+    lines.rotate_left(by as usize);
+  } else {
+    lines.rotate_right(by.unsigned_abs());
+  }
+
+  lines
+}
+
+fn join(lines: &[String]) -> String {
+  let mut joined = String::new();
+
+  for (index, line) in lines.iter().enumerate() {
+    if index == 0 {
+      joined.push_str(line.trim_end());
+      continue;
+    }
+
+    let line = line.trim();
+
+    if !line.is_empty() {
+      if !joined.is_empty() {
+        joined.push(' ');
+      }
+
+      joined.push_str(line);
+    }
+  }
+
+  joined
+}
+
+fn sorted(mut lines: Vec<String>, compare: impl Fn(&str, &str) -> Ordering) -> Vec<String> {
+  lines.sort_by(|a, b| compare(a, b));
+  lines
+}
+
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+  let mut a = a.chars().peekable();
+  let mut b = b.chars().peekable();
+
+  loop {
+    match (a.peek().copied(), b.peek().copied()) {
+      (None, None) => return Ordering::Equal,
+      (None, Some(_)) => return Ordering::Less,
+      (Some(_), None) => return Ordering::Greater,
+      (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+        let x = take_number(&mut a);
+        let y = take_number(&mut b);
+
+        let ordering = x
+          .trim_start_matches('0')
+          .len()
+          .cmp(&y.trim_start_matches('0').len())
+          .then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')))
+          .then_with(|| x.len().cmp(&y.len()));
+
+        if ordering != Ordering::Equal {
+          return ordering;
+        }
+      }
+      (Some(x), Some(y)) => {
+        let ordering = x
+          .to_lowercase()
+          .cmp(y.to_lowercase())
+          .then_with(|| x.cmp(&y));
+
+        if ordering != Ordering::Equal {
+          return ordering;
+        }
+
+        a.next();
+        b.next();
+      }
+    }
+  }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+  let mut number = String::new();
+
+  while let Some(c) = chars.next_if(char::is_ascii_digit) {
+    number.push(c);
+  }
+
+  number
+}
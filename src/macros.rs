@@ -0,0 +1,271 @@
+use std::fmt::Debug;
+use std::fs;
+use std::sync::Arc;
+
+use iced::widget::text_editor::{Action, Edit, Motion};
+
+use crate::io;
+use crate::message::{
+  CompletionAction, EditAction, LineAction, Message, TableAction, TransformAction,
+};
+use crate::transform;
+
+pub const CONFIG_FILE: &str = "macros.conf";
+pub const SLOTS: usize = 9;
+pub const MAX_REPETITIONS: usize = 10_000;
+
+const MOTIONS: &[Motion] = &[
+  Motion::Left,
+  Motion::Right,
+  Motion::Up,
+  Motion::Down,
+  Motion::WordLeft,
+  Motion::WordRight,
+  Motion::Home,
+  Motion::End,
+  Motion::PageUp,
+  Motion::PageDown,
+  Motion::DocumentStart,
+  Motion::DocumentEnd,
+];
+
+const COMPLETION_ACTIONS: &[CompletionAction] = &[
+  CompletionAction::Next,
+  CompletionAction::Previous,
+  CompletionAction::Accept,
+  CompletionAction::Dismiss,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+  Times(usize),
+  ToEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+  Repeat,
+  Name,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+  pub kind: PromptKind,
+  pub value: String,
+}
+
+impl Prompt {
+  pub fn new(kind: PromptKind) -> Self {
+    Prompt {
+      kind,
+      value: String::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Macro {
+  pub name: String,
+  pub slot: usize,
+  pub steps: Vec<Message>,
+}
+
+pub fn is_recordable(message: &Message) -> bool {
+  match message {
+    Message::Edit(action) => matches!(
+      action,
+      Action::Move(_)
+        | Action::Select(_)
+        | Action::SelectWord
+        | Action::SelectLine
+        | Action::SelectAll
+        | Action::Edit(_)
+    ),
+    Message::EditActionSelected(action) => !matches!(action, EditAction::Undo | EditAction::Redo),
+    Message::LineActionSelected(_)
+    | Message::TransformActionSelected(_)
+    | Message::TableActionSelected(_) => true,
+    Message::Completion(action) => COMPLETION_ACTIONS.contains(action),
+    _ => false,
+  }
+}
+
+pub fn load() -> Vec<Macro> {
+  io::config_path(CONFIG_FILE)
+    .and_then(|path| fs::read_to_string(path).ok())
+    .map(|contents| parse(&contents))
+    .unwrap_or_default()
+}
+
+pub fn free_slot(macros: &[Macro]) -> Option<usize> {
+  (1..=SLOTS).find(|slot| macros.iter().all(|other| other.slot != *slot))
+}
+
+fn parse(contents: &str) -> Vec<Macro> {
+  let mut macros = Vec::new();
+  let mut current: Option<Macro> = None;
+
+  for line in contents.lines() {
+    if let Some(mut recording) = current.take() {
+      if line == "endmacro" {
+        macros.push(recording);
+      } else {
+        recording.steps.extend(decode(line));
+        current = Some(recording);
+      }
+
+      continue;
+    }
+
+    let Some(header) = line.strip_prefix("macro ") else {
+      continue;
+    };
+
+    let (slot, name) = header.split_once(' ').unwrap_or((header, ""));
+
+    if let Ok(slot) = slot.parse()
+      && (1..=SLOTS).contains(&slot)
+    {
+      current = Some(Macro {
+        name: name.trim().to_owned(),
+        slot,
+        steps: Vec::new(),
+      });
+    }
+  }
+
+  macros
+}
+
+pub fn serialize(macros: &[Macro]) -> String {
+  let mut contents = String::new();
+
+  for recording in macros {
+    contents.push_str(&format!("macro {} {}\n", recording.slot, recording.name));
+
+    for step in recording.steps.iter().filter_map(encode) {
+      contents.push_str(&step);
+      contents.push('\n');
+    }
+
+    contents.push_str("endmacro\n");
+  }
+
+  contents
+}
+
+fn encode(message: &Message) -> Option<String> {
+  let encoded = match message {
+    Message::Edit(Action::Move(motion)) => format!("move {motion:?}"),
+    Message::Edit(Action::Select(motion)) => format!("select {motion:?}"),
+    Message::Edit(Action::SelectWord) => "select-word".to_owned(),
+    Message::Edit(Action::SelectLine) => "select-line".to_owned(),
+    Message::Edit(Action::SelectAll) => "select-all".to_owned(),
+    Message::Edit(Action::Edit(edit)) => match edit {
+      Edit::Insert(c) => format!("insert {}", transform::json_escape(&c.to_string())),
+      Edit::Paste(text) => format!("paste {}", transform::json_escape(text)),
+      Edit::Enter => "enter".to_owned(),
+      Edit::Indent => "indent".to_owned(),
+      Edit::Unindent => "unindent".to_owned(),
+      Edit::Backspace => "backspace".to_owned(),
+      Edit::Delete => "delete".to_owned(),
+    },
+    Message::EditActionSelected(action) => format!("edit {action:?}"),
+    Message::LineActionSelected(action) => format!("line {action:?}"),
+    Message::TransformActionSelected(action) => format!("transform {action:?}"),
+    Message::TableActionSelected(action) => format!("table {action:?}"),
+    Message::Completion(action) => format!("completion {action:?}"),
+    _ => return None,
+  };
+
+  Some(encoded)
+}
+
+fn decode(line: &str) -> Option<Message> {
+  let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+
+  let message = match command {
+    "move" => Message::Edit(Action::Move(find(MOTIONS, argument)?)),
+    "select" => Message::Edit(Action::Select(find(MOTIONS, argument)?)),
+    "select-word" => Message::Edit(Action::SelectWord),
+    "select-line" => Message::Edit(Action::SelectLine),
+    "select-all" => Message::Edit(Action::SelectAll),
+    "insert" => Message::Edit(Action::Edit(Edit::Insert(
+      transform::json_unescape(argument)?.chars().next()?,
+    ))),
+    "paste" => Message::Edit(Action::Edit(Edit::Paste(Arc::new(
+      transform::json_unescape(argument)?,
+    )))),
+    "enter" => Message::Edit(Action::Edit(Edit::Enter)),
+    "indent" => Message::Edit(Action::Edit(Edit::Indent)),
+    "unindent" => Message::Edit(Action::Edit(Edit::Unindent)),
+    "backspace" => Message::Edit(Action::Edit(Edit::Backspace)),
+    "delete" => Message::Edit(Action::Edit(Edit::Delete)),
+    "edit" => Message::EditActionSelected(find(EditAction::ALL, argument)?),
+    "line" => Message::LineActionSelected(find(LineAction::ALL, argument)?),
+    "transform" => Message::TransformActionSelected(find(TransformAction::ALL, argument)?),
+    "table" => Message::TableActionSelected(find(TableAction::ALL, argument)?),
+    "completion" => Message::Completion(find(COMPLETION_ACTIONS, argument)?),
+    _ => return None,
+  };
+
+  Some(message)
+}
+
+fn find<T: Debug + Copy>(all: &[T], name: &str) -> Option<T> {
+  all.iter().copied().find(|item| format!("{item:?}") == name)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_saved_macros() {
+    let macros = vec![Macro {
+      name: String::from("quote lines"),
+      slot: 2,
+      steps: vec![
+        Message::Edit(Action::Move(Motion::Home)),
+        Message::Edit(Action::Edit(Edit::Insert('>'))),
+        Message::Edit(Action::Edit(Edit::Paste(Arc::new(String::from(
+          " \"a\"\n",
+        ))))),
+        Message::Edit(Action::Select(Motion::WordRight)),
+        Message::Edit(Action::Edit(Edit::Enter)),
+      ],
+    }];
+
+    let contents = serialize(&macros);
+    let parsed = parse(&contents);
+
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].name, "quote lines");
+    assert_eq!(parsed[0].slot, 2);
+    assert_eq!(parsed[0].steps.len(), 5);
+    assert_eq!(serialize(&parsed), contents);
+  }
+
+  #[test]
+  fn skips_unknown_steps_and_invalid_slots() {
+    let contents = "macro 0 zero\nenter\nendmacro\n\
+      macro 3 three\nmove Nowhere\nfly away\nbackspace\nendmacro\n";
+    let parsed = parse(contents);
+
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].slot, 3);
+    assert_eq!(parsed[0].steps.len(), 1);
+  }
+
+  #[test]
+  fn finds_the_first_free_slot() {
+    let taken = |slot| Macro {
+      name: String::new(),
+      slot,
+      steps: Vec::new(),
+    };
+
+    assert_eq!(free_slot(&[taken(1), taken(3)]), Some(2));
+    assert_eq!(free_slot(&(1..=SLOTS).map(taken).collect::<Vec<_>>()), None);
+  }
+}
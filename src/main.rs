@@ -1,26 +1,62 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod blocks;
+mod brackets;
+mod comments;
+mod completion;
 mod components;
 mod constants;
+mod cursors;
+mod editorconfig;
+mod export;
 mod file;
+mod folding;
+mod front_matter;
 mod handler;
+mod highlighter;
+mod history;
+mod images;
+mod indent;
 mod io;
 mod key_bindings;
+mod lines;
+mod macros;
 mod message;
+mod outline;
+mod pdf;
+mod snippets;
+mod spelling;
 mod state;
+mod subset;
+mod table;
+mod transform;
+mod whitespace;
+mod wrapping;
 
 use iced::theme::Palette;
-use iced::widget::column;
+use iced::widget::{column, row};
 use iced::window::icon;
 use iced::{Color, Theme};
 use iced::{Element, Subscription, Task, event};
 use iced::{keyboard, window};
 
-use crate::message::Message;
+use crate::message::{Message, ScrollSync};
 use crate::state::State;
 
 pub fn main() -> iced::Result {
+  let args: Vec<String> = std::env::args().collect();
+
+  if let Some(result) = export::run(&args) {
+    if let Err(error) = result {
+      eprintln!("{error}");
+      std::process::exit(1);
+    }
+
+    return Ok(());
+  }
+
   iced::daemon(boot, update, view)
+    .title(title)
     .subscription(subscription)
     .font(constants::CUSTOM_FONT_BYTES)
     .theme(theme)
@@ -38,7 +74,13 @@ fn boot() -> (State, Task<Message>) {
   });
 
   let mut state = state::State::new(id);
-  let mut tasks = vec![open.map(Message::WindowOpened)];
+  let mut tasks = vec![
+    open.map(Message::WindowOpened),
+    Task::perform(
+      spelling::installed_in_background(),
+      Message::DictionariesFound,
+    ),
+  ];
 
   let args: Vec<String> = std::env::args().collect();
 
@@ -56,14 +98,75 @@ fn boot() -> (State, Task<Message>) {
 }
 
 fn update(state: &mut State, message: Message) -> Task<Message> {
+  state.record(&message);
+
+  let task = handle(state, message);
+
+  state.update_outline();
+  state.update_fold_regions();
+  state.update_front_matter();
+  Task::batch([
+    task,
+    handler::schedule_spelling(state),
+    handler::schedule_preview(state),
+    handler::load_images(state),
+  ])
+}
+
+fn handle(state: &mut State, message: Message) -> Task<Message> {
+  if message.interrupts_input() {
+    state.interrupt_input();
+  }
+
   match message {
     Message::Edit(action) => handler::edit(state, action),
+    Message::ModifiersChanged(modifiers) => handler::modifiers_changed(state, modifiers),
+    Message::GutterMoved(target) => handler::gutter_moved(state, target),
+    Message::GutterPressed => handler::gutter_pressed(state),
+    Message::Completion(action) => handler::completion(state, action),
+    Message::SnippetSelected(snippet) => handler::snippet_selected(state, snippet),
+    Message::MacroActionSelected(action) => handler::macro_action(state, action),
+    Message::MacroPromptChanged(value) => handler::macro_prompt_changed(state, value),
+    Message::MacroPromptSubmitted => handler::macro_prompt_submitted(state),
+    Message::MacroPromptCancelled => handler::macro_prompt_cancelled(state),
+    Message::MarginPromptChanged(value) => handler::margin_prompt_changed(state, value),
+    Message::MarginPromptSubmitted => handler::margin_prompt_submitted(state),
+    Message::MarginPromptCancelled => handler::margin_prompt_cancelled(state),
+    Message::Spelling(action) => handler::spelling(state, action),
+    Message::Outline(action) => handler::outline(state, action),
+    Message::SpellLanguageSelected(language) => handler::spell_language_selected(state, language),
+    Message::DictionaryLoaded(loaded) => handler::dictionary_loaded(state, loaded),
+    Message::DictionariesFound(installed) => handler::dictionaries_found(state, installed),
+    Message::SpellingDebounced(revision) => handler::check_spelling(state, revision),
+    Message::EditorPointerMoved(pointer) => handler::editor_pointer_moved(state, pointer),
+    Message::ConfigSaved(result) => handler::config_saved(result),
+    Message::PreviewDebounced(revision) => handler::preview_debounced(state, revision),
+    Message::MarkdownParsed(result) => handler::markdown_parsed(state, result),
+    Message::SplitDividerPressed => handler::split_divider_pressed(state),
+    Message::SplitDividerMoved(ratio) => handler::split_divider_moved(state, ratio),
+    Message::SplitDividerReleased => handler::split_divider_released(state),
+    Message::PaneHovered(pane) => handler::pane_hovered(state, pane),
+    Message::EditorScrolled(viewport) => handler::editor_scrolled(state, viewport),
+    Message::PreviewScrolled => handler::sync_scroll(state, ScrollSync::Preview),
+    Message::PreviewBlockClicked(index) => handler::preview_block_clicked(state, index),
+    Message::PreviewMeasured(sync, layout) => handler::preview_measured(state, sync, layout),
     Message::SwitchTab(index) => handler::switch_tab(state, index),
     Message::LinkClicked(url) => handler::link_clicked(url),
+    Message::ImageLoaded(loaded) => handler::image_loaded(state, loaded),
+    Message::ImagesPolled => handler::images_polled(state),
+    Message::ImagesChanged(paths) => handler::images_changed(state, paths),
+    Message::RemoteImagesAllowed => handler::remote_images_allowed(state),
+    Message::TaskToggled(line, done) => handler::task_toggled(state, line, done),
     Message::FileActionSelected(action) => handler::file_action(state, action),
+    Message::EditActionSelected(action) => handler::edit_action(state, action),
+    Message::LineActionSelected(action) => handler::line_action(state, action),
+    Message::TransformActionSelected(action) => handler::transform_action(state, action),
+    Message::TableActionSelected(action) => handler::table_action(state, action),
     Message::ViewActionSelected(action) => handler::view_action(state, action),
     Message::FileOpened(result) => handler::opened_file(state, result),
+    Message::SavePathPicked(result) => handler::save_path_picked(state, result),
     Message::FileSaved(result) => handler::saved_file(state, result),
+    Message::FileExported(result) => handler::exported_file(state, result),
     Message::WindowOpened(id) => {
       state.set_window_id(id);
       Task::none()
@@ -83,22 +186,57 @@ fn view(state: &State, _id: iced::window::Id) -> Element<'_, Message> {
 
   column![
     components::tabs::view(state.files(), state.current_file_index()),
-    components::action_bar::view(state.selected_file_action(), state.selected_view_action()),
-    components::editor::view(
+    components::action_bar::view(
+      state.selected_file_action(),
+      state.selected_edit_action(),
+      state.selected_line_action(),
+      state.selected_transform_action(),
+      state.selected_view_action(),
+      state.snippets().to_vec(),
+      state.macro_items()
+    ),
+    row![
+      state
+        .outline()
+        .is_visible()
+        .then(|| components::outline::view(current_file, state.outline())),
+      components::editor::view(
+        current_file,
+        state.mode(),
+        state.editor_settings(),
+        state.popup(),
+        state.images()
+      )
+    ],
+    state.macro_prompt().map(components::macro_prompt::view),
+    state.margin_prompt().map(components::margin_prompt::view),
+    components::status_bar::view(
       current_file,
-      state.mode(),
-      state.font_size(),
-      state.is_word_wrap_on()
+      state.is_recording(),
+      state.notice(),
+      state.spell_languages()
     ),
-    components::status_bar::view(current_file),
   ]
   .into()
 }
 
-fn subscription(_state: &State) -> Subscription<Message> {
-  event::listen_with(|e, _status, win| -> Option<Message> {
+fn title(state: &State, _id: iced::window::Id) -> String {
+  format!("{} - zoeae", state.active_file().title())
+}
+
+fn subscription(state: &State) -> Subscription<Message> {
+  let images = if state.is_watching_images() {
+    iced::time::every(constants::IMAGE_POLL_INTERVAL).map(|_| Message::ImagesPolled)
+  } else {
+    Subscription::none()
+  };
+
+  let events = event::listen_with(|e, _status, win| -> Option<Message> {
     match e {
       iced::Event::Window(window::Event::Closed) => Some(Message::WindowClosed(win)),
+      iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+        Some(Message::ModifiersChanged(modifiers))
+      }
       iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
         for vb in key_bindings::ALL {
           if vb.should_handle(&key, &modifiers) {
@@ -110,7 +248,9 @@ fn subscription(_state: &State) -> Subscription<Message> {
       }
       _ => None,
     }
-  })
+  });
+
+  Subscription::batch([events, images])
 }
 
 fn theme(_state: &State, _id: iced::window::Id) -> Theme {
@@ -1,6 +1,16 @@
 use std::{fmt::Display, path::PathBuf};
 
-use iced::{widget::text_editor, window};
+use iced::widget::{scrollable, text_editor};
+use iced::{keyboard, window};
+
+use crate::blocks::Parsed;
+use crate::components::preview::Layout;
+use crate::editorconfig::Properties;
+use crate::images;
+use crate::outline::Depth;
+use crate::pdf::PageSize;
+use crate::snippets::Snippet;
+use crate::spelling::{self, Language};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileAction {
@@ -9,6 +19,12 @@ pub enum FileAction {
   SaveAs,
   Open,
   Close(Option<usize>),
+  ToggleTrimTrailingWhitespace,
+  ToggleInsertFinalNewline,
+  ToggleTrimFinalNewlines,
+  ExportHtml,
+  ExportHtmlWithContents,
+  ExportPdf(PageSize),
 }
 
 impl FileAction {
@@ -18,6 +34,13 @@ impl FileAction {
     FileAction::SaveAs,
     FileAction::Open,
     FileAction::Close(None),
+    FileAction::ToggleTrimTrailingWhitespace,
+    FileAction::ToggleInsertFinalNewline,
+    FileAction::ToggleTrimFinalNewlines,
+    FileAction::ExportHtml,
+    FileAction::ExportHtmlWithContents,
+    FileAction::ExportPdf(PageSize::A4),
+    FileAction::ExportPdf(PageSize::Letter),
   ];
 }
 
@@ -29,6 +52,283 @@ impl Display for FileAction {
       FileAction::SaveAs => write!(f, "Save as... "),
       FileAction::Open => write!(f, "Open"),
       FileAction::Close(_) => write!(f, "Close"),
+      FileAction::ToggleTrimTrailingWhitespace => {
+        write!(f, "Toggle trim trailing whitespace on save")
+      }
+      FileAction::ToggleInsertFinalNewline => write!(f, "Toggle insert final newline on save"),
+      FileAction::ToggleTrimFinalNewlines => write!(f, "Toggle trim final newlines on save"),
+      FileAction::ExportHtml => write!(f, "Export → HTML"),
+      FileAction::ExportHtmlWithContents => write!(f, "Export → HTML with table of contents"),
+      FileAction::ExportPdf(page_size) => write!(f, "Export → PDF ({page_size})"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditAction {
+  Copy,
+  Cut,
+  AddCursorAbove,
+  AddCursorBelow,
+  AddNextOccurrence,
+  ClearCursors,
+  Undo,
+  Redo,
+  Indent,
+  Outdent,
+  IndentUsingSpaces,
+  IndentUsingTabs,
+  JumpToBracket,
+  ToggleLineComment,
+  ToggleBlockComment,
+  ColumnSelectUp,
+  ColumnSelectDown,
+  ColumnSelectLeft,
+  ColumnSelectRight,
+  TriggerCompletion,
+}
+
+impl EditAction {
+  pub const ALL: &'static [EditAction] = &[
+    EditAction::Copy,
+    EditAction::Cut,
+    EditAction::AddCursorAbove,
+    EditAction::AddCursorBelow,
+    EditAction::AddNextOccurrence,
+    EditAction::ClearCursors,
+    EditAction::Undo,
+    EditAction::Redo,
+    EditAction::Indent,
+    EditAction::Outdent,
+    EditAction::IndentUsingSpaces,
+    EditAction::IndentUsingTabs,
+    EditAction::JumpToBracket,
+    EditAction::ToggleLineComment,
+    EditAction::ToggleBlockComment,
+    EditAction::ColumnSelectUp,
+    EditAction::ColumnSelectDown,
+    EditAction::ColumnSelectLeft,
+    EditAction::ColumnSelectRight,
+    EditAction::TriggerCompletion,
+  ];
+}
+
+impl Display for EditAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      EditAction::Copy => write!(f, "Copy"),
+      EditAction::Cut => write!(f, "Cut"),
+      EditAction::AddCursorAbove => write!(f, "Add cursor above"),
+      EditAction::AddCursorBelow => write!(f, "Add cursor below"),
+      EditAction::AddNextOccurrence => write!(f, "Add next occurrence"),
+      EditAction::ClearCursors => write!(f, "Clear extra cursors"),
+      EditAction::Undo => write!(f, "Undo"),
+      EditAction::Redo => write!(f, "Redo"),
+      EditAction::Indent => write!(f, "Indent"),
+      EditAction::Outdent => write!(f, "Outdent"),
+      EditAction::IndentUsingSpaces => write!(f, "Convert indentation to spaces"),
+      EditAction::IndentUsingTabs => write!(f, "Convert indentation to tabs"),
+      EditAction::JumpToBracket => write!(f, "Jump to matching bracket"),
+      EditAction::ToggleLineComment => write!(f, "Toggle line comment"),
+      EditAction::ToggleBlockComment => write!(f, "Toggle block comment"),
+      EditAction::ColumnSelectUp => write!(f, "Column select up"),
+      EditAction::ColumnSelectDown => write!(f, "Column select down"),
+      EditAction::ColumnSelectLeft => write!(f, "Column select left"),
+      EditAction::ColumnSelectRight => write!(f, "Column select right"),
+      EditAction::TriggerCompletion => write!(f, "Suggest completions"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineAction {
+  Duplicate,
+  Delete,
+  MoveUp,
+  MoveDown,
+  Join,
+  SortAscending,
+  SortDescending,
+  SortNatural,
+  SortCaseInsensitive,
+  RemoveDuplicates,
+  Reverse,
+  RemoveBlank,
+}
+
+impl LineAction {
+  pub const ALL: &'static [LineAction] = &[
+    LineAction::Duplicate,
+    LineAction::Delete,
+    LineAction::MoveUp,
+    LineAction::MoveDown,
+    LineAction::Join,
+    LineAction::SortAscending,
+    LineAction::SortDescending,
+    LineAction::SortNatural,
+    LineAction::SortCaseInsensitive,
+    LineAction::RemoveDuplicates,
+    LineAction::Reverse,
+    LineAction::RemoveBlank,
+  ];
+
+  pub fn spans_document(&self) -> bool {
+    matches!(
+      self,
+      LineAction::SortAscending
+        | LineAction::SortDescending
+        | LineAction::SortNatural
+        | LineAction::SortCaseInsensitive
+        | LineAction::RemoveDuplicates
+        | LineAction::Reverse
+        | LineAction::RemoveBlank
+    )
+  }
+}
+
+impl Display for LineAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      LineAction::Duplicate => write!(f, "Duplicate line"),
+      LineAction::Delete => write!(f, "Delete line"),
+      LineAction::MoveUp => write!(f, "Move line up"),
+      LineAction::MoveDown => write!(f, "Move line down"),
+      LineAction::Join => write!(f, "Join lines"),
+      LineAction::SortAscending => write!(f, "Sort ascending"),
+      LineAction::SortDescending => write!(f, "Sort descending"),
+      LineAction::SortNatural => write!(f, "Sort natural"),
+      LineAction::SortCaseInsensitive => write!(f, "Sort case-insensitive"),
+      LineAction::RemoveDuplicates => write!(f, "Remove duplicate lines"),
+      LineAction::Reverse => write!(f, "Reverse lines"),
+      LineAction::RemoveBlank => write!(f, "Remove blank lines"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformAction {
+  Upper,
+  Lower,
+  Title,
+  Sentence,
+  Snake,
+  Camel,
+  Kebab,
+  Base64Encode,
+  Base64Decode,
+  UrlEncode,
+  UrlDecode,
+  JsonEscape,
+  JsonUnescape,
+  HtmlEncode,
+  HtmlDecode,
+}
+
+impl TransformAction {
+  pub const ALL: &'static [TransformAction] = &[
+    TransformAction::Upper,
+    TransformAction::Lower,
+    TransformAction::Title,
+    TransformAction::Sentence,
+    TransformAction::Snake,
+    TransformAction::Camel,
+    TransformAction::Kebab,
+    TransformAction::Base64Encode,
+    TransformAction::Base64Decode,
+    TransformAction::UrlEncode,
+    TransformAction::UrlDecode,
+    TransformAction::JsonEscape,
+    TransformAction::JsonUnescape,
+    TransformAction::HtmlEncode,
+    TransformAction::HtmlDecode,
+  ];
+}
+
+impl Display for TransformAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TransformAction::Upper => write!(f, "UPPER CASE"),
+      TransformAction::Lower => write!(f, "lower case"),
+      TransformAction::Title => write!(f, "Title Case"),
+      TransformAction::Sentence => write!(f, "Sentence case"),
+      TransformAction::Snake => write!(f, "snake_case"),
+      TransformAction::Camel => write!(f, "camelCase"),
+      TransformAction::Kebab => write!(f, "kebab-case"),
+      TransformAction::Base64Encode => write!(f, "Base64 encode"),
+      TransformAction::Base64Decode => write!(f, "Base64 decode"),
+      TransformAction::UrlEncode => write!(f, "URL encode"),
+      TransformAction::UrlDecode => write!(f, "URL decode"),
+      TransformAction::JsonEscape => write!(f, "JSON escape"),
+      TransformAction::JsonUnescape => write!(f, "JSON unescape"),
+      TransformAction::HtmlEncode => write!(f, "HTML encode"),
+      TransformAction::HtmlDecode => write!(f, "HTML decode"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableAction {
+  Format,
+  NextCell,
+  PreviousCell,
+  InsertRowAbove,
+  InsertRowBelow,
+  DeleteRow,
+  InsertColumnLeft,
+  InsertColumnRight,
+  DeleteColumn,
+  MoveColumnLeft,
+  MoveColumnRight,
+  AlignNone,
+  AlignLeft,
+  AlignCenter,
+  AlignRight,
+  SortAscending,
+  SortDescending,
+}
+
+impl TableAction {
+  pub const ALL: &'static [TableAction] = &[
+    TableAction::Format,
+    TableAction::NextCell,
+    TableAction::PreviousCell,
+    TableAction::InsertRowAbove,
+    TableAction::InsertRowBelow,
+    TableAction::DeleteRow,
+    TableAction::InsertColumnLeft,
+    TableAction::InsertColumnRight,
+    TableAction::DeleteColumn,
+    TableAction::MoveColumnLeft,
+    TableAction::MoveColumnRight,
+    TableAction::AlignNone,
+    TableAction::AlignLeft,
+    TableAction::AlignCenter,
+    TableAction::AlignRight,
+    TableAction::SortAscending,
+    TableAction::SortDescending,
+  ];
+}
+
+impl Display for TableAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TableAction::Format => write!(f, "Format table"),
+      TableAction::NextCell => write!(f, "Next cell"),
+      TableAction::PreviousCell => write!(f, "Previous cell"),
+      TableAction::InsertRowAbove => write!(f, "Insert row above"),
+      TableAction::InsertRowBelow => write!(f, "Insert row below"),
+      TableAction::DeleteRow => write!(f, "Delete row"),
+      TableAction::InsertColumnLeft => write!(f, "Insert column left"),
+      TableAction::InsertColumnRight => write!(f, "Insert column right"),
+      TableAction::DeleteColumn => write!(f, "Delete column"),
+      TableAction::MoveColumnLeft => write!(f, "Move column left"),
+      TableAction::MoveColumnRight => write!(f, "Move column right"),
+      TableAction::AlignNone => write!(f, "Align column: default"),
+      TableAction::AlignLeft => write!(f, "Align column: left"),
+      TableAction::AlignCenter => write!(f, "Align column: center"),
+      TableAction::AlignRight => write!(f, "Align column: right"),
+      TableAction::SortAscending => write!(f, "Sort rows ascending"),
+      TableAction::SortDescending => write!(f, "Sort rows descending"),
     }
   }
 }
@@ -39,7 +339,16 @@ pub enum ViewAction {
   Decrease,
   Reset,
   TogglePreview,
+  ToggleSplit,
   ToggleWordWrap,
+  ToggleLineNumbers,
+  ToggleRelativeLineNumbers,
+  ToggleWhitespace,
+  ToggleOutline,
+  Fold,
+  Unfold,
+  FoldLevel(usize),
+  UnfoldAll,
 }
 
 impl ViewAction {
@@ -48,7 +357,21 @@ impl ViewAction {
     ViewAction::Decrease,
     ViewAction::Reset,
     ViewAction::TogglePreview,
+    ViewAction::ToggleSplit,
     ViewAction::ToggleWordWrap,
+    ViewAction::ToggleLineNumbers,
+    ViewAction::ToggleRelativeLineNumbers,
+    ViewAction::ToggleWhitespace,
+    ViewAction::ToggleOutline,
+    ViewAction::Fold,
+    ViewAction::Unfold,
+    ViewAction::FoldLevel(1),
+    ViewAction::FoldLevel(2),
+    ViewAction::FoldLevel(3),
+    ViewAction::FoldLevel(4),
+    ViewAction::FoldLevel(5),
+    ViewAction::FoldLevel(6),
+    ViewAction::UnfoldAll,
   ];
 }
 
@@ -59,20 +382,202 @@ impl Display for ViewAction {
       ViewAction::Increase => write!(f, "Increase font"),
       ViewAction::Reset => write!(f, "Reset font"),
       ViewAction::TogglePreview => write!(f, "Toggle preview"),
+      ViewAction::ToggleSplit => write!(f, "Toggle side-by-side preview"),
       ViewAction::ToggleWordWrap => write!(f, "Toggle word wrap"),
+      ViewAction::ToggleLineNumbers => write!(f, "Toggle line numbers"),
+      ViewAction::ToggleRelativeLineNumbers => write!(f, "Toggle relative line numbers"),
+      ViewAction::ToggleWhitespace => write!(f, "Toggle whitespace"),
+      ViewAction::ToggleOutline => write!(f, "Toggle outline"),
+      ViewAction::Fold => write!(f, "Fold"),
+      ViewAction::Unfold => write!(f, "Unfold"),
+      ViewAction::FoldLevel(level) => write!(f, "Fold all to level {level}"),
+      ViewAction::UnfoldAll => write!(f, "Unfold all"),
     }
   }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+  Editor,
+  Preview,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollSync {
+  Editor,
+  Cursor,
+  Preview,
+  Block(usize),
+  Heading(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutlineAction {
+  Filter(String),
+  Depth(Depth),
+  Collapse(usize),
+  Jump(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpellingAction {
+  Open,
+  OpenAtPointer,
+  Replace(usize),
+  AddToDictionary,
+  IgnoreInFile,
+  Dismiss,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroAction {
+  ToggleRecording,
+  Play,
+  PlayRepeatedly,
+  PlayToEnd,
+  Save,
+  PlaySaved(usize),
+}
+
+impl MacroAction {
+  pub const ALL: &'static [MacroAction] = &[
+    MacroAction::ToggleRecording,
+    MacroAction::Play,
+    MacroAction::PlayRepeatedly,
+    MacroAction::PlayToEnd,
+    MacroAction::Save,
+  ];
+}
+
+impl Display for MacroAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MacroAction::ToggleRecording => write!(f, "Start/stop recording"),
+      MacroAction::Play => write!(f, "Play macro"),
+      MacroAction::PlayRepeatedly => write!(f, "Play macro N times..."),
+      MacroAction::PlayToEnd => write!(f, "Play macro to end of file"),
+      MacroAction::Save => write!(f, "Save macro as..."),
+      MacroAction::PlaySaved(slot) => write!(f, "Play saved macro {slot}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroItem {
+  pub action: MacroAction,
+  pub label: String,
+}
+
+impl Display for MacroItem {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.label)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pointer {
+  pub row: usize,
+  pub column: usize,
+  pub columns: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GutterTarget {
+  LineNumber(usize),
+  FoldMarker(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionAction {
+  Next,
+  Previous,
+  Accept,
+  Pick(usize),
+  Dismiss,
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
   WindowOpened(window::Id),
   WindowClosed(window::Id),
   Edit(text_editor::Action),
+  ModifiersChanged(keyboard::Modifiers),
   FileActionSelected(FileAction),
+  EditActionSelected(EditAction),
+  LineActionSelected(LineAction),
+  TransformActionSelected(TransformAction),
+  TableActionSelected(TableAction),
   ViewActionSelected(ViewAction),
+  GutterMoved(Option<GutterTarget>),
+  GutterPressed,
+  Completion(CompletionAction),
+  SnippetSelected(Snippet),
+  MacroActionSelected(MacroAction),
+  MacroPromptChanged(String),
+  MacroPromptSubmitted,
+  MacroPromptCancelled,
+  MarginPromptChanged(String),
+  MarginPromptSubmitted,
+  MarginPromptCancelled,
+  Spelling(SpellingAction),
+  Outline(OutlineAction),
+  SpellLanguageSelected(Language),
+  DictionaryLoaded(spelling::Loaded),
+  DictionariesFound(Vec<spelling::Installed>),
+  SpellingDebounced(u64),
+  EditorPointerMoved(Pointer),
+  ConfigSaved(Result<(), String>),
+  PreviewDebounced(u64),
+  MarkdownParsed(Result<Parsed, String>),
+  SplitDividerPressed,
+  SplitDividerMoved(f32),
+  SplitDividerReleased,
+  PaneHovered(Pane),
+  EditorScrolled(scrollable::Viewport),
+  PreviewScrolled,
+  PreviewBlockClicked(usize),
+  PreviewMeasured(ScrollSync, Layout),
   SwitchTab(usize),
   LinkClicked(String),
-  FileOpened(Result<(PathBuf, String), String>),
+  ImageLoaded(images::Loaded),
+  ImagesPolled,
+  ImagesChanged(Vec<PathBuf>),
+  RemoteImagesAllowed,
+  TaskToggled(usize, bool),
+  FileOpened(Result<(PathBuf, String, Properties), String>),
+  SavePathPicked(Result<(PathBuf, Properties), String>),
   FileSaved(Result<PathBuf, String>),
+  FileExported(Result<PathBuf, String>),
+}
+
+impl Message {
+  pub fn interrupts_input(&self) -> bool {
+    !matches!(
+      self,
+      Message::Edit(_)
+        | Message::ModifiersChanged(_)
+        | Message::GutterMoved(_)
+        | Message::Completion(_)
+        | Message::Spelling(_)
+        | Message::EditorPointerMoved(_)
+        | Message::ConfigSaved(_)
+        | Message::PreviewDebounced(_)
+        | Message::MarkdownParsed(_)
+        | Message::SplitDividerMoved(_)
+        | Message::SplitDividerReleased
+        | Message::PaneHovered(_)
+        | Message::EditorScrolled(_)
+        | Message::PreviewScrolled
+        | Message::PreviewMeasured(..)
+        | Message::ImageLoaded(_)
+        | Message::DictionaryLoaded(_)
+        | Message::DictionariesFound(_)
+        | Message::SpellingDebounced(_)
+        | Message::ImagesPolled
+        | Message::ImagesChanged(_)
+        | Message::EditActionSelected(
+          EditAction::Indent | EditAction::Outdent | EditAction::TriggerCompletion
+        )
+    )
+  }
 }
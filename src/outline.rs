@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use crate::{blocks, folding, front_matter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+  pub line: usize,
+  pub level: usize,
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Depth(pub usize);
+
+impl Depth {
+  pub const ALL: &'static [Depth] = &[Depth(1), Depth(2), Depth(3), Depth(4), Depth(5), Depth(6)];
+}
+
+impl Default for Depth {
+  fn default() -> Self {
+    Depth(folding::MAX_HEADING_LEVEL)
+  }
+}
+
+impl Display for Depth {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.0 {
+      1 => write!(f, "Level 1"),
+      folding::MAX_HEADING_LEVEL => write!(f, "All levels"),
+      level => write!(f, "Levels 1–{level}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry<'a> {
+  pub index: usize,
+  pub heading: &'a Heading,
+  pub has_children: bool,
+  pub is_collapsed: bool,
+  pub is_current: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Panel {
+  is_visible: bool,
+  filter: String,
+  depth: Depth,
+  collapsed: HashSet<usize>,
+}
+
+impl Panel {
+  pub fn is_visible(&self) -> bool {
+    self.is_visible
+  }
+
+  pub fn toggle(&mut self) {
+    self.is_visible = !self.is_visible;
+  }
+
+  pub fn filter(&self) -> &str {
+    &self.filter
+  }
+
+  pub fn set_filter(&mut self, filter: String) {
+    self.filter = filter;
+  }
+
+  pub fn depth(&self) -> Depth {
+    self.depth
+  }
+
+  pub fn set_depth(&mut self, depth: Depth) {
+    self.depth = depth;
+    self.collapsed.clear();
+  }
+
+  pub fn toggle_collapsed(&mut self, heading: &Heading) {
+    if !self.collapsed.remove(&heading.line) {
+      self.collapsed.insert(heading.line);
+    }
+  }
+
+  pub fn entries<'a>(&self, headings: &'a [Heading], current: Option<usize>) -> Vec<Entry<'a>> {
+    let filter = self.filter.trim().to_lowercase();
+    let mut entries = Vec::new();
+    let mut hidden_below: Option<usize> = None;
+
+    for (index, heading) in headings.iter().enumerate() {
+      if hidden_below.is_some_and(|level| heading.level > level) {
+        continue;
+      }
+
+      hidden_below = None;
+
+      if heading.level > self.depth.0 {
+        continue;
+      }
+
+      let has_children = headings[index + 1..]
+        .iter()
+        .take_while(|next| next.level > heading.level)
+        .any(|next| next.level <= self.depth.0);
+
+      let is_collapsed = has_children && self.collapsed.contains(&heading.line);
+
+      if filter.is_empty() && is_collapsed {
+        hidden_below = Some(heading.level);
+      }
+
+      if filter.is_empty() || heading.text.to_lowercase().contains(&filter) {
+        entries.push(Entry {
+          index,
+          heading,
+          has_children: has_children && filter.is_empty(),
+          is_collapsed,
+          is_current: current == Some(index),
+        });
+      }
+    }
+
+    entries
+  }
+}
+
+pub fn headings(lines: &[String]) -> Vec<Heading> {
+  let mut headings = Vec::new();
+  let start = front_matter::end(lines).unwrap_or(0);
+  let is_code = blocks::code_lines(lines);
+
+  for (index, line) in lines.iter().enumerate().skip(start) {
+    let trimmed = line.trim_start();
+
+    if is_code[index] {
+      continue;
+    }
+
+    if let Some(level) = folding::heading_level(trimmed) {
+      headings.push(Heading {
+        line: index,
+        level,
+        text: atx_text(&trimmed[level..]),
+      });
+    } else if let Some(level) = lines.get(index + 1).and_then(|next| setext_level(next))
+      && is_setext_text(lines, index, start)
+    {
+      headings.push(Heading {
+        line: index,
+        level,
+        text: trimmed.trim_end().to_owned(),
+      });
+    }
+  }
+
+  headings
+}
+
+pub fn section_at(headings: &[Heading], line: usize) -> Option<usize> {
+  headings
+    .partition_point(|heading| heading.line <= line)
+    .checked_sub(1)
+}
+
+fn atx_text(rest: &str) -> String {
+  let rest = rest.trim();
+  let closed = rest.trim_end_matches('#');
+
+  if closed.is_empty() || closed.ends_with([' ', '\t']) {
+    closed.trim_end().to_owned()
+  } else {
+    rest.to_owned()
+  }
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+  let trimmed = line.trim();
+  let indent = line.len() - line.trim_start_matches(' ').len();
+
+  if indent >= 4 || trimmed.is_empty() {
+    return None;
+  }
+
+  if trimmed.chars().all(|c| c == '=') {
+    Some(1)
+  } else if trimmed.chars().all(|c| c == '-') {
+    Some(2)
+  } else {
+    None
+  }
+}
+
+fn is_setext_text(lines: &[String], index: usize, start: usize) -> bool {
+  let trimmed = lines[index].trim();
+  let is_first = index == start || lines[index - 1].trim().is_empty();
+
+  is_first
+    && !trimmed.is_empty()
+    && setext_level(&lines[index]).is_none()
+    && !trimmed.starts_with(['>', '-', '*', '+', '|', '<'])
+    && !is_ordered_item(trimmed)
+}
+
+fn is_ordered_item(trimmed: &str) -> bool {
+  let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+
+  digits > 0 && trimmed[digits..].starts_with(['.', ')'])
+}
@@ -0,0 +1,1491 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write};
+use std::path::Path;
+
+use iced::highlighter;
+use iced::{Color, font};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ttf_parser::Face;
+
+use crate::{constants, export, subset};
+
+pub const DEFAULT_MARGIN: &str = "20mm";
+
+const POINTS_PER_MILLIMETER: f32 = 72.0 / 25.4;
+const POINTS_PER_PIXEL: f32 = 0.75;
+const TEXT: Color = Color::BLACK;
+const MUTED: Color = Color::from_rgb8(0x66, 0x66, 0x66);
+const LINK: Color = Color::from_rgb8(0x1A, 0x4D, 0xCC);
+const CODE_BACKGROUND: Color = Color::from_rgb8(0xF2, 0xF2, 0xF2);
+const RULE: Color = Color::from_rgb8(0xCC, 0xCC, 0xCC);
+const INDENT: f32 = 18.0;
+const QUOTE_BAR_WIDTH: f32 = 3.0;
+const MAX_IMAGE_HEIGHT: f32 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSize {
+  A4,
+  Letter,
+}
+
+impl PageSize {
+  pub fn parse(name: &str) -> Option<PageSize> {
+    match name.to_lowercase().as_str() {
+      "a4" => Some(PageSize::A4),
+      "letter" => Some(PageSize::Letter),
+      _ => None,
+    }
+  }
+
+  fn dimensions(self) -> (f32, f32) {
+    match self {
+      PageSize::A4 => (595.28, 841.89),
+      PageSize::Letter => (612.0, 792.0),
+    }
+  }
+}
+
+impl Display for PageSize {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PageSize::A4 => write!(f, "A4"),
+      PageSize::Letter => write!(f, "Letter"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+  pub page_size: PageSize,
+  pub margin: f32,
+  pub font_size: f32,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Settings {
+      page_size: PageSize::A4,
+      margin: parse_length(DEFAULT_MARGIN).unwrap_or_default(),
+      font_size: constants::DEFAULT_EDITOR_FONT_SIZE as f32 * POINTS_PER_PIXEL,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginPrompt {
+  pub page_size: PageSize,
+  pub value: String,
+}
+
+impl MarginPrompt {
+  pub fn new(page_size: PageSize) -> Self {
+    MarginPrompt {
+      page_size,
+      value: String::from(DEFAULT_MARGIN),
+    }
+  }
+
+  pub fn settings(&self, font_size: f32) -> Result<Settings, String> {
+    let margin = parse_length(self.value.trim())
+      .ok_or_else(|| String::from("Margin must be a length such as 20mm"))?;
+
+    Ok(Settings {
+      page_size: self.page_size,
+      margin,
+      font_size,
+    })
+  }
+}
+
+pub fn points_from_pixels(pixels: u32) -> f32 {
+  pixels as f32 * POINTS_PER_PIXEL
+}
+
+pub fn parse_length(value: &str) -> Option<f32> {
+  let units = [
+    ("mm", POINTS_PER_MILLIMETER),
+    ("cm", POINTS_PER_MILLIMETER * 10.0),
+    ("in", 72.0),
+    ("pt", 1.0),
+  ];
+
+  let (number, scale) = units
+    .iter()
+    .find_map(|(unit, scale)| value.strip_suffix(unit).map(|number| (number, *scale)))
+    .unwrap_or((value, 1.0));
+
+  number
+    .trim()
+    .parse::<f32>()
+    .ok()
+    .filter(|number| number.is_finite() && *number >= 0.0)
+    .map(|number| number * scale)
+}
+
+pub fn render(
+  source: &str,
+  name: &str,
+  directory: Option<&Path>,
+  settings: Settings,
+) -> Result<Vec<u8>, String> {
+  let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+    | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS
+    | Options::ENABLE_TABLES
+    | Options::ENABLE_STRIKETHROUGH
+    | Options::ENABLE_TASKLISTS
+    | Options::ENABLE_FOOTNOTES;
+
+  let (width, height) = settings.page_size.dimensions();
+
+  if settings.margin * 2.0 + settings.font_size * 4.0 > width.min(height) {
+    return Err(String::from("Margins leave no room for content"));
+  }
+
+  let mut renderer = Renderer {
+    layout: Layout::new(FontFile::new()?, settings),
+    directory,
+    spans: Vec::new(),
+    bold: 0,
+    italic: 0,
+    struck: 0,
+    link: None,
+    heading: None,
+    lists: Vec::new(),
+    marker: None,
+    code: None,
+    images: Vec::new(),
+    table: None,
+    slugs: HashMap::new(),
+    title: None,
+    is_metadata: false,
+  };
+
+  for event in Parser::new_ext(source, options) {
+    renderer.event(event);
+  }
+
+  renderer.flush();
+
+  let title = renderer.title.take().unwrap_or_else(|| name.to_owned());
+
+  write(renderer.layout, &title)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+  color: Color,
+  is_bold: bool,
+  is_italic: bool,
+  is_code: bool,
+  is_struck: bool,
+  link: Option<usize>,
+}
+
+impl Default for Style {
+  fn default() -> Self {
+    Style {
+      color: TEXT,
+      is_bold: false,
+      is_italic: false,
+      is_code: false,
+      is_struck: false,
+      link: None,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+  text: String,
+  style: Style,
+}
+
+struct FontFile {
+  face: Face<'static>,
+  used: HashMap<u16, char>,
+  advance: f32,
+  ascent: f32,
+  descent: f32,
+}
+
+impl FontFile {
+  fn new() -> Result<FontFile, String> {
+    let face = Face::parse(constants::CUSTOM_FONT_BYTES, 0).map_err(|e| e.to_string())?;
+    let units = face.units_per_em() as f32;
+    let advance = face
+      .glyph_index('M')
+      .and_then(|glyph| face.glyph_hor_advance(glyph))
+      .map(|advance| advance as f32 / units)
+      .unwrap_or(constants::CHARACTER_WIDTH);
+
+    Ok(FontFile {
+      advance,
+      ascent: face.ascender() as f32 / units,
+      descent: face.descender() as f32 / units,
+      face,
+      used: HashMap::new(),
+    })
+  }
+
+  fn has(&self, c: char) -> bool {
+    self.face.glyph_index(c).is_some()
+  }
+
+  fn encode(&mut self, text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len() * 4);
+
+    for c in text.chars() {
+      let glyph = self.face.glyph_index(c).map(|glyph| glyph.0).unwrap_or(0);
+
+      if glyph != 0 {
+        self.used.entry(glyph).or_insert(c);
+      }
+
+      let _ = write!(encoded, "{glyph:04X}");
+    }
+
+    encoded
+  }
+
+  fn name(&self) -> String {
+    let family = match constants::CUSTOM_FONT.family {
+      font::Family::Name(name) => name.replace(' ', ""),
+      _ => String::from("Monospace"),
+    };
+
+    let mut glyphs: Vec<u16> = self.used.keys().copied().collect();
+    glyphs.sort_unstable();
+
+    let hash = glyphs.iter().fold(0u32, |hash, glyph| {
+      hash.wrapping_mul(31).wrapping_add(*glyph as u32)
+    });
+    let tag: String = (0..6)
+      .map(|index| (b'A' + (hash / 26u32.pow(index) % 26) as u8) as char)
+      .collect();
+
+    format!("{tag}+{family}")
+  }
+
+  fn scaled(&self, value: i16) -> i32 {
+    (value as f32 * 1000.0 / self.face.units_per_em() as f32).round() as i32
+  }
+}
+
+struct Picture {
+  width: u32,
+  height: u32,
+  rgb: Vec<u8>,
+  alpha: Option<Vec<u8>>,
+}
+
+impl Picture {
+  fn load(path: &Path) -> Option<Picture> {
+    let image = image::open(path).ok()?.to_rgba8();
+    let (width, height) = image.dimensions();
+    let pixels = image.into_raw();
+
+    let rgb = pixels
+      .chunks_exact(4)
+      .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+      .collect();
+    let alpha: Vec<u8> = pixels.chunks_exact(4).map(|pixel| pixel[3]).collect();
+
+    Some(Picture {
+      width,
+      height,
+      rgb,
+      alpha: alpha.iter().any(|a| *a < u8::MAX).then_some(alpha),
+    })
+  }
+}
+
+#[derive(Default)]
+struct Page {
+  content: String,
+  links: Vec<([f32; 4], usize)>,
+}
+
+struct Layout {
+  settings: Settings,
+  width: f32,
+  height: f32,
+  font: FontFile,
+  pages: Vec<Page>,
+  y: f32,
+  left: f32,
+  quotes: Vec<f32>,
+  targets: Vec<String>,
+  anchors: HashMap<String, (usize, f32)>,
+  pictures: Vec<Picture>,
+}
+
+impl Layout {
+  fn new(font: FontFile, settings: Settings) -> Layout {
+    let (width, height) = settings.page_size.dimensions();
+
+    Layout {
+      settings,
+      width,
+      height,
+      font,
+      pages: Vec::new(),
+      y: height - settings.margin,
+      left: settings.margin,
+      quotes: Vec::new(),
+      targets: Vec::new(),
+      anchors: HashMap::new(),
+      pictures: Vec::new(),
+    }
+  }
+
+  fn top(&self) -> f32 {
+    self.height - self.settings.margin
+  }
+
+  fn right(&self) -> f32 {
+    self.width - self.settings.margin
+  }
+
+  fn page(&mut self) -> &mut Page {
+    if self.pages.is_empty() {
+      self.pages.push(Page::default());
+    }
+
+    self.pages.last_mut().expect("at least one page")
+  }
+
+  fn ensure(&mut self, height: f32) {
+    if self.pages.is_empty() || (self.y - height < self.settings.margin && self.y < self.top()) {
+      self.pages.push(Page::default());
+      self.y = self.top();
+    }
+  }
+
+  fn space(&mut self, height: f32) {
+    if self.y < self.top() {
+      self.y -= height;
+    }
+  }
+
+  fn columns(&self, x: f32, size: f32) -> usize {
+    (((self.right() - x) / (size * self.font.advance)).floor() as usize).max(1)
+  }
+
+  fn anchor(&mut self, slug: String) {
+    let page = self.pages.len().saturating_sub(1);
+    self.anchors.entry(slug).or_insert((page, self.y));
+  }
+
+  fn paragraph(&mut self, spans: &[Span], size: f32, marker: Option<&str>) {
+    let x = self.left;
+
+    for (index, line) in wrap(spans, self.columns(x, size)).iter().enumerate() {
+      self.line(line, size, x, marker.filter(|_| index == 0), false);
+    }
+  }
+
+  fn preformatted(&mut self, lines: &[Vec<Span>], size: f32, has_background: bool) {
+    let x = self.left + if has_background { size * 0.5 } else { 0.0 };
+    let columns = self.columns(x, size).saturating_sub(1).max(1);
+
+    for line in lines {
+      for part in split_columns(line, columns) {
+        self.line(&part, size, x, None, has_background);
+      }
+    }
+  }
+
+  fn line(
+    &mut self,
+    spans: &[Span],
+    size: f32,
+    x: f32,
+    marker: Option<&str>,
+    has_background: bool,
+  ) {
+    let height = size * constants::LINE_HEIGHT;
+    self.ensure(height);
+
+    let top = self.y;
+    let bottom = top - height;
+    let baseline = bottom + (height - size * (self.font.ascent - self.font.descent)) / 2.0
+      - self.font.descent * size;
+    let character = size * self.font.advance;
+
+    if has_background {
+      let (left, right) = (self.left, self.right());
+      rectangle(
+        self.page(),
+        [left, bottom, right - left, height],
+        CODE_BACKGROUND,
+      );
+    }
+
+    for bar in self.quotes.clone() {
+      rectangle(self.page(), [bar, bottom, QUOTE_BAR_WIDTH, height], RULE);
+    }
+
+    if let Some(marker) = marker {
+      let offset = (marker.chars().count() + 1) as f32 * character;
+      let span = Span {
+        text: marker.to_owned(),
+        style: Style::default(),
+      };
+
+      self.text(&span, x - offset, baseline, size);
+    }
+
+    let mut cursor = x;
+
+    for span in spans {
+      let width = span.text.chars().count() as f32 * character;
+
+      if span.style.is_code && !has_background {
+        rectangle(
+          self.page(),
+          [cursor, bottom, width, height],
+          CODE_BACKGROUND,
+        );
+      }
+
+      self.text(span, cursor, baseline, size);
+
+      if span.style.is_struck {
+        let middle = baseline + size * 0.3;
+        stroke(
+          self.page(),
+          [cursor, middle, cursor + width, middle],
+          span.style.color,
+          size * 0.06,
+        );
+      }
+
+      if let Some(link) = span.style.link {
+        self
+          .page()
+          .links
+          .push(([cursor, bottom, cursor + width, top], link));
+      }
+
+      cursor += width;
+    }
+
+    self.y = bottom;
+  }
+
+  fn text(&mut self, span: &Span, x: f32, baseline: f32, size: f32) {
+    if span.text.trim().is_empty() {
+      return;
+    }
+
+    let glyphs = self.font.encode(&span.text);
+    let color = rgb(span.style.color);
+    let skew = if span.style.is_italic { 0.2 } else { 0.0 };
+    let mode = if span.style.is_bold {
+      format!("2 Tr {:.2} w {color} RG ", size * 0.04)
+    } else {
+      String::new()
+    };
+
+    let _ = writeln!(
+      self.page().content,
+      "BT /F1 {size:.2} Tf {color} rg {mode}1 0 {skew} 1 {x:.2} {baseline:.2} Tm <{glyphs}> Tj ET"
+    );
+  }
+
+  fn rule(&mut self, size: f32) {
+    self.ensure(size);
+
+    let middle = self.y - size / 2.0;
+    let (left, right) = (self.left, self.right());
+
+    stroke(self.page(), [left, middle, right, middle], RULE, 1.0);
+    self.y -= size;
+  }
+
+  fn picture(&mut self, picture: Picture) {
+    let available = self.right() - self.left;
+    let limit = (self.top() - self.settings.margin) * MAX_IMAGE_HEIGHT;
+    let natural = (
+      picture.width as f32 * POINTS_PER_PIXEL,
+      picture.height as f32 * POINTS_PER_PIXEL,
+    );
+    let scale = (available / natural.0).min(limit / natural.1).min(1.0);
+    let (width, height) = (natural.0 * scale, natural.1 * scale);
+
+    self.ensure(height);
+
+    let index = self.pictures.len();
+    let (x, y) = (self.left, self.y - height);
+
+    let _ = writeln!(
+      self.page().content,
+      "q {width:.2} 0 0 {height:.2} {x:.2} {y:.2} cm /Im{index} Do Q"
+    );
+
+    self.pictures.push(picture);
+    self.y = y;
+  }
+}
+
+struct Table {
+  alignments: Vec<Alignment>,
+  rows: Vec<Vec<String>>,
+  header: usize,
+}
+
+struct Renderer<'a> {
+  layout: Layout,
+  directory: Option<&'a Path>,
+  spans: Vec<Span>,
+  bold: usize,
+  italic: usize,
+  struck: usize,
+  link: Option<usize>,
+  heading: Option<HeadingLevel>,
+  lists: Vec<Option<u64>>,
+  marker: Option<String>,
+  code: Option<(String, String)>,
+  images: Vec<(String, String)>,
+  table: Option<Table>,
+  slugs: HashMap<String, usize>,
+  title: Option<String>,
+  is_metadata: bool,
+}
+
+impl Renderer<'_> {
+  fn event(&mut self, event: Event) {
+    if self.is_metadata {
+      self.is_metadata = !matches!(event, Event::End(TagEnd::MetadataBlock(_)));
+      return;
+    }
+
+    match event {
+      Event::Start(tag) => self.start(tag),
+      Event::End(tag) => self.end(tag),
+      Event::Text(text) => self.text(&text, self.style()),
+      Event::Code(text) => self.text(
+        &text,
+        Style {
+          is_code: true,
+          ..self.style()
+        },
+      ),
+      Event::SoftBreak => self.text(" ", self.style()),
+      Event::HardBreak => self.text("\n", self.style()),
+      Event::Rule => {
+        self.flush();
+        self.layout.rule(self.layout.settings.font_size);
+      }
+      Event::TaskListMarker(checked) => {
+        let (glyph, fallback) = if checked {
+          ('☑', "[x]")
+        } else {
+          ('☐', "[ ]")
+        };
+
+        self.marker = Some(if self.layout.font.has(glyph) {
+          glyph.to_string()
+        } else {
+          fallback.to_owned()
+        });
+      }
+      Event::FootnoteReference(label) => self.text(
+        &format!("[{label}]"),
+        Style {
+          color: LINK,
+          ..self.style()
+        },
+      ),
+      Event::Html(_) | Event::InlineHtml(_) | Event::InlineMath(_) | Event::DisplayMath(_) => {}
+    }
+  }
+
+  fn start(&mut self, tag: Tag) {
+    match tag {
+      Tag::MetadataBlock(_) => self.is_metadata = true,
+      Tag::Paragraph => self.flush(),
+      Tag::Heading { level, .. } => {
+        self.flush();
+        self.heading = Some(level);
+      }
+      Tag::BlockQuote(_) => {
+        self.flush();
+        self.layout.quotes.push(self.layout.left);
+        self.layout.left += INDENT;
+      }
+      Tag::CodeBlock(kind) => {
+        self.flush();
+
+        let language = match kind {
+          CodeBlockKind::Fenced(info) => info
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_owned(),
+          CodeBlockKind::Indented => String::new(),
+        };
+
+        self.code = Some((language, String::new()));
+      }
+      Tag::List(first) => {
+        self.flush();
+        self.lists.push(first);
+        self.layout.left += INDENT;
+      }
+      Tag::Item => {
+        self.flush();
+        self.marker = match self.lists.last_mut() {
+          Some(Some(number)) => {
+            *number += 1;
+            Some(format!("{}.", *number - 1))
+          }
+          _ => Some(String::from("•")),
+        };
+      }
+      Tag::FootnoteDefinition(label) => {
+        self.flush();
+        self.layout.left += INDENT * 2.0;
+        self.marker = Some(format!("[{label}]"));
+      }
+      Tag::Table(alignments) => {
+        self.flush();
+        self.table = Some(Table {
+          alignments,
+          rows: Vec::new(),
+          header: 0,
+        });
+      }
+      Tag::TableHead | Tag::TableRow => {
+        if let Some(table) = &mut self.table {
+          table.rows.push(Vec::new());
+        }
+      }
+      Tag::TableCell => {
+        if let Some(row) = self.table.as_mut().and_then(|table| table.rows.last_mut()) {
+          row.push(String::new());
+        }
+      }
+      Tag::Emphasis => self.italic += 1,
+      Tag::Strong => self.bold += 1,
+      Tag::Strikethrough => self.struck += 1,
+      Tag::Link { dest_url, .. } => {
+        self.layout.targets.push(dest_url.to_string());
+        self.link = Some(self.layout.targets.len() - 1);
+      }
+      Tag::Image { dest_url, .. } => self.images.push((dest_url.to_string(), String::new())),
+      _ => {}
+    }
+  }
+
+  fn end(&mut self, tag: TagEnd) {
+    match tag {
+      TagEnd::Paragraph | TagEnd::Item => self.flush(),
+      TagEnd::Heading(level) => self.heading(level),
+      TagEnd::BlockQuote(_) => {
+        self.flush();
+        self.layout.left -= INDENT;
+        self.layout.quotes.pop();
+      }
+      TagEnd::CodeBlock => self.code_block(),
+      TagEnd::List(_) => {
+        self.flush();
+        self.lists.pop();
+        self.layout.left -= INDENT;
+
+        if self.lists.is_empty() {
+          self.layout.space(self.layout.settings.font_size * 0.6);
+        }
+      }
+      TagEnd::FootnoteDefinition => {
+        self.flush();
+        self.layout.left -= INDENT * 2.0;
+      }
+      TagEnd::TableHead => {
+        if let Some(table) = &mut self.table {
+          table.header = table.rows.len();
+        }
+      }
+      TagEnd::Table => self.table(),
+      TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+      TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+      TagEnd::Strikethrough => self.struck = self.struck.saturating_sub(1),
+      TagEnd::Link => self.link = None,
+      TagEnd::Image => self.image(),
+      _ => {}
+    }
+  }
+
+  fn style(&self) -> Style {
+    Style {
+      color: if self.link.is_some() { LINK } else { TEXT },
+      is_bold: self.bold > 0 || self.heading.is_some(),
+      is_italic: self.italic > 0,
+      is_code: false,
+      is_struck: self.struck > 0,
+      link: self.link,
+    }
+  }
+
+  fn text(&mut self, text: &str, style: Style) {
+    if let Some((_, code)) = &mut self.code {
+      code.push_str(text);
+    } else if let Some((_, alt)) = self.images.last_mut() {
+      alt.push_str(text);
+    } else if let Some(table) = &mut self.table {
+      if let Some(cell) = table.rows.last_mut().and_then(|row| row.last_mut()) {
+        cell.push_str(text);
+      }
+    } else {
+      push(&mut self.spans, text, style);
+    }
+  }
+
+  fn flush(&mut self) {
+    if self.spans.iter().all(|span| span.text.trim().is_empty()) {
+      self.spans.clear();
+      return;
+    }
+
+    let spans = std::mem::take(&mut self.spans);
+    let size = self.layout.settings.font_size;
+
+    self
+      .layout
+      .paragraph(&spans, size, self.marker.take().as_deref());
+    self.layout.space(if self.lists.is_empty() {
+      size * 0.6
+    } else {
+      size * 0.2
+    });
+  }
+
+  fn heading(&mut self, level: HeadingLevel) {
+    let spans = std::mem::take(&mut self.spans);
+    let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+    let text = text.trim().to_owned();
+    let slug = export::unique_slug(&mut self.slugs, &text);
+
+    let size = self.layout.settings.font_size
+      * match level {
+        HeadingLevel::H1 => 2.0,
+        HeadingLevel::H2 => 1.75,
+        HeadingLevel::H3 => 1.5,
+        HeadingLevel::H4 => 1.25,
+        HeadingLevel::H5 | HeadingLevel::H6 => 1.0,
+      };
+
+    if level == HeadingLevel::H1 && self.title.is_none() {
+      self.title = Some(text);
+    }
+
+    self.layout.space(size * 0.4);
+    self.layout.ensure(size * constants::LINE_HEIGHT * 2.0);
+    self.layout.anchor(slug);
+    self.layout.paragraph(&spans, size, None);
+    self.layout.space(size * 0.3);
+    self.heading = None;
+  }
+
+  fn code_block(&mut self) {
+    let Some((language, code)) = self.code.take() else {
+      return;
+    };
+
+    let size = self.layout.settings.font_size * 0.85;
+    let lines: Vec<Vec<Span>> =
+      export::highlight_lines(&code, &language, highlighter::Theme::InspiredGitHub)
+        .into_iter()
+        .map(|tokens| {
+          tokens
+            .into_iter()
+            .map(|token| Span {
+              text: token.text,
+              style: Style {
+                color: token.color.unwrap_or(TEXT),
+                is_bold: token
+                  .font
+                  .is_some_and(|font| font.weight == font::Weight::Bold),
+                is_italic: token
+                  .font
+                  .is_some_and(|font| font.style == font::Style::Italic),
+                is_code: true,
+                ..Style::default()
+              },
+            })
+            .collect()
+        })
+        .collect();
+
+    self.layout.preformatted(&lines, size, true);
+    self.layout.space(self.layout.settings.font_size * 0.6);
+  }
+
+  fn table(&mut self) {
+    let Some(table) = self.table.take() else {
+      return;
+    };
+
+    let count = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..count)
+      .map(|column| {
+        table
+          .rows
+          .iter()
+          .filter_map(|row| row.get(column))
+          .map(|cell| cell.trim().chars().count())
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+
+    let (vertical, horizontal, cross) = if self.layout.font.has('│') {
+      ('│', '─', '┼')
+    } else {
+      ('|', '-', '+')
+    };
+
+    let mut lines = Vec::new();
+
+    for (index, row) in table.rows.iter().enumerate() {
+      let mut line = Vec::new();
+
+      for (column, width) in widths.iter().enumerate() {
+        if column > 0 {
+          push(
+            &mut line,
+            &format!(" {vertical} "),
+            Style {
+              color: MUTED,
+              ..Style::default()
+            },
+          );
+        }
+
+        let cell = row.get(column).map(|cell| cell.trim()).unwrap_or_default();
+        let padding = width - cell.chars().count();
+        let (before, after) = match table.alignments.get(column) {
+          Some(Alignment::Right) => (padding, 0),
+          Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+          _ => (0, padding),
+        };
+
+        let style = Style {
+          is_bold: index < table.header,
+          ..Style::default()
+        };
+
+        push(&mut line, &" ".repeat(before), style);
+        push(&mut line, cell, style);
+        push(&mut line, &" ".repeat(after), style);
+      }
+
+      lines.push(line);
+
+      if index + 1 == table.header {
+        let separator = widths
+          .iter()
+          .map(|width| horizontal.to_string().repeat(*width))
+          .collect::<Vec<_>>()
+          .join(&format!("{horizontal}{cross}{horizontal}"));
+
+        lines.push(vec![Span {
+          text: separator,
+          style: Style {
+            color: MUTED,
+            ..Style::default()
+          },
+        }]);
+      }
+    }
+
+    let size = self.layout.settings.font_size * 0.85;
+
+    self.layout.preformatted(&lines, size, false);
+    self.layout.space(self.layout.settings.font_size * 0.6);
+  }
+
+  fn image(&mut self) {
+    let Some((url, alt)) = self.images.pop() else {
+      return;
+    };
+
+    let picture = (!url.contains("://"))
+      .then(|| {
+        let path = Path::new(&url);
+        match self.directory {
+          Some(directory) if path.is_relative() => directory.join(path),
+          _ => path.to_path_buf(),
+        }
+      })
+      .and_then(|path| Picture::load(&path));
+
+    match picture {
+      Some(picture) => {
+        self.flush();
+        self.layout.picture(picture);
+        self.layout.space(self.layout.settings.font_size * 0.6);
+      }
+      None => {
+        let style = Style {
+          color: MUTED,
+          is_italic: true,
+          ..self.style()
+        };
+
+        push(
+          &mut self.spans,
+          &format!("[{}]", if alt.is_empty() { url } else { alt }),
+          style,
+        );
+      }
+    }
+  }
+}
+
+fn push(spans: &mut Vec<Span>, text: &str, style: Style) {
+  if text.is_empty() {
+    return;
+  }
+
+  match spans.last_mut() {
+    Some(last) if last.style == style => last.text.push_str(text),
+    _ => spans.push(Span {
+      text: text.to_owned(),
+      style,
+    }),
+  }
+}
+
+fn wrap(spans: &[Span], columns: usize) -> Vec<Vec<Span>> {
+  let mut lines: Vec<Vec<Span>> = vec![Vec::new()];
+  let mut column = 0;
+
+  for span in spans {
+    for token in tokens(&span.text) {
+      if token == "\n" {
+        lines.push(Vec::new());
+        column = 0;
+        continue;
+      }
+
+      if token.starts_with(char::is_whitespace) {
+        if column > 0 && column < columns {
+          push(
+            lines.last_mut().expect("at least one line"),
+            " ",
+            span.style,
+          );
+          column += 1;
+        }
+
+        continue;
+      }
+
+      let length = token.chars().count();
+
+      if column > 0 && column + length > columns {
+        lines.push(Vec::new());
+        column = 0;
+      }
+
+      let mut rest = token;
+
+      while rest.chars().count() > columns - column {
+        let split = rest
+          .char_indices()
+          .nth(columns - column)
+          .map(|(index, _)| index)
+          .unwrap_or(rest.len());
+
+        push(
+          lines.last_mut().expect("at least one line"),
+          &rest[..split],
+          span.style,
+        );
+        lines.push(Vec::new());
+        column = 0;
+        rest = &rest[split..];
+      }
+
+      push(
+        lines.last_mut().expect("at least one line"),
+        rest,
+        span.style,
+      );
+      column += rest.chars().count();
+    }
+  }
+
+  for line in &mut lines {
+    while let Some(last) = line.last_mut() {
+      let trimmed = last.text.trim_end().len();
+      last.text.truncate(trimmed);
+
+      if !last.text.is_empty() {
+        break;
+      }
+
+      line.pop();
+    }
+  }
+
+  lines
+}
+
+fn tokens(text: &str) -> Vec<&str> {
+  let mut tokens = Vec::new();
+  let mut start = 0;
+  let mut is_space = None;
+
+  for (index, c) in text.char_indices() {
+    let kind = if c == '\n' {
+      None
+    } else {
+      Some(c.is_whitespace())
+    };
+
+    if c == '\n' || (is_space.is_some() && kind != is_space) {
+      if index > start {
+        tokens.push(&text[start..index]);
+      }
+
+      start = index;
+    }
+
+    if c == '\n' {
+      tokens.push("\n");
+      start = index + 1;
+      is_space = None;
+    } else {
+      is_space = kind;
+    }
+  }
+
+  if start < text.len() {
+    tokens.push(&text[start..]);
+  }
+
+  tokens
+}
+
+fn split_columns(line: &[Span], columns: usize) -> Vec<Vec<Span>> {
+  let mut parts: Vec<Vec<Span>> = vec![Vec::new()];
+  let mut column = 0;
+
+  for span in line {
+    for c in span.text.chars() {
+      if column == columns {
+        parts.push(Vec::new());
+        column = 0;
+      }
+
+      let text = if c == '\t' { ' ' } else { c };
+      push(
+        parts.last_mut().expect("at least one part"),
+        &text.to_string(),
+        span.style,
+      );
+      column += 1;
+    }
+  }
+
+  parts
+}
+
+fn rgb(color: Color) -> String {
+  format!("{:.3} {:.3} {:.3}", color.r, color.g, color.b)
+}
+
+fn rectangle(page: &mut Page, [x, y, width, height]: [f32; 4], color: Color) {
+  let _ = writeln!(
+    page.content,
+    "{} rg {x:.2} {y:.2} {width:.2} {height:.2} re f",
+    rgb(color)
+  );
+}
+
+fn stroke(page: &mut Page, [x1, y1, x2, y2]: [f32; 4], color: Color, width: f32) {
+  let _ = writeln!(
+    page.content,
+    "{} RG {width:.2} w {x1:.2} {y1:.2} m {x2:.2} {y2:.2} l S",
+    rgb(color)
+  );
+}
+
+struct Document {
+  bytes: Vec<u8>,
+  offsets: Vec<usize>,
+}
+
+impl Document {
+  fn reserve(&mut self) -> usize {
+    self.offsets.push(0);
+    self.offsets.len()
+  }
+
+  fn object(&mut self, id: usize, body: &str) {
+    self.offsets[id - 1] = self.bytes.len();
+    self
+      .bytes
+      .extend_from_slice(format!("{id} 0 obj\n{body}\nendobj\n").as_bytes());
+  }
+
+  fn stream(&mut self, id: usize, dictionary: &str, data: &[u8]) {
+    let compressed = miniz_oxide::deflate::compress_to_vec_zlib(data, 6);
+
+    self.offsets[id - 1] = self.bytes.len();
+    self.bytes.extend_from_slice(
+      format!(
+        "{id} 0 obj\n<< {dictionary} /Filter /FlateDecode /Length {} >>\nstream\n",
+        compressed.len()
+      )
+      .as_bytes(),
+    );
+    self.bytes.extend_from_slice(&compressed);
+    self.bytes.extend_from_slice(b"\nendstream\nendobj\n");
+  }
+
+  fn finish(mut self, catalog: usize, info: usize) -> Vec<u8> {
+    let start = self.bytes.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+
+    for offset in &self.offsets {
+      let _ = writeln!(table, "{offset:010} 00000 n ");
+    }
+
+    let _ = write!(
+      table,
+      "trailer\n<< /Size {} /Root {catalog} 0 R /Info {info} 0 R >>\nstartxref\n{start}\n%%EOF\n",
+      self.offsets.len() + 1
+    );
+
+    self.bytes.extend_from_slice(table.as_bytes());
+    self.bytes
+  }
+}
+
+fn write(mut layout: Layout, title: &str) -> Result<Vec<u8>, String> {
+  if layout.pages.is_empty() {
+    layout.pages.push(Page::default());
+  }
+
+  decorate(&mut layout, title);
+
+  let mut document = Document {
+    bytes: b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+    offsets: Vec::new(),
+  };
+
+  let catalog = document.reserve();
+  let pages = document.reserve();
+  let info = document.reserve();
+  let font = document.reserve();
+  let descendant = document.reserve();
+  let descriptor = document.reserve();
+  let file = document.reserve();
+  let cmap = document.reserve();
+
+  let pictures: Vec<(usize, Option<usize>)> = layout
+    .pictures
+    .iter()
+    .map(|picture| {
+      let image = document.reserve();
+      (image, picture.alpha.as_ref().map(|_| document.reserve()))
+    })
+    .collect();
+
+  let page_ids: Vec<(usize, usize)> = layout
+    .pages
+    .iter()
+    .map(|_| (document.reserve(), document.reserve()))
+    .collect();
+
+  document.object(catalog, &format!("<< /Type /Catalog /Pages {pages} 0 R >>"));
+
+  let kids: Vec<String> = page_ids
+    .iter()
+    .map(|(page, _)| format!("{page} 0 R"))
+    .collect();
+  document.object(
+    pages,
+    &format!(
+      "<< /Type /Pages /Kids [{}] /Count {} >>",
+      kids.join(" "),
+      kids.len()
+    ),
+  );
+
+  document.object(
+    info,
+    &format!("<< /Title <{}> /Producer (zoeae) >>", utf16(title)),
+  );
+
+  let font_name = layout.font.name();
+  document.object(
+    font,
+    &format!(
+      "<< /Type /Font /Subtype /Type0 /BaseFont /{font_name} /Encoding /Identity-H \
+       /DescendantFonts [{descendant} 0 R] /ToUnicode {cmap} 0 R >>"
+    ),
+  );
+
+  document.object(
+    descendant,
+    &format!(
+      "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{font_name} \
+       /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+       /FontDescriptor {descriptor} 0 R /DW {} /CIDToGIDMap /Identity >>",
+      (layout.font.advance * 1000.0).round()
+    ),
+  );
+
+  let face = &layout.font.face;
+  let bounds = face.global_bounding_box();
+  document.object(
+    descriptor,
+    &format!(
+      "<< /Type /FontDescriptor /FontName /{font_name} /Flags 33 /FontBBox [{} {} {} {}] \
+       /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 {file} 0 R >>",
+      layout.font.scaled(bounds.x_min),
+      layout.font.scaled(bounds.y_min),
+      layout.font.scaled(bounds.x_max),
+      layout.font.scaled(bounds.y_max),
+      layout.font.scaled(face.ascender()),
+      layout.font.scaled(face.descender()),
+      layout
+        .font
+        .scaled(face.capital_height().unwrap_or(face.ascender())),
+    ),
+  );
+
+  let subset = subset::subset(
+    constants::CUSTOM_FONT_BYTES,
+    layout.font.used.values().copied(),
+  )?;
+  document.stream(file, &format!("/Length1 {}", subset.len()), &subset);
+
+  document.stream(cmap, "", to_unicode(&layout.font.used).as_bytes());
+
+  for (picture, (image, mask)) in layout.pictures.iter().zip(&pictures) {
+    let soft_mask = mask
+      .map(|mask| format!(" /SMask {mask} 0 R"))
+      .unwrap_or_default();
+
+    document.stream(
+      *image,
+      &format!(
+        "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+         /BitsPerComponent 8{soft_mask}",
+        picture.width, picture.height
+      ),
+      &picture.rgb,
+    );
+
+    if let (Some(mask), Some(alpha)) = (mask, &picture.alpha) {
+      document.stream(
+        *mask,
+        &format!(
+          "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray \
+           /BitsPerComponent 8",
+          picture.width, picture.height
+        ),
+        alpha,
+      );
+    }
+  }
+
+  let images: String = pictures
+    .iter()
+    .enumerate()
+    .map(|(index, (image, _))| format!("/Im{index} {image} 0 R "))
+    .collect();
+
+  for (page, (page_id, content_id)) in layout.pages.iter().zip(&page_ids) {
+    let annotations: Vec<String> = page
+      .links
+      .iter()
+      .filter_map(|(rect, target)| {
+        let action = link_action(&layout.targets[*target], &layout.anchors, &page_ids)?;
+
+        Some(format!(
+          "<< /Type /Annot /Subtype /Link /Rect [{:.2} {:.2} {:.2} {:.2}] /Border [0 0 0] {action} >>",
+          rect[0], rect[1], rect[2], rect[3]
+        ))
+      })
+      .collect();
+
+    document.object(
+      *page_id,
+      &format!(
+        "<< /Type /Page /Parent {pages} 0 R /MediaBox [0 0 {:.2} {:.2}] \
+         /Resources << /Font << /F1 {font} 0 R >> /XObject << {images}>> >> \
+         /Contents {content_id} 0 R /Annots [{}] >>",
+        layout.width,
+        layout.height,
+        annotations.join(" ")
+      ),
+    );
+
+    document.stream(*content_id, "", page.content.as_bytes());
+  }
+
+  Ok(document.finish(catalog, info))
+}
+
+fn decorate(layout: &mut Layout, title: &str) {
+  let count = layout.pages.len();
+  let size = layout.settings.font_size * 0.75;
+  let margin = layout.settings.margin;
+  let character = size * layout.font.advance;
+  let columns = ((layout.width - margin * 2.0) / character).floor() as usize;
+  let header: String = title.chars().take(columns).collect();
+
+  for index in 0..count {
+    let footer = format!("Page {} of {count}", index + 1);
+    let center = (layout.width - footer.chars().count() as f32 * character) / 2.0;
+    let style = Style {
+      color: MUTED,
+      ..Style::default()
+    };
+
+    let header = Span {
+      text: header.clone(),
+      style,
+    };
+    let footer = Span {
+      text: footer,
+      style,
+    };
+
+    let glyphs = (
+      layout.font.encode(&header.text),
+      layout.font.encode(&footer.text),
+    );
+    let color = rgb(MUTED);
+    let top = layout.height - margin / 2.0 - size / 2.0;
+    let bottom = margin / 2.0 - size / 2.0;
+
+    let _ = writeln!(
+      layout.pages[index].content,
+      "BT /F1 {size:.2} Tf {color} rg 1 0 0 1 {margin:.2} {top:.2} Tm <{}> Tj ET\n\
+       BT /F1 {size:.2} Tf {color} rg 1 0 0 1 {center:.2} {bottom:.2} Tm <{}> Tj ET",
+      glyphs.0, glyphs.1
+    );
+  }
+}
+
+fn link_action(
+  target: &str,
+  anchors: &HashMap<String, (usize, f32)>,
+  pages: &[(usize, usize)],
+) -> Option<String> {
+  match target.strip_prefix('#') {
+    Some(anchor) => {
+      let (page, y) = anchors
+        .get(anchor)
+        .or_else(|| anchors.get(&export::slug(anchor)))?;
+      let (page, _) = pages.get(*page)?;
+
+      Some(format!("/Dest [{page} 0 R /XYZ null {y:.2} null]"))
+    }
+    None => Some(format!("/A << /S /URI /URI ({}) >>", literal(target))),
+  }
+}
+
+fn to_unicode(used: &HashMap<u16, char>) -> String {
+  let mut glyphs: Vec<(&u16, &char)> = used.iter().collect();
+  glyphs.sort();
+
+  let mut cmap = String::from(
+    "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+     /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+     /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+     1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+  );
+
+  for chunk in glyphs.chunks(100) {
+    let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+
+    for (glyph, c) in chunk {
+      let _ = writeln!(cmap, "<{glyph:04X}> <{}>", utf16_hex(**c));
+    }
+
+    cmap.push_str("endbfchar\n");
+  }
+
+  cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+  cmap
+}
+
+fn utf16_hex(c: char) -> String {
+  c.encode_utf16(&mut [0; 2])
+    .iter()
+    .map(|unit| format!("{unit:04X}"))
+    .collect()
+}
+
+fn utf16(text: &str) -> String {
+  std::iter::once(String::from("FEFF"))
+    .chain(text.chars().map(utf16_hex))
+    .collect()
+}
+
+fn literal(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '\\' | '(' | ')' => {
+        escaped.push('\\');
+        escaped.push(c);
+      }
+      c if c.is_ascii() && !c.is_ascii_control() => escaped.push(c),
+      c => {
+        let mut buffer = [0; 4];
+
+        for byte in c.encode_utf8(&mut buffer).bytes() {
+          let _ = write!(escaped, "%{byte:02X}");
+        }
+      }
+    }
+  }
+
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_lengths_in_each_unit() {
+    assert_eq!(parse_length("72"), Some(72.0));
+    assert_eq!(parse_length("1in"), Some(72.0));
+    assert_eq!(parse_length("12pt"), Some(12.0));
+    assert!((parse_length("10mm").unwrap() - 28.346).abs() < 0.01);
+    assert!((parse_length("1cm").unwrap() - 28.346).abs() < 0.01);
+    assert_eq!(parse_length("-5mm"), None);
+    assert_eq!(parse_length("wide"), None);
+  }
+
+  #[test]
+  fn margin_prompt_starts_at_the_default_margin() {
+    let prompt = MarginPrompt::new(PageSize::Letter);
+    let settings = prompt.settings(9.0).unwrap();
+
+    assert_eq!(settings.page_size, PageSize::Letter);
+    assert_eq!(settings.margin, parse_length(DEFAULT_MARGIN).unwrap());
+    assert_eq!(settings.font_size, 9.0);
+  }
+
+  #[test]
+  fn margin_prompt_rejects_invalid_lengths() {
+    let prompt = MarginPrompt {
+      page_size: PageSize::A4,
+      value: String::from("narrow"),
+    };
+
+    assert!(prompt.settings(9.0).is_err());
+  }
+}
@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::io;
+
+const CONFIG_FILE: &str = "snippets.conf";
+const FALLBACK_LANGUAGE: &str = "txt";
+const MARKDOWN: &[&str] = &["md", "markdown"];
+
+const BUILT_IN_MARKDOWN: &[(&str, &str, &str)] = &[
+  (
+    "table",
+    "Table",
+    "| ${1:Column} | ${2:Column} |\n| --- | --- |\n| $3 | $4 |\n$0",
+  ),
+  (
+    "code",
+    "Fenced code block",
+    "```${1:language}\n${2:$SELECTION}\n```\n$0",
+  ),
+  ("link", "Link", "[${1:$SELECTION}](${2:https://})$0"),
+  (
+    "front",
+    "Front matter",
+    "---\ntitle: ${1:$FILENAME}\ndate: $DATE\n---\n\n$0",
+  ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+  pub prefix: String,
+  pub description: String,
+  pub body: String,
+}
+
+impl Display for Snippet {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.description.is_empty() {
+      write!(f, "{}", self.prefix)
+    } else {
+      write!(f, "{} ({})", self.description, self.prefix)
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Library {
+  languages: HashMap<String, Vec<Snippet>>,
+}
+
+impl Library {
+  pub fn load() -> Self {
+    let mut library = Library::default();
+
+    for language in MARKDOWN {
+      for (prefix, description, body) in BUILT_IN_MARKDOWN {
+        library.add(
+          language,
+          Snippet {
+            prefix: prefix.to_string(),
+            description: description.to_string(),
+            body: body.to_string(),
+          },
+        );
+      }
+    }
+
+    if let Some(contents) =
+      io::config_path(CONFIG_FILE).and_then(|path| fs::read_to_string(path).ok())
+    {
+      for (languages, snippet) in parse(&contents) {
+        for language in &languages {
+          library.add(language, snippet.clone());
+        }
+      }
+    }
+
+    library
+  }
+
+  fn add(&mut self, language: &str, snippet: Snippet) {
+    let snippets = self
+      .languages
+      .entry(language.to_ascii_lowercase())
+      .or_default();
+
+    snippets.retain(|other| other.prefix != snippet.prefix);
+    snippets.push(snippet);
+  }
+
+  pub fn for_language(&self, extension: Option<&str>) -> &[Snippet] {
+    let language = extension.unwrap_or(FALLBACK_LANGUAGE).to_ascii_lowercase();
+
+    self
+      .languages
+      .get(&language)
+      .map_or(&[], |snippets| snippets.as_slice())
+  }
+
+  pub fn find(&self, extension: Option<&str>, prefix: &str) -> Option<&Snippet> {
+    self
+      .for_language(extension)
+      .iter()
+      .find(|snippet| snippet.prefix == prefix)
+  }
+}
+
+fn parse(contents: &str) -> Vec<(Vec<String>, Snippet)> {
+  let mut snippets = Vec::new();
+  let mut languages: Vec<String> = Vec::new();
+  let mut current: Option<(Snippet, Vec<&str>)> = None;
+
+  for line in contents.lines() {
+    if let Some((mut snippet, mut body)) = current.take() {
+      if line.trim_end() == "endsnippet" {
+        snippet.body = body.join("\n");
+        snippets.push((languages.clone(), snippet));
+      } else {
+        body.push(line);
+        current = Some((snippet, body));
+      }
+
+      continue;
+    }
+
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+      continue;
+    }
+
+    if let Some(section) = trimmed
+      .strip_prefix('[')
+      .and_then(|rest| rest.strip_suffix(']'))
+    {
+      languages = section
+        .split(',')
+        .map(|language| language.trim().trim_start_matches('.').to_owned())
+        .filter(|language| !language.is_empty())
+        .collect();
+    } else if let Some(header) = trimmed.strip_prefix("snippet ") {
+      let header = header.trim();
+      let (prefix, description) = header
+        .split_once(char::is_whitespace)
+        .unwrap_or((header, ""));
+
+      current = Some((
+        Snippet {
+          prefix: prefix.to_owned(),
+          description: description.trim().trim_matches('"').to_owned(),
+          body: String::new(),
+        },
+        Vec::new(),
+      ));
+    }
+  }
+
+  snippets
+}
+
+pub struct Variables {
+  pub date: String,
+  pub filename: String,
+  pub selection: String,
+}
+
+impl Variables {
+  fn get(&self, name: &str) -> Option<&str> {
+    match name {
+      "DATE" => Some(&self.date),
+      "FILENAME" => Some(&self.filename),
+      "SELECTION" => Some(&self.selection),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+  Text(String),
+  Stop(usize, Vec<Token>),
+  Variable(String, Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+  pub text: String,
+  pub stops: Vec<Vec<Range<usize>>>,
+}
+
+pub fn expand(body: &str, variables: &Variables, indentation: &str) -> Expansion {
+  let chars: Vec<char> = body.chars().collect();
+  let mut index = 0;
+  let tokens = tokenize(&chars, &mut index, false);
+
+  let mut defaults = HashMap::new();
+  collect_defaults(&tokens, &mut defaults);
+
+  let mut renderer = Renderer {
+    variables,
+    indentation,
+    defaults: &defaults,
+    text: String::new(),
+    stops: HashMap::new(),
+  };
+
+  renderer.render(&tokens);
+
+  let Renderer {
+    text, mut stops, ..
+  } = renderer;
+
+  let end = text.len();
+  let last = stops
+    .remove(&0)
+    .unwrap_or_else(|| std::iter::once(end..end).collect());
+  let mut numbers: Vec<usize> = stops.keys().copied().collect();
+  numbers.sort_unstable();
+
+  let mut ordered: Vec<Vec<Range<usize>>> = numbers
+    .into_iter()
+    .filter_map(|number| stops.remove(&number))
+    .collect();
+
+  ordered.push(last);
+
+  Expansion {
+    text,
+    stops: ordered,
+  }
+}
+
+fn tokenize(chars: &[char], index: &mut usize, is_nested: bool) -> Vec<Token> {
+  let mut tokens = Vec::new();
+  let mut text = String::new();
+
+  while let Some(&c) = chars.get(*index) {
+    match c {
+      '\\'
+        if chars
+          .get(*index + 1)
+          .is_some_and(|next| matches!(next, '$' | '}' | '\\')) =>
+      {
+        text.push(chars[*index + 1]);
+        *index += 2;
+      }
+      '}' if is_nested => {
+        *index += 1;
+        break;
+      }
+      '$' => match dollar(chars, index) {
+        Some(token) => {
+          if !text.is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+          }
+
+          tokens.push(token);
+        }
+        None => {
+          text.push('$');
+          *index += 1;
+        }
+      },
+      _ => {
+        text.push(c);
+        *index += 1;
+      }
+    }
+  }
+
+  if !text.is_empty() {
+    tokens.push(Token::Text(text));
+  }
+
+  tokens
+}
+
+fn dollar(chars: &[char], index: &mut usize) -> Option<Token> {
+  let start = *index + 1;
+  let is_braced = chars.get(start) == Some(&'{');
+  let name_start = if is_braced { start + 1 } else { start };
+
+  let is_number = chars.get(name_start)?.is_ascii_digit();
+  let name_end = name_start
+    + chars[name_start..]
+      .iter()
+      .take_while(|c| {
+        if is_number {
+          c.is_ascii_digit()
+        } else {
+          c.is_ascii_uppercase() || **c == '_'
+        }
+      })
+      .count();
+
+  if name_end == name_start {
+    return None;
+  }
+
+  let name: String = chars[name_start..name_end].iter().collect();
+  let mut end = name_end;
+
+  let default = if is_braced {
+    match chars.get(end) {
+      Some('}') => {
+        end += 1;
+        Vec::new()
+      }
+      Some(':') => {
+        end += 1;
+        tokenize(chars, &mut end, true)
+      }
+      _ => return None,
+    }
+  } else {
+    Vec::new()
+  };
+
+  *index = end;
+
+  Some(match name.parse() {
+    Ok(number) if is_number => Token::Stop(number, default),
+    _ => Token::Variable(name, default),
+  })
+}
+
+fn collect_defaults(tokens: &[Token], defaults: &mut HashMap<usize, Vec<Token>>) {
+  for token in tokens {
+    match token {
+      Token::Stop(number, default) if !default.is_empty() => {
+        defaults.entry(*number).or_insert_with(|| default.clone());
+        collect_defaults(default, defaults);
+      }
+      Token::Variable(_, default) => collect_defaults(default, defaults),
+      _ => {}
+    }
+  }
+}
+
+struct Renderer<'a> {
+  variables: &'a Variables,
+  indentation: &'a str,
+  defaults: &'a HashMap<usize, Vec<Token>>,
+  text: String,
+  stops: HashMap<usize, Vec<Range<usize>>>,
+}
+
+impl Renderer<'_> {
+  fn render(&mut self, tokens: &[Token]) {
+    for token in tokens {
+      match token {
+        Token::Text(text) => self.push(text),
+        Token::Stop(number, default) => {
+          let start = self.text.len();
+
+          if !default.is_empty() {
+            self.render(default);
+          } else if let Some(default) = self.defaults.get(number) {
+            let mut mirror = Renderer {
+              stops: HashMap::new(),
+              text: String::new(),
+              ..*self
+            };
+
+            mirror.render(default);
+            self.push_raw(&mirror.text);
+          }
+
+          let range = start..self.text.len();
+          self.stops.entry(*number).or_default().push(range);
+        }
+        Token::Variable(name, default) => match self.variables.get(name) {
+          Some(value) if !value.is_empty() => self.push(value),
+          Some(_) => self.render(default),
+          None => {
+            self.push("$");
+            self.push(name);
+          }
+        },
+      }
+    }
+  }
+
+  fn push(&mut self, text: &str) {
+    for (rank, line) in text.split('\n').enumerate() {
+      if rank > 0 {
+        self.text.push('\n');
+        self.text.push_str(self.indentation);
+      }
+
+      self.text.push_str(line);
+    }
+  }
+
+  fn push_raw(&mut self, text: &str) {
+    self.text.push_str(text);
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+  stops: Vec<Vec<Range<usize>>>,
+  current: usize,
+  length: usize,
+}
+
+impl Session {
+  pub fn new(base: usize, expansion: Expansion, length: usize) -> Self {
+    let stops = expansion
+      .stops
+      .into_iter()
+      .map(|ranges| {
+        ranges
+          .into_iter()
+          .map(|range| range.start + base..range.end + base)
+          .collect()
+      })
+      .collect();
+
+    Session {
+      stops,
+      current: 0,
+      length,
+    }
+  }
+
+  pub fn current(&self) -> &[Range<usize>] {
+    &self.stops[self.current]
+  }
+
+  pub fn is_final(&self) -> bool {
+    self.current + 1 >= self.stops.len()
+  }
+
+  pub fn settle(&mut self, length: usize) -> bool {
+    let delta = length as isize - self.length as isize;
+    let edited = self.stops[self.current].clone();
+    let count = edited.len() as isize;
+
+    if delta % count != 0 {
+      return false;
+    }
+
+    let per_occurrence = delta / count;
+    let shift = |offset: usize, is_end: bool| -> Option<usize> {
+      let before = edited
+        .iter()
+        .filter(|range| {
+          if is_end {
+            range.end <= offset
+          } else {
+            range.end <= offset && range.start < offset
+          }
+        })
+        .count() as isize;
+
+      offset.checked_add_signed(before * per_occurrence)
+    };
+
+    for (number, ranges) in self.stops.iter_mut().enumerate() {
+      for range in ranges.iter_mut() {
+        let (start, end) = if number == self.current {
+          (shift(range.start, false), shift(range.end, true))
+        } else {
+          (shift(range.start, true), shift(range.end, true))
+        };
+
+        let (Some(start), Some(end)) = (start, end) else {
+          return false;
+        };
+
+        *range = start..end.max(start);
+      }
+    }
+
+    self.length = length;
+    true
+  }
+
+  pub fn advance(&mut self, step: isize) {
+    self.current = self
+      .current
+      .saturating_add_signed(step)
+      .min(self.stops.len() - 1);
+  }
+}
+
+pub fn today() -> String {
+  let seconds = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |duration| duration.as_secs());
+
+  let days = (seconds / 86_400) as i64 + 719_468;
+  let era = days.div_euclid(146_097);
+  let day_of_era = days.rem_euclid(146_097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let shifted_month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+  let month = if shifted_month < 10 {
+    shifted_month + 3
+  } else {
+    shifted_month - 9
+  };
+  let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+  format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,784 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{env, iter};
+
+use iced::widget::text_editor::Position;
+
+use crate::blocks;
+use crate::editorconfig::{self, Charset};
+use crate::front_matter;
+use crate::io;
+
+pub const PERSONAL_FILE: &str = "personal.dic";
+const DICTIONARY_DIRECTORY: &str = "dictionaries";
+const MAX_SUGGESTIONS: usize = 8;
+const DEFAULT_TRY: &str = "esianrtolcdugmphbyfvkwzxjq'";
+const URL_PREFIXES: &[&str] = &["http://", "https://", "ftp://", "mailto:", "www."];
+
+type Flag = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Language(pub Option<String>);
+
+impl Display for Language {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.0 {
+      Some(name) => write!(f, "Spelling: {name}"),
+      None => write!(f, "Spelling: off"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Installed {
+  pub name: String,
+  dic: PathBuf,
+  aff: PathBuf,
+}
+
+pub fn installed() -> Vec<Installed> {
+  let mut directories: Vec<PathBuf> = env::var_os("DICPATH")
+    .map(|paths| env::split_paths(&paths).collect())
+    .unwrap_or_default();
+
+  directories.extend(
+    [
+      "/usr/share/hunspell",
+      "/usr/share/myspell",
+      "/usr/share/myspell/dicts",
+      "/usr/local/share/hunspell",
+      "/Library/Spelling",
+    ]
+    .map(PathBuf::from),
+  );
+
+  if let Some(home) = env::var_os("HOME") {
+    let home = PathBuf::from(home);
+    directories.push(home.join(".local/share/hunspell"));
+    directories.push(home.join("Library/Spelling"));
+  }
+
+  directories.extend(io::config_path(DICTIONARY_DIRECTORY));
+
+  let mut installed: Vec<Installed> = Vec::new();
+
+  for directory in directories {
+    let Ok(entries) = fs::read_dir(&directory) else {
+      continue;
+    };
+
+    for entry in entries.flatten() {
+      let dic = entry.path();
+
+      if dic.extension().is_none_or(|extension| extension != "dic") {
+        continue;
+      }
+
+      let aff = dic.with_extension("aff");
+      let name = dic
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+      if aff.is_file() && installed.iter().all(|other| other.name != name) {
+        installed.push(Installed { name, dic, aff });
+      }
+    }
+  }
+
+  installed.sort_by(|a, b| a.name.cmp(&b.name));
+  installed
+}
+
+pub fn system_language(installed: &[Installed]) -> Option<String> {
+  let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+    .iter()
+    .filter_map(|name| env::var(name).ok())
+    .find(|value| !value.is_empty())
+    .unwrap_or_default();
+
+  let locale = locale.split(['.', '@']).next().unwrap_or_default();
+  let language = locale.split('_').next().unwrap_or_default();
+
+  installed
+    .iter()
+    .find(|dictionary| dictionary.name == locale)
+    .or_else(|| {
+      installed
+        .iter()
+        .find(|dictionary| !language.is_empty() && dictionary.name.starts_with(language))
+    })
+    .or_else(|| {
+      installed
+        .iter()
+        .find(|dictionary| dictionary.name.starts_with("en"))
+    })
+    .or(installed.first())
+    .map(|dictionary| dictionary.name.clone())
+}
+
+pub fn load_personal() -> HashSet<String> {
+  io::config_path(PERSONAL_FILE)
+    .and_then(|path| fs::read_to_string(path).ok())
+    .map(|contents| {
+      contents
+        .lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+pub fn serialize_personal(words: &HashSet<String>) -> String {
+  let mut words: Vec<&String> = words.iter().collect();
+  words.sort();
+
+  words.into_iter().map(|word| format!("{word}\n")).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagType {
+  Char,
+  Long,
+  Numeric,
+}
+
+impl FlagType {
+  fn parse(&self, flags: &str) -> Vec<Flag> {
+    match self {
+      FlagType::Char => flags.chars().map(Flag::from).collect(),
+      FlagType::Long => flags
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .map(|pair| pair.iter().fold(0, |flag, c| (flag << 16) | Flag::from(*c)))
+        .collect(),
+      FlagType::Numeric => flags
+        .split(',')
+        .filter_map(|flag| flag.trim().parse().ok())
+        .collect(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+  Any,
+  Char(char),
+  Set(Vec<char>, bool),
+}
+
+#[derive(Debug, Clone)]
+struct Affix {
+  flag: Flag,
+  cross: bool,
+  strip: String,
+  append: String,
+  conditions: Vec<Condition>,
+}
+
+impl Condition {
+  fn test(&self, c: char) -> bool {
+    match self {
+      Condition::Any => true,
+      Condition::Char(expected) => c == *expected,
+      Condition::Set(set, is_negated) => set.contains(&c) != *is_negated,
+    }
+  }
+}
+
+impl Affix {
+  fn strip_prefix(&self, word: &str) -> Option<String> {
+    let rest = word.strip_prefix(self.append.as_str())?;
+    let root = format!("{}{rest}", self.strip);
+
+    let matches = root.chars().count() >= self.conditions.len()
+      && self
+        .conditions
+        .iter()
+        .zip(root.chars())
+        .all(|(condition, c)| condition.test(c));
+
+    (!root.is_empty() && matches).then_some(root)
+  }
+
+  fn strip_suffix(&self, word: &str) -> Option<String> {
+    let rest = word.strip_suffix(self.append.as_str())?;
+    let root = format!("{rest}{}", self.strip);
+
+    let matches = root.chars().count() >= self.conditions.len()
+      && self
+        .conditions
+        .iter()
+        .rev()
+        .zip(root.chars().rev())
+        .all(|(condition, c)| condition.test(c));
+
+    (!root.is_empty() && matches).then_some(root)
+  }
+}
+
+fn parse_conditions(condition: &str) -> Vec<Condition> {
+  if condition == "." {
+    return Vec::new();
+  }
+
+  let mut conditions = Vec::new();
+  let mut chars = condition.chars();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '.' => conditions.push(Condition::Any),
+      '[' => {
+        let mut set: Vec<char> = chars.by_ref().take_while(|c| *c != ']').collect();
+        let is_negated = set.first() == Some(&'^');
+
+        if is_negated {
+          set.remove(0);
+        }
+
+        conditions.push(Condition::Set(set, is_negated));
+      }
+      _ => conditions.push(Condition::Char(c)),
+    }
+  }
+
+  conditions
+}
+
+#[derive(Debug, Clone)]
+pub struct Loaded {
+  pub language: String,
+  pub dictionary: Option<Dictionary>,
+}
+
+pub async fn installed_in_background() -> Vec<Installed> {
+  tokio::task::spawn_blocking(installed)
+    .await
+    .unwrap_or_default()
+}
+
+pub async fn load_in_background(installed: Installed) -> Loaded {
+  let language = installed.name.clone();
+  let dictionary = tokio::task::spawn_blocking(move || Dictionary::load(&installed))
+    .await
+    .ok()
+    .flatten();
+
+  Loaded {
+    language,
+    dictionary,
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+  words: HashMap<String, Vec<Flag>>,
+  prefixes: Vec<Affix>,
+  suffixes: Vec<Affix>,
+  replacements: Vec<(String, String)>,
+  try_chars: Vec<char>,
+  forbidden: Option<Flag>,
+  need_affix: Option<Flag>,
+  no_suggest: Option<Flag>,
+  cache: HashMap<String, bool>,
+}
+
+impl Dictionary {
+  pub fn load(installed: &Installed) -> Option<Self> {
+    let aff = read(&installed.aff, None)?;
+    let charset = aff
+      .lines()
+      .find_map(|line| line.strip_prefix("SET "))
+      .map(str::trim)
+      .and_then(|set| match set.to_ascii_uppercase().as_str() {
+        "UTF-8" => None,
+        _ => Some(Charset::Latin1),
+      });
+
+    let aff = match charset {
+      Some(charset) => read(&installed.aff, Some(charset))?,
+      None => aff,
+    };
+    let dic = read(&installed.dic, charset)?;
+
+    Some(Self::parse(&aff, &dic))
+  }
+
+  fn parse(aff: &str, dic: &str) -> Self {
+    let mut dictionary = Dictionary::default();
+    let mut flag_type = FlagType::Char;
+    let mut cross_products: HashMap<Flag, bool> = HashMap::new();
+
+    for line in aff.lines() {
+      let fields: Vec<&str> = line.split_whitespace().collect();
+
+      match fields.as_slice() {
+        ["FLAG", kind, ..] => {
+          flag_type = match *kind {
+            "long" => FlagType::Long,
+            "num" => FlagType::Numeric,
+            _ => FlagType::Char,
+          };
+        }
+        ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+        ["REP", from, to, ..] => dictionary
+          .replacements
+          .push((from.replace('_', " "), to.replace('_', " "))),
+        ["FORBIDDENWORD", flag, ..] => {
+          dictionary.forbidden = flag_type.parse(flag).first().copied()
+        }
+        ["NEEDAFFIX", flag, ..] => dictionary.need_affix = flag_type.parse(flag).first().copied(),
+        ["NOSUGGEST", flag, ..] => dictionary.no_suggest = flag_type.parse(flag).first().copied(),
+        [kind @ ("PFX" | "SFX"), flag, strip, append, condition, ..] => {
+          let Some(&flag) = flag_type.parse(flag).first() else {
+            continue;
+          };
+
+          let append = append.split('/').next().unwrap_or_default();
+          let affix = Affix {
+            flag,
+            cross: cross_products.get(&flag).copied().unwrap_or(true),
+            strip: if *strip == "0" { "" } else { strip }.to_owned(),
+            append: if append == "0" { "" } else { append }.to_owned(),
+            conditions: parse_conditions(condition),
+          };
+
+          if *kind == "PFX" {
+            dictionary.prefixes.push(affix);
+          } else {
+            dictionary.suffixes.push(affix);
+          }
+        }
+        ["PFX" | "SFX", flag, cross, _] => {
+          for flag in flag_type.parse(flag) {
+            cross_products.insert(flag, *cross == "Y");
+          }
+        }
+        _ => {}
+      }
+    }
+
+    for line in dic.lines().skip(1) {
+      let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+
+      if entry.is_empty() {
+        continue;
+      }
+
+      let (word, flags) = match entry.split_once('/') {
+        Some((word, flags)) => (word, flag_type.parse(flags)),
+        None => (entry, Vec::new()),
+      };
+
+      dictionary
+        .words
+        .entry(word.to_owned())
+        .or_default()
+        .extend(flags);
+    }
+
+    dictionary
+  }
+
+  pub fn check(&mut self, word: &str) -> bool {
+    if let Some(&is_correct) = self.cache.get(word) {
+      return is_correct;
+    }
+
+    let is_correct = case_variants(word)
+      .iter()
+      .any(|variant| self.lookup(variant));
+    self.cache.insert(word.to_owned(), is_correct);
+    is_correct
+  }
+
+  pub fn suggest(&mut self, word: &str) -> Vec<String> {
+    let lower = word.to_lowercase();
+    let mut candidates: Vec<String> = Vec::new();
+
+    for (from, to) in &self.replacements {
+      for (index, _) in lower.match_indices(from.as_str()) {
+        candidates.push(format!(
+          "{}{to}{}",
+          &lower[..index],
+          &lower[index + from.len()..]
+        ));
+      }
+    }
+
+    let chars: Vec<char> = lower.chars().collect();
+    let try_chars = if self.try_chars.is_empty() {
+      DEFAULT_TRY.chars().collect()
+    } else {
+      self.try_chars.clone()
+    };
+
+    for index in 0..chars.len() {
+      let mut deleted = chars.clone();
+      deleted.remove(index);
+      candidates.push(deleted.into_iter().collect());
+
+      if index + 1 < chars.len() {
+        let mut swapped = chars.clone();
+        swapped.swap(index, index + 1);
+        candidates.push(swapped.into_iter().collect());
+      }
+    }
+
+    for index in 0..=chars.len() {
+      for &c in &try_chars {
+        if index < chars.len() && chars[index] != c {
+          let mut replaced = chars.clone();
+          replaced[index] = c;
+          candidates.push(replaced.into_iter().collect());
+        }
+
+        let mut inserted = chars.clone();
+        inserted.insert(index, c);
+        candidates.push(inserted.into_iter().collect());
+      }
+    }
+
+    for index in 1..chars.len() {
+      let (left, right): (String, String) = (
+        chars[..index].iter().collect(),
+        chars[index..].iter().collect(),
+      );
+      candidates.push(format!("{left} {right}"));
+    }
+
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+
+    for candidate in candidates {
+      if suggestions.len() >= MAX_SUGGESTIONS || !seen.insert(candidate.clone()) {
+        continue;
+      }
+
+      let is_known = candidate.split(' ').all(|part| self.check(part))
+        && candidate
+          .split(' ')
+          .all(|part| !self.has_flag(part, self.no_suggest));
+
+      if is_known {
+        suggestions.push(match_case(word, &candidate));
+      }
+    }
+
+    suggestions
+  }
+
+  fn has_flag(&self, word: &str, flag: Option<Flag>) -> bool {
+    flag.is_some_and(|flag| {
+      self
+        .words
+        .get(word)
+        .is_some_and(|flags| flags.contains(&flag))
+    })
+  }
+
+  fn root_has(&self, root: &str, flags: &[Flag]) -> bool {
+    self.words.get(root).is_some_and(|entry| {
+      flags.iter().all(|flag| entry.contains(flag))
+        && self
+          .forbidden
+          .is_none_or(|forbidden| !entry.contains(&forbidden))
+    })
+  }
+
+  fn lookup(&self, word: &str) -> bool {
+    if let Some(flags) = self.words.get(word) {
+      let is_forbidden = self.forbidden.is_some_and(|flag| flags.contains(&flag));
+      let needs_affix = self.need_affix.is_some_and(|flag| flags.contains(&flag));
+
+      if is_forbidden {
+        return false;
+      }
+
+      if !needs_affix {
+        return true;
+      }
+    }
+
+    let by_suffix = self.suffixes.iter().any(|suffix| {
+      suffix
+        .strip_suffix(word)
+        .is_some_and(|root| self.root_has(&root, &[suffix.flag]))
+    });
+
+    if by_suffix {
+      return true;
+    }
+
+    self.prefixes.iter().any(|prefix| {
+      let Some(root) = prefix.strip_prefix(word) else {
+        return false;
+      };
+
+      if self.root_has(&root, &[prefix.flag]) {
+        return true;
+      }
+
+      prefix.cross
+        && self
+          .suffixes
+          .iter()
+          .filter(|suffix| suffix.cross)
+          .any(|suffix| {
+            suffix
+              .strip_suffix(&root)
+              .is_some_and(|stem| self.root_has(&stem, &[prefix.flag, suffix.flag]))
+          })
+    })
+  }
+}
+
+fn read(path: &Path, charset: Option<Charset>) -> Option<String> {
+  let bytes = fs::read(path).ok()?;
+
+  match charset {
+    Some(charset) => editorconfig::decode(bytes, Some(charset)).ok(),
+    None => Some(String::from_utf8_lossy(&bytes).into_owned()),
+  }
+}
+
+fn case_variants(word: &str) -> Vec<String> {
+  let mut variants = vec![word.to_owned()];
+  let mut chars = word.chars();
+
+  let Some(first) = chars.next() else {
+    return variants;
+  };
+
+  let rest: String = chars.collect();
+  let lower = word.to_lowercase();
+
+  if word.chars().all(|c| !c.is_lowercase()) {
+    variants.push(first.to_string() + &rest.to_lowercase());
+    variants.push(lower);
+  } else if first.is_uppercase() && rest.chars().all(|c| !c.is_uppercase()) {
+    variants.push(lower);
+  }
+
+  variants
+}
+
+fn match_case(original: &str, suggestion: &str) -> String {
+  let is_upper = original.chars().count() > 1 && original.chars().all(|c| !c.is_lowercase());
+  let is_capitalized = original.chars().next().is_some_and(char::is_uppercase);
+
+  if is_upper {
+    return suggestion.to_uppercase();
+  }
+
+  let mut chars = suggestion.chars();
+
+  match chars.next() {
+    Some(first) if is_capitalized => first.to_uppercase().chain(chars).collect(),
+    _ => suggestion.to_owned(),
+  }
+}
+
+pub fn words(line: &str, skipped: &[Range<usize>]) -> Vec<Range<usize>> {
+  let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '\'' | '’');
+  let mut words = Vec::new();
+  let mut start = None;
+
+  for (index, c) in line.char_indices().chain(iter::once((line.len(), ' '))) {
+    match (start, is_word_char(c)) {
+      (None, true) => start = Some(index),
+      (Some(from), false) => {
+        start = None;
+
+        let token = &line[from..index];
+        let trimmed_start = token.len() - token.trim_start_matches(['\'', '’']).len();
+        let trimmed = token.trim_matches(['\'', '’']);
+        let range = from + trimmed_start..from + trimmed_start + trimmed.len();
+
+        let is_checkable = trimmed.chars().count() > 1
+          && trimmed.chars().all(|c| !c.is_numeric() && c != '_')
+          && !line[index..].starts_with('@')
+          && !line[..from].ends_with(['@', '.', '/', '\\'])
+          && !skipped
+            .iter()
+            .any(|skip| skip.start < range.end && range.start < skip.end);
+
+        if is_checkable {
+          words.push(range);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  words
+}
+
+pub fn markdown_skips(lines: &[String]) -> Vec<Option<Vec<Range<usize>>>> {
+  let mut skips = Vec::with_capacity(lines.len());
+  let front_matter = front_matter::end(lines).unwrap_or(0);
+  let is_code = blocks::code_lines(lines);
+
+  for (index, line) in lines.iter().enumerate() {
+    if index < front_matter || is_code[index] {
+      skips.push(None);
+      continue;
+    }
+
+    skips.push(Some(inline_skips(line)));
+  }
+
+  skips
+}
+
+fn inline_skips(line: &str) -> Vec<Range<usize>> {
+  let mut skips = Vec::new();
+  let bytes = line.as_bytes();
+  let mut index = 0;
+
+  while index < bytes.len() {
+    let rest = &line[index..];
+
+    if rest.starts_with('`') {
+      let run = rest.len() - rest.trim_start_matches('`').len();
+      let fence = &rest[..run];
+
+      if let Some(end) = rest[run..].find(fence) {
+        skips.push(index..index + run + end + run);
+        index += run + end + run;
+        continue;
+      }
+
+      index += run;
+      continue;
+    }
+
+    if rest.starts_with("](")
+      && let Some(end) = rest.find(')')
+    {
+      skips.push(index..index + end + 1);
+      index += end + 1;
+      continue;
+    }
+
+    if rest.starts_with('<')
+      && let Some(end) = rest.find('>')
+    {
+      skips.push(index..index + end + 1);
+      index += end + 1;
+      continue;
+    }
+
+    let is_boundary = line[..index]
+      .chars()
+      .next_back()
+      .is_none_or(|c| !c.is_alphanumeric());
+
+    if is_boundary
+      && URL_PREFIXES.iter().any(|prefix| {
+        rest
+          .get(..prefix.len())
+          .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+      })
+    {
+      let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, ')' | '>' | ']'))
+        .unwrap_or(rest.len());
+
+      skips.push(index..index + end);
+      index += end;
+      continue;
+    }
+
+    index += rest.chars().next().map_or(1, char::len_utf8);
+  }
+
+  skips
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu {
+  pub start: Position,
+  pub end: Position,
+  pub word: String,
+  pub suggestions: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const AFF: &str = "\
+SET UTF-8
+PFX U Y 1
+PFX U 0 un .
+SFX D Y 3
+SFX D 0 d e
+SFX D y ied [^aeiou]y
+SFX D 0 ed [^ey]
+SFX N Y 1
+SFX N y iness [^aeiou]y
+";
+
+  const DIC: &str = "\
+4
+create/D
+try/D
+walk/D
+happy/UN
+";
+
+  #[test]
+  fn strips_suffixes_that_meet_their_conditions() {
+    let mut dictionary = Dictionary::parse(AFF, DIC);
+
+    assert!(dictionary.check("created"));
+    assert!(dictionary.check("tried"));
+    assert!(dictionary.check("walked"));
+    assert!(!dictionary.check("tryed"));
+    assert!(!dictionary.check("walkd"));
+  }
+
+  #[test]
+  fn combines_prefixes_with_cross_product_suffixes() {
+    let mut dictionary = Dictionary::parse(AFF, DIC);
+
+    assert!(dictionary.check("unhappy"));
+    assert!(dictionary.check("unhappiness"));
+    assert!(!dictionary.check("unwalked"));
+  }
+
+  #[test]
+  fn accepts_capitalised_words() {
+    let mut dictionary = Dictionary::parse(AFF, DIC);
+
+    assert!(dictionary.check("Walked"));
+    assert!(dictionary.check("TRIED"));
+  }
+
+  #[test]
+  fn reads_long_flags() {
+    let mut dictionary = Dictionary::parse("FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n", "1\ncat/Aa\n");
+
+    assert!(dictionary.check("cats"));
+    assert!(!dictionary.check("cat's"));
+  }
+
+  #[test]
+  fn skips_code_spans_and_urls_next_to_multibyte_text() {
+    let line = "naïve `cöde` see https://example.com/é ok";
+    let skipped = inline_skips(line);
+    let words: Vec<&str> = words(line, &skipped)
+      .into_iter()
+      .map(|range| &line[range])
+      .collect();
+
+    assert_eq!(words, ["naïve", "see", "ok"]);
+  }
+}
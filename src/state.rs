@@ -1,16 +1,32 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use iced::widget::text_editor;
-use iced::window;
+use iced::widget::{scrollable, text_editor};
+use iced::{keyboard, window};
 
-use crate::message::{FileAction, ViewAction};
-use crate::{constants, file};
+use crate::blocks;
+use crate::completion::{self, Completion};
+use crate::components::editor::{self, Popup};
+use crate::components::gutter::{self, Numbering};
+use crate::components::preview::{self, Layout};
+use crate::editorconfig::Properties;
+use crate::macros::{self, Macro, Prompt};
+use crate::message::{
+  CompletionAction, EditAction, FileAction, GutterTarget, LineAction, TableAction, TransformAction,
+  ViewAction,
+};
+use crate::message::{MacroAction, MacroItem, Message, Pane, Pointer, ScrollSync, SpellingAction};
+use crate::pdf::MarginPrompt;
+use crate::snippets::{self, Snippet};
+use crate::spelling::{self, Dictionary, Installed, Language};
+use crate::{constants, cursors, file, images, indent, outline, whitespace, wrapping};
 
 #[derive(Default, Copy, Clone)]
 pub enum Mode {
   #[default]
   Edit,
   Preview,
+  Split(f32),
 }
 
 #[derive(Default)]
@@ -21,20 +37,54 @@ pub struct State {
   current_file: usize,
   editor_font_size: u32,
   selected_file_action: Option<FileAction>,
+  selected_edit_action: Option<EditAction>,
+  selected_line_action: Option<LineAction>,
+  selected_transform_action: Option<TransformAction>,
   selected_view_action: Option<ViewAction>,
   is_word_wrap_on: bool,
+  is_line_numbers_on: bool,
+  is_relative_line_numbers_on: bool,
+  is_whitespace_visible: bool,
+  cleanup: whitespace::Cleanup,
+  modifiers: keyboard::Modifiers,
+  gutter_target: Option<GutterTarget>,
+  completion: Option<Completion>,
+  snippets: snippets::Library,
+  recording: Option<Vec<Message>>,
+  recorded: Vec<Message>,
+  macros: Vec<Macro>,
+  macro_prompt: Option<Prompt>,
+  margin_prompt: Option<MarginPrompt>,
+  notice: Option<String>,
+  installed_dictionaries: Vec<Installed>,
+  dictionaries: HashMap<String, Option<Dictionary>>,
+  personal_words: HashSet<String>,
+  spelling_menu: Option<spelling::Menu>,
+  pointer: Option<Pointer>,
+  is_dragging_divider: bool,
+  preview_seen: u64,
+  preview_revision: u64,
+  editor_viewport: Option<gutter::Viewport>,
+  spelling_seen: Option<u64>,
+  spelling_revision: u64,
+  hovered_pane: Option<Pane>,
+  outline: outline::Panel,
+  images: images::Cache,
+  images_seen: u64,
 }
 
 impl State {
   pub fn new(window_id: window::Id) -> Self {
-    let default_file = file::File::default();
-
     Self {
       window_id: Some(window_id),
-      files: vec![default_file],
+      files: vec![file::File::default()],
       current_file: 0,
       editor_font_size: constants::DEFAULT_EDITOR_FONT_SIZE,
       is_word_wrap_on: false,
+      is_line_numbers_on: true,
+      snippets: snippets::Library::load(),
+      macros: macros::load(),
+      personal_words: spelling::load_personal(),
       ..Default::default()
     }
   }
@@ -44,17 +94,601 @@ impl State {
   }
 
   pub fn apply_edit(&mut self, action: text_editor::Action) {
-    self.files[self.current_file]
-      .content_mut()
-      .perform(action.clone());
+    let file = &mut self.files[self.current_file];
+    let is_edit = action.is_edit();
+
+    let completes = match &action {
+      text_editor::Action::Edit(text_editor::Edit::Insert(c)) => cursors::is_word_char(*c),
+      text_editor::Action::Edit(text_editor::Edit::Backspace) => self.completion.is_some(),
+      _ => false,
+    };
+    let keeps_completion = matches!(action, text_editor::Action::Scroll { .. });
+
+    match action {
+      text_editor::Action::Click(_) if self.modifiers.alt() => file.add_cursor_at(action),
+      text_editor::Action::Drag(_) if self.modifiers.alt() => file.drag_block(action),
+      _ => file.perform(action),
+    }
+
+    if is_edit {
+      file.set_needs_saving(true);
+    }
+
+    if completes {
+      self.complete(false);
+    } else if !keeps_completion {
+      self.completion = None;
+    }
+
+    if !keeps_completion {
+      self.spelling_menu = None;
+    }
+  }
+
+  pub fn complete(&mut self, is_forced: bool) {
+    for file in &mut self.files {
+      file.update_words();
+    }
+
+    let file = &mut self.files[self.current_file];
+
+    if file.is_markdown() {
+      file.update_code_lines();
+    }
+
+    let file = &self.files[self.current_file];
+    self.completion = completion::complete(file, &self.files, is_forced);
+    self.spelling_menu = None;
+  }
+
+  pub fn apply_completion_action(&mut self, action: CompletionAction) {
+    let Some(completion) = &mut self.completion else {
+      return;
+    };
+
+    match action {
+      CompletionAction::Next => completion.select_next(),
+      CompletionAction::Previous => completion.select_previous(),
+      CompletionAction::Accept => self.accept_completion(),
+      CompletionAction::Pick(index) => {
+        completion.selected = index;
+        self.accept_completion();
+      }
+      CompletionAction::Dismiss => self.completion = None,
+    }
+  }
+
+  pub fn accept_completion(&mut self) {
+    let Some(completion) = self.completion.take() else {
+      return;
+    };
+
+    let file = &mut self.files[self.current_file];
+    let end = file.content().cursor().position;
+
+    file.replace(completion.start, end, completion.selected_item());
+    file.set_needs_saving(true);
+  }
+
+  pub fn record(&mut self, message: &Message) {
+    if let Some(steps) = &mut self.recording
+      && macros::is_recordable(message)
+    {
+      steps.push(message.clone());
+    }
+  }
+
+  pub fn toggle_recording(&mut self) {
+    match self.recording.take() {
+      Some(steps) if !steps.is_empty() => self.recorded = steps,
+      Some(_) => {}
+      None => self.recording = Some(Vec::new()),
+    }
+  }
+
+  pub fn is_recording(&self) -> bool {
+    self.recording.is_some()
+  }
+
+  pub fn recorded_macro(&self) -> Vec<Message> {
+    self.recorded.clone()
+  }
+
+  pub fn saved_macro(&self, slot: usize) -> Vec<Message> {
+    self
+      .macros
+      .iter()
+      .find(|recording| recording.slot == slot)
+      .map(|recording| recording.steps.clone())
+      .unwrap_or_default()
+  }
+
+  pub fn save_macro(&mut self, name: String) -> Option<String> {
+    if self.recorded.is_empty() || name.is_empty() {
+      return None;
+    }
+
+    let slot = match self.macros.iter().position(|other| other.name == name) {
+      Some(index) => self.macros.remove(index).slot,
+      None => macros::free_slot(&self.macros)?,
+    };
+
+    self.macros.push(Macro {
+      name,
+      slot,
+      steps: self.recorded.clone(),
+    });
+    self.macros.sort_by_key(|recording| recording.slot);
+
+    Some(macros::serialize(&self.macros))
+  }
+
+  pub fn macro_items(&self) -> Vec<MacroItem> {
+    let actions = MacroAction::ALL.iter().map(|action| MacroItem {
+      action: *action,
+      label: action.to_string(),
+    });
+
+    let saved = self.macros.iter().map(|recording| MacroItem {
+      action: MacroAction::PlaySaved(recording.slot),
+      label: format!("Play {} (Ctrl+Alt+{})", recording.name, recording.slot),
+    });
+
+    actions.chain(saved).collect()
+  }
+
+  pub fn open_macro_prompt(&mut self, prompt: Prompt) {
+    self.macro_prompt = Some(prompt);
+  }
+
+  pub fn set_macro_prompt_value(&mut self, value: String) {
+    if let Some(prompt) = &mut self.macro_prompt {
+      prompt.value = value;
+    }
+  }
+
+  pub fn take_macro_prompt(&mut self) -> Option<Prompt> {
+    self.macro_prompt.take()
+  }
+
+  pub fn macro_prompt(&self) -> Option<&Prompt> {
+    self.macro_prompt.as_ref()
+  }
+
+  pub fn open_margin_prompt(&mut self, prompt: MarginPrompt) {
+    self.margin_prompt = Some(prompt);
+  }
+
+  pub fn set_margin_prompt_value(&mut self, value: String) {
+    if let Some(prompt) = &mut self.margin_prompt {
+      prompt.value = value;
+    }
+  }
+
+  pub fn close_margin_prompt(&mut self) {
+    self.margin_prompt = None;
+  }
+
+  pub fn margin_prompt(&self) -> Option<&MarginPrompt> {
+    self.margin_prompt.as_ref()
+  }
+
+  pub fn set_notice(&mut self, notice: Option<String>) {
+    self.notice = notice;
+  }
+
+  pub fn notice(&self) -> Option<&str> {
+    self.notice.as_deref()
+  }
+
+  pub fn begin_playback(&mut self) {
+    self.files[self.current_file].begin_transaction();
+  }
+
+  pub fn end_playback(&mut self) {
+    self.files[self.current_file].end_transaction();
+  }
+
+  pub fn interrupt_input(&mut self) {
+    self.completion = None;
+    self.spelling_menu = None;
+    self.files[self.current_file].end_snippet();
+  }
+
+  pub fn insert_snippet(&mut self, snippet: &Snippet) {
+    let file = &mut self.files[self.current_file];
+    file.insert_snippet(snippet);
+    file.set_needs_saving(true);
+  }
+
+  pub fn snippets(&self) -> &[Snippet] {
+    self.snippets.for_language(self.active_file().extension())
+  }
+
+  pub fn is_completing(&self) -> bool {
+    self.completion.is_some()
+  }
+
+  pub fn popup(&self) -> Option<Popup<'_>> {
+    self
+      .completion
+      .as_ref()
+      .map(Popup::Completion)
+      .or(self.spelling_menu.as_ref().map(Popup::Spelling))
+  }
+
+  pub fn schedule_spelling(&mut self) -> Option<u64> {
+    let file = &self.files[self.current_file];
+
+    if !file.needs_spelling() || self.spelling_seen == Some(file.revision()) {
+      return None;
+    }
+
+    self.spelling_seen = Some(file.revision());
+    self.spelling_revision += 1;
+
+    Some(self.spelling_revision)
+  }
+
+  pub fn check_spelling(&mut self, revision: u64) -> Option<Installed> {
+    if revision != self.spelling_revision {
+      return None;
+    }
+
+    self.spelling_seen = None;
+
+    let file = &mut self.files[self.current_file];
+    let mut requested = None;
+
+    if let Some(language) = file.spell_language()
+      && !self.dictionaries.contains_key(language)
+    {
+      self.dictionaries.insert(language.to_owned(), None);
+      requested = self
+        .installed_dictionaries
+        .iter()
+        .find(|installed| installed.name == language)
+        .cloned();
+    }
+
+    let dictionary = file
+      .spell_language()
+      .and_then(|language| self.dictionaries.get_mut(language))
+      .and_then(Option::as_mut);
+
+    file.check_spelling(dictionary, &self.personal_words);
+    requested
+  }
+
+  pub fn apply_dictionary(&mut self, loaded: spelling::Loaded) {
+    for file in &mut self.files {
+      if file.spell_language() == Some(loaded.language.as_str()) {
+        file.invalidate_spelling();
+      }
+    }
+
+    self.dictionaries.insert(loaded.language, loaded.dictionary);
+  }
+
+  pub fn set_installed_dictionaries(&mut self, installed: Vec<Installed>) {
+    self.installed_dictionaries = installed;
+
+    for index in 0..self.files.len() {
+      if self.files[index].spell_language().is_none()
+        && let Some(language) = self.default_spell_language(&self.files[index])
+      {
+        self.files[index].set_spell_language(Some(language));
+      }
+    }
+  }
+
+  pub fn spell_languages(&self) -> Vec<Language> {
+    std::iter::once(Language(None))
+      .chain(
+        self
+          .installed_dictionaries
+          .iter()
+          .map(|installed| Language(Some(installed.name.clone()))),
+      )
+      .collect()
+  }
+
+  pub fn set_spell_language(&mut self, language: Language) {
+    self.files[self.current_file].set_spell_language(language.0);
+    self.spelling_menu = None;
+  }
+
+  pub fn set_pointer(&mut self, pointer: Pointer) {
+    self.pointer = Some(pointer);
+  }
+
+  pub fn open_spelling_menu(&mut self, position: text_editor::Position) {
+    self.completion = None;
+    self.spelling_menu = None;
+
+    let file = &self.files[self.current_file];
+
+    let Some((start, end, word)) = file.misspelling_at(position) else {
+      return;
+    };
+
+    let suggestions = file
+      .spell_language()
+      .and_then(|language| self.dictionaries.get_mut(language))
+      .and_then(Option::as_mut)
+      .map(|dictionary| dictionary.suggest(&word))
+      .unwrap_or_default();
+
+    self.spelling_menu = Some(spelling::Menu {
+      start,
+      end,
+      word,
+      suggestions,
+    });
+  }
+
+  pub fn apply_spelling_action(&mut self, action: SpellingAction) -> Option<String> {
+    match action {
+      SpellingAction::Open => {
+        let position = self.files[self.current_file].content().cursor().position;
+        self.open_spelling_menu(position);
+      }
+      SpellingAction::OpenAtPointer => {
+        if let Some(pointer) = self.pointer {
+          let file = &self.files[self.current_file];
+          let position = wrapping::position_at(
+            file.content(),
+            pointer.row,
+            pointer.column,
+            pointer.columns,
+            file.tab_width(),
+          );
+
+          self.open_spelling_menu(position);
+        }
+      }
+      SpellingAction::Replace(index) => {
+        let menu = self.spelling_menu.take()?;
+        let suggestion = menu.suggestions.get(index)?;
+        let file = &mut self.files[self.current_file];
+
+        file.replace(menu.start, menu.end, suggestion);
+        file.set_needs_saving(true);
+      }
+      SpellingAction::AddToDictionary => {
+        let menu = self.spelling_menu.take()?;
+        self.personal_words.insert(menu.word);
+
+        for file in &mut self.files {
+          file.invalidate_spelling();
+        }
+
+        return Some(spelling::serialize_personal(&self.personal_words));
+      }
+      SpellingAction::IgnoreInFile => {
+        let menu = self.spelling_menu.take()?;
+        self.files[self.current_file].ignore_word(menu.word);
+      }
+      SpellingAction::Dismiss => self.spelling_menu = None,
+    }
+
+    None
+  }
+
+  pub fn set_modifiers(&mut self, modifiers: keyboard::Modifiers) {
+    self.modifiers = modifiers;
+  }
+
+  pub fn add_cursor_above(&mut self) {
+    self.files[self.current_file].add_cursor_vertically(-1);
+  }
+
+  pub fn add_cursor_below(&mut self) {
+    self.files[self.current_file].add_cursor_vertically(1);
+  }
+
+  pub fn add_next_occurrence(&mut self) {
+    self.files[self.current_file].add_next_occurrence();
+  }
+
+  pub fn extend_column_selection(&mut self, lines: isize, columns: isize) {
+    self.files[self.current_file].extend_block(lines, columns);
+  }
+
+  pub fn undo(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    if file.undo() {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn redo(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    if file.redo() {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn indent_selection(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    if file.jump_tab_stop(1) {
+      return;
+    }
+
+    let is_changed = if file.trigger_snippet(&self.snippets) {
+      true
+    } else if let Some(is_changed) = file.apply_table_action(TableAction::NextCell) {
+      is_changed
+    } else {
+      file.indent_selection();
+      true
+    };
+
+    if is_changed {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn outdent_selection(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    if file.jump_tab_stop(-1) {
+      return;
+    }
+
+    let is_changed = match file.apply_table_action(TableAction::PreviousCell) {
+      Some(is_changed) => is_changed,
+      None => {
+        file.outdent_selection();
+        true
+      }
+    };
+
+    if is_changed {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn convert_indentation(&mut self, style: indent::Style) {
+    let file = &mut self.files[self.current_file];
+    file.convert_indentation(style);
+    file.set_needs_saving(true);
+  }
+
+  pub fn jump_to_bracket(&mut self) {
+    self.files[self.current_file].jump_to_bracket();
+  }
 
-    if matches!(action, text_editor::Action::Edit(_)) {
-      self.files[self.current_file].set_needs_saving(true);
+  pub fn toggle_line_comment(&mut self) {
+    let file = &mut self.files[self.current_file];
+    file.toggle_line_comment();
+    file.set_needs_saving(true);
+  }
+
+  pub fn toggle_block_comment(&mut self) {
+    let file = &mut self.files[self.current_file];
+    file.toggle_block_comment();
+    file.set_needs_saving(true);
+  }
+
+  pub fn apply_line_action(&mut self, action: LineAction) {
+    let file = &mut self.files[self.current_file];
+    file.apply_line_action(action);
+    file.set_needs_saving(true);
+  }
+
+  pub fn apply_transform(&mut self, action: TransformAction) {
+    let file = &mut self.files[self.current_file];
+
+    if file.apply_transform(action) {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn apply_table_action(&mut self, action: TableAction) {
+    let file = &mut self.files[self.current_file];
+
+    if file.apply_table_action(action) == Some(true) {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn fold(&mut self) {
+    self.files[self.current_file].fold();
+  }
+
+  pub fn unfold(&mut self) {
+    self.files[self.current_file].unfold();
+  }
+
+  pub fn fold_level(&mut self, level: usize) {
+    self.files[self.current_file].fold_level(level);
+  }
+
+  pub fn unfold_all(&mut self) {
+    self.files[self.current_file].unfold_all();
+  }
+
+  pub fn outline(&self) -> &outline::Panel {
+    &self.outline
+  }
+
+  pub fn toggle_outline(&mut self) {
+    self.outline.toggle();
+  }
+
+  pub fn update_outline(&mut self) {
+    if self.outline.is_visible() {
+      self.files[self.current_file].update_outline();
+    }
+  }
+
+  pub fn update_fold_regions(&mut self) {
+    self.files[self.current_file].update_fold_regions();
+  }
+
+  pub fn update_front_matter(&mut self) {
+    for file in &mut self.files {
+      file.update_front_matter();
+    }
+  }
+
+  pub fn set_outline_filter(&mut self, filter: String) {
+    self.outline.set_filter(filter);
+  }
+
+  pub fn set_outline_depth(&mut self, depth: outline::Depth) {
+    self.outline.set_depth(depth);
+  }
+
+  pub fn toggle_outline_collapsed(&mut self, index: usize) {
+    if let Some(heading) = self.files[self.current_file].outline().get(index) {
+      self.outline.toggle_collapsed(heading);
+    }
+  }
+
+  pub fn jump_to_heading(&mut self, line: usize) {
+    self.completion = None;
+    self.spelling_menu = None;
+    self.files[self.current_file].move_to_source_line(line);
+  }
+
+  pub fn set_gutter_target(&mut self, target: Option<GutterTarget>) {
+    self.gutter_target = target;
+  }
+
+  pub fn press_gutter(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    match self.gutter_target {
+      Some(GutterTarget::LineNumber(line)) => file.select_line(line),
+      Some(GutterTarget::FoldMarker(line)) => file.toggle_fold(line),
+      None => {}
     }
   }
 
+  pub fn clear_cursors(&mut self) {
+    self.files[self.current_file].clear_cursors();
+  }
+
+  pub fn active_selection(&self) -> Option<String> {
+    self.files[self.current_file].selections()
+  }
+
+  pub fn delete_active_selection(&mut self) {
+    let file = &mut self.files[self.current_file];
+    file.delete_selections();
+    file.set_needs_saving(true);
+  }
+
   pub fn new_file(&mut self) {
-    self.files.push(file::File::default());
+    let mut new_file = file::File::default();
+    new_file.set_spell_language(self.default_spell_language(&new_file));
+
+    self.files.push(new_file);
     self.current_file = self.files.len() - 1;
   }
 
@@ -76,35 +710,85 @@ impl State {
     false
   }
 
-  pub fn open_file(&mut self, path: PathBuf, content: String) {
+  pub fn open_file(&mut self, path: PathBuf, content: String, properties: Properties) {
     if let Some(index) = self.files.iter().position(|f| f.path() == Some(&path)) {
       self.files[index].set_content(&content);
+      self.files[index].set_editorconfig(properties);
       self.files[index].set_needs_saving(false);
       self.current_file = index;
       return;
     }
 
-    let opened_file = file::File::from(&content, Some(path));
+    let mut opened_file = file::File::from(&content, Some(path));
+    opened_file.set_editorconfig(properties);
+    opened_file.set_spell_language(self.default_spell_language(&opened_file));
 
     self.files.push(opened_file);
     self.current_file = self.files.len() - 1;
   }
 
+  fn default_spell_language(&self, file: &file::File) -> Option<String> {
+    let is_prose = file.is_markdown() || matches!(file.extension(), None | Some("txt"));
+
+    if is_prose {
+      spelling::system_language(&self.installed_dictionaries)
+    } else {
+      None
+    }
+  }
+
   pub fn switch_tab(&mut self, index: usize) {
     if index < self.files.len() {
       self.current_file = index;
     }
   }
 
-  pub fn active_file_data(&self) -> (Option<&PathBuf>, String) {
+  pub fn toggle_cleanup(&mut self, option: impl FnOnce(&mut whitespace::Cleanup) -> &mut bool) {
+    let enabled = option(&mut self.cleanup);
+    *enabled = !*enabled;
+  }
+
+  pub fn clean_up_whitespace(&mut self) {
+    let file = &mut self.files[self.current_file];
+
+    if file.clean_up_whitespace(self.cleanup) {
+      file.set_needs_saving(true);
+    }
+  }
+
+  pub fn active_file_path(&self) -> Option<PathBuf> {
+    self.files[self.current_file].path().cloned()
+  }
+
+  pub fn encode_active_file(&self) -> Result<Vec<u8>, String> {
+    self.files[self.current_file].encoded()
+  }
+
+  pub fn set_active_file_editorconfig(&mut self, properties: Properties) {
+    self.files[self.current_file].set_editorconfig(properties);
+  }
+
+  pub fn export_source(&self) -> (String, String, Option<PathBuf>) {
     let file = &self.files[self.current_file];
-    (file.path(), file.content().text())
+    let path = file.path().map(PathBuf::as_path);
+    let name = path
+      .and_then(|path| path.file_stem())
+      .and_then(|stem| stem.to_str())
+      .unwrap_or("Untitled");
+
+    (
+      name.to_owned(),
+      file.text(),
+      path.and_then(Path::parent).map(Path::to_path_buf),
+    )
   }
 
   pub fn set_active_file_path(&mut self, path: PathBuf) {
     if let Some(file) = self.files.get_mut(self.current_file) {
       file.set_path(Some(path));
     }
+
+    self.images_seen = 0;
   }
 
   pub fn increase_font(&mut self) {
@@ -125,7 +809,7 @@ impl State {
 
   pub fn toggle_preview(&mut self) {
     match self.mode {
-      Mode::Edit => {
+      Mode::Edit | Mode::Split(_) => {
         self.files[self.current_file].update_markdown();
         self.mode = Mode::Preview;
       }
@@ -133,12 +817,299 @@ impl State {
     }
   }
 
+  pub fn toggle_split(&mut self) {
+    match self.mode {
+      Mode::Edit | Mode::Preview => {
+        let file = &mut self.files[self.current_file];
+        file.update_markdown();
+        self.preview_seen = file.revision();
+        self.mode = Mode::Split(constants::DEFAULT_SPLIT_RATIO);
+      }
+      Mode::Split(_) => self.mode = Mode::Edit,
+    }
+  }
+
+  pub fn schedule_preview(&mut self) -> Option<u64> {
+    if !matches!(self.mode, Mode::Split(_)) {
+      return None;
+    }
+
+    let revision = self.files[self.current_file].revision();
+
+    if revision == self.preview_seen {
+      return None;
+    }
+
+    self.preview_seen = revision;
+    self.preview_revision += 1;
+
+    Some(self.preview_revision)
+  }
+
+  pub fn refresh_preview(&mut self, revision: u64) -> Option<blocks::Request> {
+    if revision == self.preview_revision && matches!(self.mode, Mode::Split(_)) {
+      self.files[self.current_file].request_markdown()
+    } else {
+      None
+    }
+  }
+
+  pub fn apply_markdown(&mut self, parsed: blocks::Parsed) {
+    if let Some(file) = self.files.iter_mut().find(|file| file.is_awaiting(&parsed)) {
+      file.apply_markdown(parsed);
+    }
+  }
+
+  pub fn images(&self) -> &images::Cache {
+    &self.images
+  }
+
+  pub fn is_watching_images(&self) -> bool {
+    !matches!(self.mode, Mode::Edit) && self.images.is_watching()
+  }
+
+  pub fn request_images(&mut self) -> Vec<images::Source> {
+    if matches!(self.mode, Mode::Edit) {
+      return Vec::new();
+    }
+
+    let file = &self.files[self.current_file];
+    let revision = file.markdown_revision();
+
+    if revision == self.images_seen {
+      return Vec::new();
+    }
+
+    self.images_seen = revision;
+
+    let directory = file.path().and_then(|path| path.parent());
+    let sources = file
+      .markdown()
+      .iter()
+      .flat_map(|block| &block.images)
+      .filter_map(|url| images::Source::resolve(url, directory));
+
+    self.images.request(sources)
+  }
+
+  pub fn apply_image(&mut self, loaded: images::Loaded) {
+    self.images.apply(loaded);
+  }
+
+  pub fn reload_images(&mut self, paths: Vec<PathBuf>) -> Vec<images::Source> {
+    self.images.reload(paths)
+  }
+
+  pub fn allow_remote_images(&mut self) {
+    self.images.allow_remote();
+    self.images_seen = 0;
+  }
+
+  pub fn toggle_task(&mut self, line: usize, done: bool) {
+    let file = &mut self.files[self.current_file];
+
+    if file.set_task(line, done) {
+      file.set_needs_saving(true);
+      file.update_markdown();
+    }
+  }
+
+  pub fn hover_pane(&mut self, pane: Pane) {
+    self.hovered_pane = Some(pane);
+  }
+
+  pub fn follows(&self, sync: ScrollSync) -> bool {
+    let is_hovered = |pane| self.hovered_pane == Some(pane);
+
+    match sync {
+      ScrollSync::Heading(_) => true,
+      ScrollSync::Editor => self.is_split() && is_hovered(Pane::Editor),
+      ScrollSync::Preview => self.is_split() && is_hovered(Pane::Preview),
+      ScrollSync::Cursor | ScrollSync::Block(_) => self.is_split(),
+    }
+  }
+
+  fn is_split(&self) -> bool {
+    matches!(self.mode, Mode::Split(_))
+  }
+
+  pub fn move_to_block(&mut self, index: usize) {
+    let file = &mut self.files[self.current_file];
+
+    if let Some(line) = file.markdown().get(index).map(|block| block.lines.start) {
+      file.move_to_source_line(line);
+    }
+  }
+
+  pub fn sync_scroll(&self, sync: ScrollSync, layout: &Layout) -> Option<(&'static str, f32)> {
+    let (editor, preview) = (layout.editor?, layout.preview?);
+    let file = &self.files[self.current_file];
+    let line_height = self.editor_font_size as f32 * constants::LINE_HEIGHT;
+    let columns = self.wrap_columns(editor.width);
+
+    let row_top = |row: usize| constants::EDITOR_PADDING + row as f32 * line_height;
+
+    match sync {
+      ScrollSync::Editor | ScrollSync::Cursor => {
+        let row = if sync == ScrollSync::Cursor {
+          wrapping::locate(
+            file.content(),
+            file.content().cursor().position,
+            columns,
+            file.tab_width(),
+          )
+          .0
+        } else {
+          ((editor.top - constants::EDITOR_PADDING) / line_height).max(0.0) as usize
+        };
+
+        let offset = (row_top(row) - editor.top).clamp(0.0, preview.height - line_height);
+        let line = file.source_line(
+          wrapping::position_at(file.content(), row, 0, columns, file.tab_width()).line,
+        );
+
+        let index = file.block_at(line)?;
+        let block = &file.markdown()[index];
+        let top = *layout.blocks.get(index)?;
+        let bottom = layout.block_bottom(index)?;
+        let fraction = ((line - block.lines.start) as f32 / block.lines.len() as f32).min(1.0);
+
+        Some((preview::ID, top + (bottom - top) * fraction - offset))
+      }
+      ScrollSync::Preview | ScrollSync::Block(_) => {
+        let (index, offset) = match sync {
+          ScrollSync::Block(index) => (index, *layout.blocks.get(index)? - preview.top),
+          _ => (
+            layout
+              .blocks
+              .partition_point(|top| *top <= preview.top)
+              .checked_sub(1)?,
+            0.0,
+          ),
+        };
+
+        let block = file.markdown().get(index)?;
+        let top = *layout.blocks.get(index)?;
+        let bottom = layout.block_bottom(index)?;
+
+        let fraction = if sync == ScrollSync::Preview && bottom > top {
+          ((preview.top - top) / (bottom - top)).min(1.0)
+        } else {
+          0.0
+        };
+
+        let line = block.lines.start + (block.lines.len() as f32 * fraction) as usize;
+        let position = text_editor::Position {
+          line: file.content_line(line),
+          column: 0,
+        };
+
+        let row = wrapping::locate(file.content(), position, columns, file.tab_width()).0;
+        let top = row_top(row) - offset;
+
+        Some((
+          editor::ID,
+          (editor.content_height - editor.height).max(0.0) - top,
+        ))
+      }
+      ScrollSync::Heading(_) => None,
+    }
+  }
+
+  pub fn scroll_to_heading(&self, line: usize, layout: &Layout) -> Vec<(&'static str, f32)> {
+    let file = &self.files[self.current_file];
+    let mut targets = Vec::new();
+
+    if let Some(editor) = layout.editor {
+      let position = text_editor::Position {
+        line: file.content_line(line),
+        column: 0,
+      };
+
+      let columns = self.wrap_columns(editor.width);
+      let row = wrapping::locate(file.content(), position, columns, file.tab_width()).0;
+      let top = row as f32 * self.editor_font_size as f32 * constants::LINE_HEIGHT;
+
+      targets.push((
+        editor::ID,
+        ((editor.content_height - editor.height).max(0.0) - top).max(0.0),
+      ));
+    }
+
+    if layout.preview.is_some()
+      && let Some(top) = file
+        .block_at(line)
+        .and_then(|index| layout.blocks.get(index))
+    {
+      targets.push((preview::ID, *top));
+    }
+
+    targets
+  }
+
+  fn wrap_columns(&self, width: f32) -> usize {
+    if self.is_word_wrap_on {
+      let file = &self.files[self.current_file];
+      let width = width - gutter::width(file, self.editor_font_size, self.line_numbering());
+      wrapping::columns(width, self.editor_font_size)
+    } else {
+      usize::MAX
+    }
+  }
+
+  pub fn press_divider(&mut self) {
+    self.is_dragging_divider = true;
+  }
+
+  pub fn move_divider(&mut self, ratio: f32) {
+    if self.is_dragging_divider && matches!(self.mode, Mode::Split(_)) {
+      self.mode = Mode::Split(ratio.clamp(constants::MIN_SPLIT_RATIO, constants::MAX_SPLIT_RATIO));
+    }
+  }
+
+  pub fn release_divider(&mut self) {
+    self.is_dragging_divider = false;
+  }
+
   pub fn toggle_word_wrap(&mut self) {
     self.is_word_wrap_on = !self.is_word_wrap_on;
   }
 
-  pub fn is_word_wrap_on(&self) -> bool {
-    self.is_word_wrap_on
+  pub fn toggle_line_numbers(&mut self) {
+    self.is_line_numbers_on = !self.is_line_numbers_on;
+  }
+
+  pub fn toggle_relative_line_numbers(&mut self) {
+    self.is_relative_line_numbers_on = !self.is_relative_line_numbers_on;
+  }
+
+  pub fn toggle_whitespace(&mut self) {
+    self.is_whitespace_visible = !self.is_whitespace_visible;
+  }
+
+  pub fn line_numbering(&self) -> Numbering {
+    match (self.is_line_numbers_on, self.is_relative_line_numbers_on) {
+      (false, _) => Numbering::Hidden,
+      (true, false) => Numbering::Absolute,
+      (true, true) => Numbering::Relative,
+    }
+  }
+
+  pub fn editor_settings(&self) -> editor::Settings {
+    editor::Settings {
+      font_size: self.editor_font_size,
+      is_word_wrap_on: self.is_word_wrap_on,
+      numbering: self.line_numbering(),
+      is_whitespace_visible: self.is_whitespace_visible,
+      viewport: self.editor_viewport,
+    }
+  }
+
+  pub fn set_editor_viewport(&mut self, viewport: scrollable::Viewport) {
+    self.editor_viewport = Some(gutter::Viewport {
+      top: viewport.absolute_offset_reversed().y,
+      height: viewport.bounds().height,
+    });
   }
 
   pub fn files(&self) -> &[file::File] {
@@ -169,6 +1140,18 @@ impl State {
     self.selected_file_action
   }
 
+  pub fn selected_edit_action(&self) -> Option<EditAction> {
+    self.selected_edit_action
+  }
+
+  pub fn selected_line_action(&self) -> Option<LineAction> {
+    self.selected_line_action
+  }
+
+  pub fn selected_transform_action(&self) -> Option<TransformAction> {
+    self.selected_transform_action
+  }
+
   pub fn selected_view_action(&self) -> Option<ViewAction> {
     self.selected_view_action
   }
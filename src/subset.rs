@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ttf_parser::Face;
+
+const KEPT_TABLES: &[&[u8; 4]] = &[
+  b"OS/2", b"cvt ", b"fpgm", b"gasp", b"hhea", b"hmtx", b"maxp", b"name", b"prep",
+];
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+pub fn subset(font: &[u8], characters: impl IntoIterator<Item = char>) -> Result<Vec<u8>, String> {
+  let face = Face::parse(font, 0).map_err(|error| error.to_string())?;
+  let tables = tables(font)?;
+  let table = |tag: &[u8; 4]| {
+    tables
+      .get(tag)
+      .copied()
+      .ok_or_else(|| format!("Font has no {} table", String::from_utf8_lossy(tag)))
+  };
+
+  let head = table(b"head")?;
+  let loca = table(b"loca")?;
+  let glyf = table(b"glyf")?;
+  let post = table(b"post")?;
+
+  let is_long = read_u16(head, 50)? == 1;
+  let count = face.number_of_glyphs() as usize;
+  let offsets = (0..=count)
+    .map(|glyph| {
+      if is_long {
+        read_u32(loca, glyph * 4).map(|offset| offset as usize)
+      } else {
+        read_u16(loca, glyph * 2).map(|offset| offset as usize * 2)
+      }
+    })
+    .collect::<Result<Vec<usize>, String>>()?;
+
+  let glyph = |id: u16| -> Result<&[u8], String> {
+    let start = offsets[id as usize];
+    let end = offsets[id as usize + 1];
+
+    glyf
+      .get(start..end)
+      .ok_or_else(|| String::from("Glyph outside of the glyf table"))
+  };
+
+  let mapped: BTreeMap<char, u16> = characters
+    .into_iter()
+    .filter_map(|c| face.glyph_index(c).map(|id| (c, id.0)))
+    .collect();
+
+  let mut kept = BTreeSet::from([0]);
+  let mut pending: Vec<u16> = mapped.values().copied().collect();
+
+  while let Some(id) = pending.pop() {
+    if (id as usize) < count && kept.insert(id) {
+      pending.extend(components(glyph(id)?)?);
+    }
+  }
+
+  let mut glyphs = Vec::new();
+  let mut starts = Vec::with_capacity(count + 1);
+
+  for id in 0..count {
+    starts.push(glyphs.len());
+
+    if kept.contains(&(id as u16)) {
+      glyphs.extend_from_slice(glyph(id as u16)?);
+      glyphs.resize(glyphs.len().next_multiple_of(4), 0);
+    }
+  }
+
+  starts.push(glyphs.len());
+
+  let is_long = glyphs.len() / 2 > u16::MAX as usize;
+  let locations = starts
+    .into_iter()
+    .flat_map(|start| {
+      if is_long {
+        (start as u32).to_be_bytes().to_vec()
+      } else {
+        ((start / 2) as u16).to_be_bytes().to_vec()
+      }
+    })
+    .collect();
+
+  let mut head = head.to_vec();
+  head[8..12].fill(0);
+  head[50..52].copy_from_slice(&(is_long as u16).to_be_bytes());
+
+  let mut post = post
+    .get(..32)
+    .ok_or("Font has a truncated post table")?
+    .to_vec();
+  post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+
+  let mut output: BTreeMap<[u8; 4], Vec<u8>> = KEPT_TABLES
+    .iter()
+    .filter_map(|tag| Some((**tag, tables.get(*tag)?.to_vec())))
+    .collect();
+
+  output.insert(*b"cmap", cmap(&mapped));
+  output.insert(*b"glyf", glyphs);
+  output.insert(*b"head", head);
+  output.insert(*b"loca", locations);
+  output.insert(*b"post", post);
+
+  Ok(assemble(output))
+}
+
+fn tables(font: &[u8]) -> Result<BTreeMap<[u8; 4], &[u8]>, String> {
+  let count = read_u16(font, 4)? as usize;
+
+  (0..count)
+    .map(|index| {
+      let record = 12 + index * 16;
+      let tag = font
+        .get(record..record + 4)
+        .and_then(|tag| tag.try_into().ok())
+        .ok_or("Font has a truncated table directory")?;
+      let offset = read_u32(font, record + 8)? as usize;
+      let length = read_u32(font, record + 12)? as usize;
+      let data = font
+        .get(offset..offset + length)
+        .ok_or("Font table outside of the file")?;
+
+      Ok((tag, data))
+    })
+    .collect()
+}
+
+fn components(glyph: &[u8]) -> Result<Vec<u16>, String> {
+  if glyph.is_empty() || read_u16(glyph, 0)? as i16 >= 0 {
+    return Ok(Vec::new());
+  }
+
+  let mut components = Vec::new();
+  let mut offset = 10;
+
+  loop {
+    let flags = read_u16(glyph, offset)?;
+    components.push(read_u16(glyph, offset + 2)?);
+
+    offset += 4;
+    offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+      4
+    } else {
+      2
+    };
+
+    offset += if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+      8
+    } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+      4
+    } else if flags & WE_HAVE_A_SCALE != 0 {
+      2
+    } else {
+      0
+    };
+
+    if flags & MORE_COMPONENTS == 0 {
+      return Ok(components);
+    }
+  }
+}
+
+fn cmap(mapped: &BTreeMap<char, u16>) -> Vec<u8> {
+  let mut groups: Vec<(u32, u32, u16)> = Vec::new();
+
+  for (&c, &glyph) in mapped {
+    let c = c as u32;
+
+    match groups.last_mut() {
+      Some((start, end, first))
+        if *end + 1 == c && (*first as u32 + c - *start) == glyph as u32 =>
+      {
+        *end = c;
+      }
+      _ => groups.push((c, c, glyph)),
+    }
+  }
+
+  let basic: Vec<(u32, u32, u16)> = groups
+    .iter()
+    .filter(|(_, end, _)| *end < 0xFFFF)
+    .copied()
+    .chain([(0xFFFF, 0xFFFF, 0)])
+    .collect();
+
+  let mut subtables = Vec::new();
+
+  if basic.len() * 8 + 16 <= u16::MAX as usize {
+    subtables.push((1u16, segments(&basic)));
+  }
+
+  subtables.push((10u16, ranges(&groups)));
+
+  let mut cmap = Vec::new();
+  cmap.extend_from_slice(&0u16.to_be_bytes());
+  cmap.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+
+  let mut offset = 4 + subtables.len() * 8;
+
+  for (encoding, data) in &subtables {
+    cmap.extend_from_slice(&3u16.to_be_bytes());
+    cmap.extend_from_slice(&encoding.to_be_bytes());
+    cmap.extend_from_slice(&(offset as u32).to_be_bytes());
+    offset += data.len();
+  }
+
+  for (_, data) in subtables {
+    cmap.extend_from_slice(&data);
+  }
+
+  cmap
+}
+
+fn segments(groups: &[(u32, u32, u16)]) -> Vec<u8> {
+  let count = groups.len() as u16;
+  let power = 1u16 << count.ilog2();
+
+  let mut data = Vec::with_capacity(16 + groups.len() * 8);
+
+  for value in [
+    4,
+    16 + count * 8,
+    0,
+    count * 2,
+    power * 2,
+    power.ilog2() as u16,
+    (count - power) * 2,
+  ] {
+    data.extend_from_slice(&value.to_be_bytes());
+  }
+
+  for (_, end, _) in groups {
+    data.extend_from_slice(&(*end as u16).to_be_bytes());
+  }
+
+  data.extend_from_slice(&0u16.to_be_bytes());
+
+  for (start, _, _) in groups {
+    data.extend_from_slice(&(*start as u16).to_be_bytes());
+  }
+
+  for (start, _, glyph) in groups {
+    let delta = match *start {
+      0xFFFF => 1,
+      start => glyph.wrapping_sub(start as u16),
+    };
+
+    data.extend_from_slice(&delta.to_be_bytes());
+  }
+
+  data.extend(std::iter::repeat_n(0, groups.len() * 2));
+  data
+}
+
+fn ranges(groups: &[(u32, u32, u16)]) -> Vec<u8> {
+  let mut data = Vec::with_capacity(16 + groups.len() * 12);
+
+  data.extend_from_slice(&12u16.to_be_bytes());
+  data.extend_from_slice(&0u16.to_be_bytes());
+  data.extend_from_slice(&(16 + groups.len() as u32 * 12).to_be_bytes());
+  data.extend_from_slice(&0u32.to_be_bytes());
+  data.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+
+  for (start, end, glyph) in groups {
+    data.extend_from_slice(&start.to_be_bytes());
+    data.extend_from_slice(&end.to_be_bytes());
+    data.extend_from_slice(&(*glyph as u32).to_be_bytes());
+  }
+
+  data
+}
+
+fn assemble(tables: BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+  let count = tables.len() as u16;
+  let power = 1u16 << count.ilog2();
+
+  let mut font = 0x0001_0000u32.to_be_bytes().to_vec();
+
+  for value in [
+    count,
+    power * 16,
+    power.ilog2() as u16,
+    (count - power) * 16,
+  ] {
+    font.extend_from_slice(&value.to_be_bytes());
+  }
+
+  let mut offset = 12 + tables.len() * 16;
+
+  for (tag, data) in &tables {
+    font.extend_from_slice(tag);
+    font.extend_from_slice(&checksum(data).to_be_bytes());
+    font.extend_from_slice(&(offset as u32).to_be_bytes());
+    font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    offset += data.len().next_multiple_of(4);
+  }
+
+  let mut head = None;
+
+  for (tag, data) in &tables {
+    if tag == b"head" {
+      head = Some(font.len());
+    }
+
+    font.extend_from_slice(data);
+    font.resize(font.len().next_multiple_of(4), 0);
+  }
+
+  if let Some(head) = head {
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+    font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+  }
+
+  font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+  data.chunks(4).fold(0u32, |sum, chunk| {
+    let mut word = [0; 4];
+    word[..chunk.len()].copy_from_slice(chunk);
+    sum.wrapping_add(u32::from_be_bytes(word))
+  })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+  data
+    .get(offset..offset + 2)
+    .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    .ok_or_else(|| String::from("Font data is truncated"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+  data
+    .get(offset..offset + 4)
+    .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    .ok_or_else(|| String::from("Font data is truncated"))
+}
@@ -0,0 +1,472 @@
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::lines;
+use crate::message::TableAction;
+
+const MIN_WIDTH: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+  None,
+  Left,
+  Center,
+  Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+  pub lines: Vec<String>,
+  pub line: usize,
+  pub columns: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Table {
+  indent: String,
+  header: Vec<String>,
+  alignments: Vec<Alignment>,
+  rows: Vec<Vec<String>>,
+}
+
+impl Table {
+  fn parse(lines: &[String]) -> Option<Table> {
+    let alignments = delimiter(lines.get(1)?)?;
+    let header = cells(&lines[0]);
+    let rows: Vec<Vec<String>> = lines[2..].iter().map(|line| cells(line)).collect();
+
+    let count = rows
+      .iter()
+      .map(Vec::len)
+      .chain([header.len(), alignments.len()])
+      .max()
+      .unwrap_or(0);
+
+    let pad = |mut cells: Vec<String>| {
+      cells.resize(count, String::new());
+      cells
+    };
+
+    let mut alignments = alignments;
+    alignments.resize(count, Alignment::None);
+
+    Some(Table {
+      indent: lines[0][..lines[0].len() - lines[0].trim_start().len()].to_owned(),
+      header: pad(header),
+      alignments,
+      rows: rows.into_iter().map(pad).collect(),
+    })
+  }
+
+  fn columns(&self) -> usize {
+    self.alignments.len()
+  }
+
+  fn row(&self, row: usize) -> &[String] {
+    if row == 0 {
+      &self.header
+    } else {
+      &self.rows[row - 1]
+    }
+  }
+
+  fn format(&self) -> (Vec<String>, Vec<Vec<Range<usize>>>) {
+    let widths: Vec<usize> = (0..self.columns())
+      .map(|column| {
+        (0..=self.rows.len())
+          .map(|row| self.row(row)[column].width())
+          .fold(MIN_WIDTH, usize::max)
+      })
+      .collect();
+
+    let mut lines = Vec::with_capacity(self.rows.len() + 2);
+    let mut cells = Vec::with_capacity(self.rows.len() + 1);
+
+    for row in 0..=self.rows.len() {
+      let (line, ranges) = self.format_row(self.row(row), &widths);
+      lines.push(line);
+      cells.push(ranges);
+
+      if row == 0 {
+        lines.push(self.format_delimiter(&widths));
+      }
+    }
+
+    (lines, cells)
+  }
+
+  fn format_row(&self, row: &[String], widths: &[usize]) -> (String, Vec<Range<usize>>) {
+    let mut line = self.indent.clone();
+    let mut ranges = Vec::with_capacity(row.len());
+
+    for ((cell, width), alignment) in row.iter().zip(widths).zip(&self.alignments) {
+      let padding = width - cell.width();
+
+      let (left, right) = match alignment {
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::None | Alignment::Left => (0, padding),
+      };
+
+      line.push_str("| ");
+      line.push_str(&" ".repeat(left));
+      ranges.push(line.len()..line.len() + cell.len());
+      line.push_str(cell);
+      line.push_str(&" ".repeat(right));
+      line.push(' ');
+    }
+
+    line.push('|');
+    (line, ranges)
+  }
+
+  fn format_delimiter(&self, widths: &[usize]) -> String {
+    let mut line = self.indent.clone();
+
+    for (width, alignment) in widths.iter().zip(&self.alignments) {
+      let dashes = match alignment {
+        Alignment::None => "-".repeat(*width),
+        Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+        Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+        Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+      };
+
+      line.push_str("| ");
+      line.push_str(&dashes);
+      line.push(' ');
+    }
+
+    line.push('|');
+    line
+  }
+
+  fn edit(&self, row: usize, column: usize, offset: Option<usize>) -> Edit {
+    let (lines, cells) = self.format();
+    let row = row.min(self.rows.len());
+    let column = column.min(self.columns() - 1);
+    let range = cells[row][column].clone();
+    let line = if row == 0 { 0 } else { row + 1 };
+
+    let columns = match offset {
+      Some(offset) => {
+        let cell = &self.row(row)[column];
+        let offset = (0..=offset.min(cell.len()))
+          .rev()
+          .find(|offset| cell.is_char_boundary(*offset))
+          .unwrap_or(0);
+
+        range.start + offset..range.start + offset
+      }
+      None => range,
+    };
+
+    Edit {
+      lines,
+      line,
+      columns,
+    }
+  }
+}
+
+pub fn bounds(line: usize, text: impl Fn(usize) -> Option<String>) -> Option<Range<usize>> {
+  let is_row = |line: usize| text(line).is_some_and(|text| is_row(&text));
+
+  if !is_row(line) {
+    return None;
+  }
+
+  let mut start = line;
+  while start > 0 && is_row(start - 1) {
+    start -= 1;
+  }
+
+  let mut end = line + 1;
+  while is_row(end) {
+    end += 1;
+  }
+
+  let header = (start..end.saturating_sub(1)).rev().find(|&header| {
+    header <= line && text(header + 1).and_then(|text| delimiter(&text)).is_some()
+  })?;
+
+  Some(header..end)
+}
+
+pub fn apply(action: TableAction, lines: &[String], line: usize, position: usize) -> Option<Edit> {
+  let mut table = Table::parse(lines)?;
+  let row = line.saturating_sub(1);
+  let body = row.checked_sub(1);
+
+  let cells = split(&lines[line]);
+  let column = cell_at(&cells, position).min(table.columns() - 1);
+  let keep = Some(cells.get(column).map_or(0, |range| {
+    let cell = &lines[line][range.clone()];
+    position.saturating_sub(range.start + cell.len() - cell.trim_start().len())
+  }));
+
+  match action {
+    TableAction::Format => Some(table.edit(row, column, keep)),
+    TableAction::InsertRowAbove | TableAction::InsertRowBelow => {
+      let index = match (body, action) {
+        (None, _) => 0,
+        (Some(index), TableAction::InsertRowAbove) => index,
+        (Some(index), _) => index + 1,
+      };
+
+      table
+        .rows
+        .insert(index, vec![String::new(); table.columns()]);
+      Some(table.edit(index + 1, column, Some(0)))
+    }
+    TableAction::DeleteRow => {
+      let index = body?;
+      table.rows.remove(index);
+      Some(table.edit(row, column, keep))
+    }
+    TableAction::InsertColumnLeft | TableAction::InsertColumnRight => {
+      let index = if action == TableAction::InsertColumnLeft {
+        column
+      } else {
+        column + 1
+      };
+
+      table.header.insert(index, String::new());
+      table.alignments.insert(index, Alignment::None);
+
+      for cells in &mut table.rows {
+        cells.insert(index, String::new());
+      }
+
+      Some(table.edit(row, index, Some(0)))
+    }
+    TableAction::DeleteColumn => {
+      if table.columns() == 1 {
+        return None;
+      }
+
+      table.header.remove(column);
+      table.alignments.remove(column);
+
+      for cells in &mut table.rows {
+        cells.remove(column);
+      }
+
+      Some(table.edit(row, column, keep))
+    }
+    TableAction::MoveColumnLeft | TableAction::MoveColumnRight => {
+      let target = if action == TableAction::MoveColumnLeft {
+        column.checked_sub(1)?
+      } else {
+        Some(column + 1).filter(|target| *target < table.columns())?
+      };
+
+      table.header.swap(column, target);
+      table.alignments.swap(column, target);
+
+      for cells in &mut table.rows {
+        cells.swap(column, target);
+      }
+
+      Some(table.edit(row, target, keep))
+    }
+    TableAction::AlignNone
+    | TableAction::AlignLeft
+    | TableAction::AlignCenter
+    | TableAction::AlignRight => {
+      table.alignments[column] = match action {
+        TableAction::AlignLeft => Alignment::Left,
+        TableAction::AlignCenter => Alignment::Center,
+        TableAction::AlignRight => Alignment::Right,
+        _ => Alignment::None,
+      };
+
+      Some(table.edit(row, column, keep))
+    }
+    TableAction::SortAscending | TableAction::SortDescending => {
+      let is_descending = action == TableAction::SortDescending;
+
+      table.rows.sort_by(|a, b| {
+        let (a, b) = (&a[column], &b[column]);
+        let ordering = lines::natural_cmp(a, b);
+
+        a.is_empty().cmp(&b.is_empty()).then(if is_descending {
+          ordering.reverse()
+        } else {
+          ordering
+        })
+      });
+
+      Some(table.edit(row, column, keep))
+    }
+    TableAction::NextCell => {
+      let (row, column) = if column + 1 < table.columns() {
+        (row, column + 1)
+      } else if row < table.rows.len() {
+        (row + 1, 0)
+      } else {
+        table.rows.push(vec![String::new(); table.columns()]);
+        (row + 1, 0)
+      };
+
+      Some(table.edit(row, column, None))
+    }
+    TableAction::PreviousCell => {
+      let (row, column) = match (row, column) {
+        (0, 0) => (0, 0),
+        (row, 0) => (row - 1, table.columns() - 1),
+        (row, column) => (row, column - 1),
+      };
+
+      Some(table.edit(row, column, None))
+    }
+  }
+}
+
+fn is_row(line: &str) -> bool {
+  !split(line).is_empty() && line.contains('|')
+}
+
+fn delimiter(line: &str) -> Option<Vec<Alignment>> {
+  let cells = cells(line);
+
+  if cells.is_empty() || !line.contains('-') {
+    return None;
+  }
+
+  cells
+    .iter()
+    .map(|cell| {
+      let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+
+      if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+        return None;
+      }
+
+      Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => Alignment::Center,
+        (true, false) => Alignment::Left,
+        (false, true) => Alignment::Right,
+        (false, false) => Alignment::None,
+      })
+    })
+    .collect()
+}
+
+fn cells(line: &str) -> Vec<String> {
+  split(line)
+    .into_iter()
+    .map(|range| line[range].trim().to_owned())
+    .collect()
+}
+
+fn cell_at(cells: &[Range<usize>], column: usize) -> usize {
+  cells
+    .iter()
+    .position(|range| column <= range.end)
+    .unwrap_or(usize::MAX)
+}
+
+fn split(line: &str) -> Vec<Range<usize>> {
+  let start = line.len() - line.trim_start().len();
+  let end = line.trim_end().len();
+
+  if start >= end {
+    return Vec::new();
+  }
+
+  let mut separators = Vec::new();
+  let mut is_escaped = false;
+  let mut is_code = false;
+
+  for (index, c) in line[start..end].char_indices() {
+    match c {
+      '|' if !is_escaped && !is_code => separators.push(start + index),
+      '`' if !is_escaped => is_code = !is_code,
+      _ => {}
+    }
+
+    is_escaped = c == '\\' && !is_escaped;
+  }
+
+  let mut bounds = Vec::with_capacity(separators.len() + 2);
+
+  if separators.first() != Some(&start) {
+    bounds.push(start);
+  }
+
+  for separator in &separators {
+    bounds.push(*separator + 1);
+  }
+
+  let mut ranges: Vec<Range<usize>> = bounds
+    .iter()
+    .map(|&cell| {
+      let next = separators
+        .iter()
+        .copied()
+        .find(|separator| *separator >= cell)
+        .unwrap_or(end);
+
+      cell..next
+    })
+    .collect();
+
+  if separators.last() == Some(&(end - 1)) {
+    ranges.pop();
+  }
+
+  ranges
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn format(lines: &[&str]) -> Vec<String> {
+    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    apply(TableAction::Format, &lines, 0, 0).unwrap().lines
+  }
+
+  #[test]
+  fn pads_cells_to_the_widest_in_each_column() {
+    assert_eq!(
+      format(&["|a|b|c|", "|-|:-:|-:|", "|long|x|y|"]),
+      [
+        "| a    |  b  |   c |",
+        "| ---- | :-: | --: |",
+        "| long |  x  |   y |",
+      ],
+    );
+  }
+
+  #[test]
+  fn measures_wide_characters_by_display_width() {
+    assert_eq!(
+      format(&["| 名前 | b |", "|---|---|", "| abc | é |"]),
+      ["| 名前 | b   |", "| ---- | --- |", "| abc  | é   |",],
+    );
+  }
+
+  #[test]
+  fn fills_missing_cells() {
+    assert_eq!(
+      format(&["| a | b |", "| --- |", "| c |"]),
+      ["| a   | b   |", "| --- | --- |", "| c   |     |"],
+    );
+  }
+
+  #[test]
+  fn next_cell_appends_a_row_after_the_last_cell() {
+    let lines: Vec<String> = ["| a | b |", "| --- | --- |", "| c | d |"]
+      .iter()
+      .map(|line| line.to_string())
+      .collect();
+
+    let edit = apply(TableAction::NextCell, &lines, 2, 7).unwrap();
+
+    assert_eq!(edit.lines.last().unwrap(), "|     |     |");
+    assert_eq!(edit.line, 3);
+    assert_eq!(edit.columns, 2..2);
+  }
+}
@@ -0,0 +1,424 @@
+use crate::message::TransformAction;
+
+const BASE64_ALPHABET: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn apply(action: TransformAction, text: &str) -> Option<String> {
+  match action {
+    TransformAction::Upper => Some(text.to_uppercase()),
+    TransformAction::Lower => Some(text.to_lowercase()),
+    TransformAction::Title => Some(title_case(text)),
+    TransformAction::Sentence => Some(sentence_case(text)),
+    TransformAction::Snake => Some(per_line(text, |line| words(line).join("_"))),
+    TransformAction::Kebab => Some(per_line(text, |line| words(line).join("-"))),
+    TransformAction::Camel => Some(per_line(text, camel_case)),
+    TransformAction::Base64Encode => Some(base64_encode(text.as_bytes())),
+    TransformAction::Base64Decode => String::from_utf8(base64_decode(text)?).ok(),
+    TransformAction::UrlEncode => Some(url_encode(text)),
+    TransformAction::UrlDecode => url_decode(text),
+    TransformAction::JsonEscape => Some(json_escape(text)),
+    TransformAction::JsonUnescape => json_unescape(text),
+    TransformAction::HtmlEncode => Some(html_encode(text)),
+    TransformAction::HtmlDecode => Some(html_decode(text)),
+  }
+}
+
+fn per_line(text: &str, transform: impl Fn(&str) -> String) -> String {
+  text
+    .split('\n')
+    .map(|line| match line.strip_suffix('\r') {
+      Some(line) => transform(line) + "\r",
+      None => transform(line),
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn capitalize(word: &str) -> String {
+  let mut chars = word.chars();
+
+  match chars.next() {
+    Some(first) => first
+      .to_uppercase()
+      .chain(chars.flat_map(char::to_lowercase))
+      .collect(),
+    None => String::new(),
+  }
+}
+
+fn title_case(text: &str) -> String {
+  let mut result = String::with_capacity(text.len());
+  let mut word = String::new();
+
+  for c in text.chars() {
+    if c.is_whitespace() {
+      result.push_str(&capitalize(&word));
+      result.push(c);
+      word.clear();
+    } else {
+      word.push(c);
+    }
+  }
+
+  result.push_str(&capitalize(&word));
+  result
+}
+
+fn sentence_case(text: &str) -> String {
+  let mut result = String::with_capacity(text.len());
+  let mut starts_sentence = true;
+
+  for c in text.chars() {
+    if starts_sentence && c.is_alphanumeric() {
+      result.extend(c.to_uppercase());
+      starts_sentence = false;
+    } else {
+      result.extend(c.to_lowercase());
+    }
+
+    if matches!(c, '.' | '!' | '?') {
+      starts_sentence = true;
+    }
+  }
+
+  result
+}
+
+fn words(text: &str) -> Vec<String> {
+  let chars: Vec<char> = text.chars().collect();
+  let mut words = Vec::new();
+  let mut word = String::new();
+
+  for (index, &c) in chars.iter().enumerate() {
+    if !c.is_alphanumeric() {
+      if !word.is_empty() {
+        words.push(std::mem::take(&mut word));
+      }
+
+      continue;
+    }
+
+    let previous = index.checked_sub(1).map(|i| chars[i]);
+    let next = chars.get(index + 1).copied();
+
+    let is_boundary = c.is_uppercase()
+      && previous.is_some_and(|p| {
+        p.is_lowercase()
+          || p.is_numeric()
+          || (p.is_uppercase() && next.is_some_and(char::is_lowercase))
+      });
+
+    if is_boundary && !word.is_empty() {
+      words.push(std::mem::take(&mut word));
+    }
+
+    word.extend(c.to_lowercase());
+  }
+
+  if !word.is_empty() {
+    words.push(word);
+  }
+
+  words
+}
+
+fn camel_case(text: &str) -> String {
+  words(text)
+    .iter()
+    .enumerate()
+    .map(|(index, word)| {
+      if index == 0 {
+        word.clone()
+      } else {
+        capitalize(word)
+      }
+    })
+    .collect()
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+  let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+  for chunk in bytes.chunks(3) {
+    let triple = (chunk[0] as u32) << 16
+      | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+      | chunk.get(2).copied().unwrap_or(0) as u32;
+
+    for index in 0..4 {
+      if index <= chunk.len() {
+        encoded.push(BASE64_ALPHABET[(triple >> (18 - index * 6) & 0x3F) as usize] as char);
+      } else {
+        encoded.push('=');
+      }
+    }
+  }
+
+  encoded
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+  let symbols: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+  let data = symbols
+    .iter()
+    .position(|&c| c == b'=')
+    .map_or(&symbols[..], |index| &symbols[..index]);
+  let padding = symbols.len() - data.len();
+
+  if padding > 2 || symbols[data.len()..].iter().any(|&c| c != b'=') {
+    return None;
+  }
+
+  if (padding > 0 && !symbols.len().is_multiple_of(4)) || data.len() % 4 == 1 {
+    return None;
+  }
+
+  let is_url_safe = data.iter().any(|&c| c == b'-' || c == b'_');
+  let mut decoded = Vec::with_capacity(data.len() / 4 * 3);
+  let mut buffer = 0u32;
+  let mut bits = 0;
+
+  for &c in data {
+    let value = match c {
+      b'+' | b'/' if is_url_safe => return None,
+      b'-' => 62,
+      b'_' => 63,
+      _ => BASE64_ALPHABET.iter().position(|&b| b == c)? as u32,
+    };
+
+    buffer = buffer << 6 | value;
+    bits += 6;
+
+    if bits >= 8 {
+      bits -= 8;
+      decoded.push((buffer >> bits & 0xFF) as u8);
+    }
+  }
+
+  if buffer & ((1 << bits) - 1) != 0 {
+    return None;
+  }
+
+  Some(decoded)
+}
+
+fn url_encode(text: &str) -> String {
+  let mut encoded = String::with_capacity(text.len());
+
+  for byte in text.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        encoded.push(byte as char)
+      }
+      _ => encoded.push_str(&format!("%{byte:02X}")),
+    }
+  }
+
+  encoded
+}
+
+fn url_decode(text: &str) -> Option<String> {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+
+  while index < bytes.len() {
+    if bytes[index] == b'%' {
+      let hex = text.get(index + 1..index + 3)?;
+      decoded.push(u8::from_str_radix(hex, 16).ok()?);
+      index += 3;
+    } else {
+      decoded.push(bytes[index]);
+      index += 1;
+    }
+  }
+
+  String::from_utf8(decoded).ok()
+}
+
+pub fn json_escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '"' => escaped.push_str("\\\""),
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      '\t' => escaped.push_str("\\t"),
+      '\u{08}' => escaped.push_str("\\b"),
+      '\u{0C}' => escaped.push_str("\\f"),
+      c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+      c => escaped.push(c),
+    }
+  }
+
+  escaped
+}
+
+pub fn json_unescape(text: &str) -> Option<String> {
+  let mut unescaped = String::with_capacity(text.len());
+  let mut chars = text.chars();
+
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      unescaped.push(c);
+      continue;
+    }
+
+    match chars.next()? {
+      '"' => unescaped.push('"'),
+      '\\' => unescaped.push('\\'),
+      '/' => unescaped.push('/'),
+      'n' => unescaped.push('\n'),
+      'r' => unescaped.push('\r'),
+      't' => unescaped.push('\t'),
+      'b' => unescaped.push('\u{08}'),
+      'f' => unescaped.push('\u{0C}'),
+      'u' => {
+        let high = hex_code(&mut chars)?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+          if chars.next()? != '\\' || chars.next()? != 'u' {
+            return None;
+          }
+
+          let low = hex_code(&mut chars)?;
+          0x10000 + ((high - 0xD800) << 10) + low.checked_sub(0xDC00)?
+        } else {
+          high
+        };
+
+        unescaped.push(char::from_u32(code)?);
+      }
+      _ => return None,
+    }
+  }
+
+  Some(unescaped)
+}
+
+fn hex_code(chars: &mut std::str::Chars<'_>) -> Option<u32> {
+  let hex: String = chars.by_ref().take(4).collect();
+
+  if hex.len() != 4 {
+    return None;
+  }
+
+  u32::from_str_radix(&hex, 16).ok()
+}
+
+pub fn html_encode(text: &str) -> String {
+  let mut encoded = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '&' => encoded.push_str("&amp;"),
+      '<' => encoded.push_str("&lt;"),
+      '>' => encoded.push_str("&gt;"),
+      '"' => encoded.push_str("&quot;"),
+      '\'' => encoded.push_str("&#39;"),
+      c => encoded.push(c),
+    }
+  }
+
+  encoded
+}
+
+fn html_decode(text: &str) -> String {
+  let mut decoded = String::with_capacity(text.len());
+  let mut rest = text;
+
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    let entity = rest
+      .find(';')
+      .filter(|&end| end <= 10)
+      .and_then(|end| Some((entity(&rest[1..end])?, end)));
+
+    match entity {
+      Some((c, end)) => {
+        decoded.push(c);
+        rest = &rest[end + 1..];
+      }
+      None => {
+        decoded.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+
+  decoded.push_str(rest);
+  decoded
+}
+
+fn entity(name: &str) -> Option<char> {
+  match name {
+    "amp" => Some('&'),
+    "lt" => Some('<'),
+    "gt" => Some('>'),
+    "quot" => Some('"'),
+    "apos" => Some('\''),
+    "nbsp" => Some('\u{A0}'),
+    _ => {
+      let number = name.strip_prefix('#')?;
+
+      let code = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => number.parse().ok()?,
+      };
+
+      char::from_u32(code)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_base64() {
+    for text in ["", "f", "fo", "foo", "foob", "fooba", "foobar", "naïve ✓"] {
+      assert_eq!(
+        base64_decode(&base64_encode(text.as_bytes())).unwrap(),
+        text.as_bytes()
+      );
+    }
+  }
+
+  #[test]
+  fn decodes_base64_without_padding_and_across_lines() {
+    assert_eq!(base64_decode("Zm9vYg").unwrap(), b"foob");
+    assert_eq!(base64_decode("Zm9v\nYmFy\n").unwrap(), b"foobar");
+    assert_eq!(base64_decode("Zm9vYg =\n=").unwrap(), b"foob");
+    assert_eq!(base64_decode("-_8").unwrap(), [0xFB, 0xFF]);
+  }
+
+  #[test]
+  fn rejects_malformed_base64() {
+    for text in [
+      "Zm9v!",
+      "Z",
+      "Zm9vY",
+      "Zm=9v",
+      "Zm9vYg=",
+      "Zm9vYg===",
+      "Zm9=",
+      "Zh==",
+      "+_8=",
+    ] {
+      assert_eq!(base64_decode(text), None, "{text}");
+    }
+  }
+
+  #[test]
+  fn leaves_the_selection_alone_when_decoding_fails() {
+    assert_eq!(apply(TransformAction::Base64Decode, "not base64!"), None);
+    assert_eq!(
+      apply(TransformAction::Base64Decode, "aGVsbG8="),
+      Some(String::from("hello"))
+    );
+  }
+}
@@ -0,0 +1,127 @@
+use crate::cursors;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Cleanup {
+  pub trim_trailing_whitespace: bool,
+  pub insert_final_newline: bool,
+  pub trim_final_newlines: bool,
+}
+
+pub fn trailing_start(line: &str, is_markdown: bool) -> Option<usize> {
+  let end = line.trim_end_matches([' ', '\t']).len();
+
+  if end == line.len() {
+    return None;
+  }
+
+  let is_hard_break = is_markdown && end > 0 && line[end..].starts_with("  ");
+
+  (!is_hard_break).then_some(end)
+}
+
+pub fn clean(lines: &[String], cleanup: Cleanup, is_markdown: bool) -> Vec<String> {
+  let mut lines: Vec<String> = lines
+    .iter()
+    .map(|line| match trailing_start(line, is_markdown) {
+      Some(end) if cleanup.trim_trailing_whitespace => line[..end].to_owned(),
+      _ => line.clone(),
+    })
+    .collect();
+
+  if cleanup.trim_final_newlines {
+    while lines.len() > 1 && lines[lines.len() - 2..].iter().all(|l| l.trim().is_empty()) {
+      lines.pop();
+    }
+  }
+
+  let has_text = lines.iter().any(|line| !line.is_empty());
+
+  if cleanup.insert_final_newline && has_text && lines.last().is_some_and(|l| !l.is_empty()) {
+    lines.push(String::new());
+  }
+
+  lines
+}
+
+pub fn glyphs(
+  line: &str,
+  range: std::ops::Range<usize>,
+  trailing: Option<usize>,
+  tab_width: usize,
+) -> (String, String) {
+  let mut visible = String::new();
+  let mut highlighted = String::new();
+  let mut column = 0;
+
+  for (index, c) in line[range.clone()].char_indices() {
+    let next = cursors::advance(column, c, tab_width);
+    let glyph = match c {
+      ' ' => "·".to_owned(),
+      '\t' => "→".to_owned() + &" ".repeat(next - column - 1),
+      _ => " ".repeat(next - column),
+    };
+    let blank = " ".repeat(next - column);
+
+    if trailing.is_some_and(|start| range.start + index >= start) {
+      visible.push_str(&blank);
+      highlighted.push_str(&glyph);
+    } else {
+      visible.push_str(&glyph);
+      highlighted.push_str(&blank);
+    }
+
+    column = next;
+  }
+
+  (visible, highlighted)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(text: &str) -> Vec<String> {
+    text.split('\n').map(String::from).collect()
+  }
+
+  #[test]
+  fn finds_trailing_whitespace_but_keeps_markdown_hard_breaks() {
+    assert_eq!(trailing_start("text \t", false), Some(4));
+    assert_eq!(trailing_start("text", false), None);
+    assert_eq!(trailing_start("break  ", false), Some(5));
+    assert_eq!(trailing_start("break  ", true), None);
+    assert_eq!(trailing_start("single ", true), Some(6));
+    assert_eq!(trailing_start("   ", true), Some(0));
+  }
+
+  #[test]
+  fn cleans_trailing_whitespace_and_final_newlines() {
+    let cleanup = Cleanup {
+      trim_trailing_whitespace: true,
+      insert_final_newline: true,
+      trim_final_newlines: true,
+    };
+
+    assert_eq!(
+      clean(&lines("a  \nb \t\n\n\n"), cleanup, false),
+      lines("a\nb\n")
+    );
+    assert_eq!(clean(&lines("a  \nb"), cleanup, true), lines("a  \nb\n"));
+    assert_eq!(clean(&lines(""), cleanup, false), lines(""));
+  }
+
+  #[test]
+  fn leaves_lines_alone_without_cleanup() {
+    let text = lines("a  \n\n\n");
+
+    assert_eq!(clean(&text, Cleanup::default(), false), text);
+  }
+
+  #[test]
+  fn draws_spaces_and_tabs_at_their_columns() {
+    let (visible, highlighted) = glyphs("a\tb ", 0..4, Some(3), 4);
+
+    assert_eq!(visible, " →    ");
+    assert_eq!(highlighted, "     ·");
+  }
+}
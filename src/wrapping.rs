@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use iced::widget::text_editor::{Content, Position};
+
+use crate::{constants, cursors};
+
+pub fn columns(editor_width: f32, font_size: u32) -> usize {
+  let character_width = font_size as f32 * constants::CHARACTER_WIDTH;
+  ((editor_width - constants::EDITOR_PADDING * 2.0) / character_width).max(1.0) as usize
+}
+
+pub fn rows(line: &str, columns: usize, tab_width: usize) -> Vec<Range<usize>> {
+  let mut rows = Vec::new();
+  let mut start = 0;
+  let mut offset = 0;
+  let mut width = 0;
+
+  for word in line.split_inclusive(char::is_whitespace) {
+    let visible = word.trim_end();
+    let end = visible
+      .chars()
+      .fold(width, |width, c| cursors::advance(width, c, tab_width));
+
+    if width > 0 && end > columns {
+      rows.push(start..offset);
+      start = offset;
+      width = 0;
+    }
+
+    for (index, c) in word.char_indices() {
+      let mut next = cursors::advance(width, c, tab_width);
+
+      if width > 0 && next > columns && index < visible.len() {
+        rows.push(start..offset + index);
+        start = offset + index;
+        next = cursors::advance(0, c, tab_width);
+      }
+
+      width = next;
+    }
+
+    offset += word.len();
+  }
+
+  rows.push(start..line.len());
+  rows
+}
+
+pub fn locate(
+  content: &Content,
+  position: Position,
+  columns: usize,
+  tab_width: usize,
+) -> (usize, usize) {
+  let rows_above: usize = content
+    .lines()
+    .take(position.line)
+    .map(|line| rows(&line.text, columns, tab_width).len())
+    .sum();
+
+  let Some(line) = content.line(position.line) else {
+    return (rows_above, 0);
+  };
+
+  let (rank, start) = rows(&line.text, columns, tab_width)
+    .iter()
+    .enumerate()
+    .rfind(|(_, range)| range.start <= position.column)
+    .map_or((0, 0), |(rank, range)| (rank, range.start));
+
+  let column = cursors::visual_column(&line.text[start..], position.column - start, tab_width);
+
+  (rows_above + rank, column)
+}
+
+pub fn position_at(
+  content: &Content,
+  row: usize,
+  column: usize,
+  columns: usize,
+  tab_width: usize,
+) -> Position {
+  let mut remaining = row;
+
+  for (index, line) in content.lines().enumerate() {
+    let ranges = rows(&line.text, columns, tab_width);
+
+    if let Some(range) = ranges.get(remaining) {
+      let text = &line.text[range.clone()];
+
+      return Position {
+        line: index,
+        column: range.start + cursors::byte_column(text, column, false, tab_width),
+      };
+    }
+
+    remaining -= ranges.len();
+  }
+
+  let last = content.line_count().saturating_sub(1);
+
+  Position {
+    line: last,
+    column: content.line(last).map_or(0, |line| line.text.len()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn split(line: &str, columns: usize, tab_width: usize) -> Vec<&str> {
+    rows(line, columns, tab_width)
+      .into_iter()
+      .map(|range| &line[range])
+      .collect()
+  }
+
+  #[test]
+  fn wraps_at_word_boundaries() {
+    assert_eq!(split("one two three", 8, 4), ["one two ", "three"]);
+    assert_eq!(split("abcdefghij", 4, 4), ["abcd", "efgh", "ij"]);
+    assert_eq!(split("", 4, 4), [""]);
+  }
+
+  #[test]
+  fn counts_tab_stops() {
+    assert_eq!(split("\tab cd", 6, 4), ["\tab ", "cd"]);
+    assert_eq!(split("a\tb", 4, 4), ["a\t", "b"]);
+  }
+
+  #[test]
+  fn counts_wide_and_combining_characters() {
+    assert_eq!(split("日本語です", 4, 4), ["日本", "語で", "す"]);
+    assert_eq!(split("cafe\u{301} bar", 5, 4), ["cafe\u{301} ", "bar"]);
+  }
+
+  #[test]
+  fn locates_positions_across_rows() {
+    let content = Content::with_text("one two three\n\tx");
+
+    assert_eq!(
+      locate(
+        &content,
+        Position {
+          line: 0,
+          column: 10
+        },
+        8,
+        4
+      ),
+      (1, 2)
+    );
+    assert_eq!(
+      locate(&content, Position { line: 1, column: 2 }, 8, 4),
+      (2, 5)
+    );
+    assert_eq!(
+      position_at(&content, 1, 2, 8, 4),
+      Position {
+        line: 0,
+        column: 10
+      }
+    );
+    assert_eq!(
+      position_at(&content, 9, 0, 8, 4),
+      Position { line: 1, column: 2 }
+    );
+  }
+}